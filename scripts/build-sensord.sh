@@ -0,0 +1,27 @@
+#!/bin/sh
+# Build pulse-sensord and stage it in src-tauri/binaries/ as a Tauri
+# external binary. build.rs hashes the staged copy, so the app only ever
+# runs the sensord it was built with.
+#
+# Runs before `tauri build` on Linux (see tauri.linux.conf.json); run it
+# once by hand before `tauri dev`.
+#
+# Usage: scripts/build-sensord.sh [release|debug]
+set -eu
+
+profile="${1:-release}"
+cd "$(dirname "$0")/../src-tauri"
+
+triple="$(rustc -vV | sed -n 's/^host: //p')"
+flags=""
+if [ "$profile" = "release" ]; then
+    flags="--release"
+fi
+
+# The staged binary doesn't exist yet, so keep tauri-build from looking for it
+TAURI_CONFIG='{"bundle":{"externalBin":[]}}' \
+    cargo build $flags --bin pulse-sensord
+
+mkdir -p binaries
+cp "target/$profile/pulse-sensord" "binaries/pulse-sensord-$triple"
+echo "Staged binaries/pulse-sensord-$triple"
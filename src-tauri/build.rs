@@ -1,5 +1,10 @@
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
 fn main() {
-    // Embed Windows application manifest for UAC elevation
+    embed_sidecar_digest();
+
+    // Embed Windows application manifest (asInvoker, DPI awareness)
     #[cfg(windows)]
     {
         let mut windows = tauri_build::WindowsAttributes::new();
@@ -13,3 +18,48 @@ fn main() {
         tauri_build::build()
     }
 }
+
+/// Sidecar binary shipped in `binaries/` for the target
+/// (see tauri.conf.json and tauri.linux.conf.json)
+fn sidecar_binary(target: &str) -> Option<String> {
+    match std::env::var("CARGO_CFG_TARGET_OS").as_deref() {
+        Ok("windows") => Some("binaries/lhm-sidecar-x86_64-pc-windows-msvc.exe".to_string()),
+        // Staged by scripts/build-sensord.sh before the app is built
+        Ok("linux") => Some(format!("binaries/pulse-sensord-{}", target)),
+        _ => None,
+    }
+}
+
+/// Hash the sidecar binary so the app only ever spawns this exact build
+/// CI can pin the digest instead by setting PULSE_SIDECAR_SHA256
+fn embed_sidecar_digest() {
+    let target = std::env::var("TARGET").unwrap_or_default();
+    println!("cargo:rustc-env=PULSE_TARGET_TRIPLE={}", target);
+    println!("cargo:rerun-if-env-changed=PULSE_SIDECAR_SHA256");
+
+    let Some(sidecar) = sidecar_binary(&target) else {
+        return;
+    };
+    println!("cargo:rerun-if-changed={}", sidecar);
+
+    if let Ok(digest) = std::env::var("PULSE_SIDECAR_SHA256") {
+        println!("cargo:rustc-env=PULSE_SIDECAR_SHA256={}", digest.to_lowercase());
+        return;
+    }
+
+    match std::fs::read(Path::new(&sidecar)) {
+        Ok(bytes) => {
+            let digest: String = Sha256::digest(&bytes)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            println!("cargo:rustc-env=PULSE_SIDECAR_SHA256={}", digest);
+        }
+        Err(_) => {
+            println!(
+                "cargo:warning=Sidecar binary {} not found, release builds will refuse to spawn it",
+                sidecar
+            );
+        }
+    }
+}
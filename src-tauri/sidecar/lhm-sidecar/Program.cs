@@ -1,4 +1,8 @@
+using System.Diagnostics;
+using System.IO.Pipes;
 using System.Runtime.Versioning;
+using System.Security.AccessControl;
+using System.Security.Principal;
 using System.Text.Json;
 using System.Text.Json.Serialization;
 using LibreHardwareMonitor.Hardware;
@@ -18,11 +22,29 @@ public class HardwareData
     [JsonPropertyName("gpu")]
     public List<GpuData> Gpus { get; set; } = [];
 
+    [JsonPropertyName("sensors")]
+    public List<SensorData> Sensors { get; set; } = [];
+
     [JsonPropertyName("timestamp")]
     public long Timestamp { get; set; }
 
     [JsonPropertyName("error")]
-    public string? Error { get; set; }
+    public ErrorData? Error { get; set; }
+}
+
+/// <summary>
+/// Structured error - code is one of requires_admin, collection_failed, fatal
+/// </summary>
+public class ErrorData
+{
+    [JsonPropertyName("code")]
+    public string Code { get; set; } = "";
+
+    [JsonPropertyName("message")]
+    public string Message { get; set; } = "";
+
+    [JsonPropertyName("retryable")]
+    public bool Retryable { get; set; }
 }
 
 public class CpuData
@@ -39,9 +61,14 @@ public class CpuData
     [JsonPropertyName("core_temperatures")]
     public List<float?> CoreTemperatures { get; set; } = [];
 
+    /// <summary>Hottest core ("Core Max"), not TjMax</summary>
     [JsonPropertyName("max_temperature")]
     public float? MaxTemperature { get; set; }
 
+    /// <summary>Throttle temperature (TjMax), if the CPU reports it</summary>
+    [JsonPropertyName("tj_max")]
+    public float? TjMax { get; set; }
+
     [JsonPropertyName("power")]
     public float? Power { get; set; }
 
@@ -79,6 +106,42 @@ public class GpuData
     public float? Load { get; set; }
 }
 
+/// <summary>
+/// Generic sensor entry - one per sensor in the LibreHardwareMonitor tree
+/// </summary>
+public class SensorData
+{
+    [JsonPropertyName("id")]
+    public string Id { get; set; } = "";
+
+    [JsonPropertyName("hardware_id")]
+    public string HardwareId { get; set; } = "";
+
+    [JsonPropertyName("hardware_name")]
+    public string HardwareName { get; set; } = "";
+
+    [JsonPropertyName("hardware_type")]
+    public string HardwareType { get; set; } = "";
+
+    [JsonPropertyName("sensor_type")]
+    public string SensorType { get; set; } = "";
+
+    [JsonPropertyName("name")]
+    public string Name { get; set; } = "";
+
+    [JsonPropertyName("value")]
+    public float? Value { get; set; }
+
+    [JsonPropertyName("min")]
+    public float? Min { get; set; }
+
+    [JsonPropertyName("max")]
+    public float? Max { get; set; }
+
+    [JsonPropertyName("unit")]
+    public string Unit { get; set; } = "";
+}
+
 /// <summary>
 /// Source-generated JSON context for trimming-safe serialization
 /// </summary>
@@ -111,13 +174,134 @@ public class UpdateVisitor : IVisitor
     public void VisitParameter(IParameter parameter) { }
 }
 
+/// <summary>
+/// Sends JSON lines to every client connected to a named pipe, so the
+/// sidecar can run as a privileged collector for an unprivileged UI
+/// </summary>
+class PipeBroadcaster(string name)
+{
+    readonly List<StreamWriter> _clients = [];
+    readonly object _lock = new();
+
+    /// <summary>
+    /// Exit once the last client disconnects, set when serving a single app
+    /// </summary>
+    public bool ExitWhenIdle { get; init; }
+
+    public void Start()
+    {
+        new Thread(AcceptLoop) { IsBackground = true, Name = "pipe-accept" }.Start();
+    }
+
+    void AcceptLoop()
+    {
+        while (true)
+        {
+            try
+            {
+                var server = NamedPipeServerStreamAcl.Create(
+                    name,
+                    PipeDirection.Out,
+                    NamedPipeServerStream.MaxAllowedServerInstances,
+                    PipeTransmissionMode.Byte,
+                    PipeOptions.None,
+                    0,
+                    0,
+                    CreateSecurity());
+                server.WaitForConnection();
+
+                lock (_lock)
+                {
+                    _clients.Add(new StreamWriter(server) { AutoFlush = true });
+                    Program.Log("info", $"Pipe client connected ({_clients.Count} total)");
+                }
+            }
+            catch (Exception ex)
+            {
+                Program.Log("error", $"Pipe accept failed: {ex.GetType().Name}: {ex.Message}");
+                Thread.Sleep(1000);
+            }
+        }
+    }
+
+    public void WriteLine(string line)
+    {
+        lock (_lock)
+        {
+            for (int i = _clients.Count - 1; i >= 0; i--)
+            {
+                try
+                {
+                    _clients[i].WriteLine(line);
+                }
+                catch (IOException)
+                {
+                    // Client went away
+                    _clients[i].Dispose();
+                    _clients.RemoveAt(i);
+                    Program.Log("info", $"Pipe client disconnected ({_clients.Count} left)");
+
+                    if (ExitWhenIdle && _clients.Count == 0)
+                    {
+                        Program.Log("info", "Last pipe client gone, exiting");
+                        Environment.Exit(0);
+                    }
+                }
+            }
+        }
+    }
+
+    /// <summary>
+    /// Admins and SYSTEM own the pipe, other signed-in users may only read
+    /// </summary>
+    static PipeSecurity CreateSecurity()
+    {
+        var security = new PipeSecurity();
+        security.AddAccessRule(new PipeAccessRule(
+            new SecurityIdentifier(WellKnownSidType.BuiltinAdministratorsSid, null),
+            PipeAccessRights.FullControl,
+            AccessControlType.Allow));
+        security.AddAccessRule(new PipeAccessRule(
+            new SecurityIdentifier(WellKnownSidType.LocalSystemSid, null),
+            PipeAccessRights.FullControl,
+            AccessControlType.Allow));
+        security.AddAccessRule(new PipeAccessRule(
+            new SecurityIdentifier(WellKnownSidType.AuthenticatedUserSid, null),
+            PipeAccessRights.Read,
+            AccessControlType.Allow));
+        return security;
+    }
+}
+
 class Program
 {
+    /// <summary>
+    /// Set in --pipe mode, JSON lines go to pipe clients instead of stdout
+    /// </summary>
+    static PipeBroadcaster? _pipe;
+
+    /// <summary>
+    /// Hardware classes enabled when no --hardware flag is given
+    /// </summary>
+    static readonly string[] DefaultHardware =
+        ["cpu", "gpu", "memory", "motherboard", "storage", "controller", "psu"];
+
+    static readonly string[] KnownHardware =
+        ["cpu", "gpu", "memory", "motherboard", "storage", "network", "battery", "controller", "psu"];
+
+    /// <summary>
+    /// Hardware selection received on stdin, applied by the monitor loop
+    /// </summary>
+    static HashSet<string>? _pendingHardware;
+
     static void Main(string[] args)
     {
         // Parse arguments
         int intervalMs = 1000; // Default 1 second
         bool singleShot = false;
+        string? pipeName = null;
+        int? parentPid = null;
+        var hardware = new HashSet<string>(DefaultHardware);
 
         for (int i = 0; i < args.Length; i++)
         {
@@ -127,52 +311,139 @@ class Program
                     if (int.TryParse(args[++i], out int parsed))
                         intervalMs = Math.Max(100, parsed); // Minimum 100ms
                     break;
-                case "--single" or "-s":
+                case "--once" or "--single" or "-s":
                     singleShot = true;
                     break;
+                case "--pipe" or "-p" when i + 1 < args.Length:
+                    pipeName = args[++i];
+                    break;
+                case "--parent" when i + 1 < args.Length:
+                    if (int.TryParse(args[++i], out int parsedPid))
+                        parentPid = parsedPid;
+                    break;
+                case "--hardware" or "-w" when i + 1 < args.Length:
+                    if (TryParseHardware(args[++i], out var parsedHardware))
+                        hardware = parsedHardware;
+                    break;
                 case "--help" or "-h":
                     PrintHelp();
                     return;
             }
         }
 
+        Log("info", $"Starting (interval {intervalMs} ms, single shot: {singleShot}, hardware: {string.Join(",", hardware)})");
+
         // Check for admin rights
         if (!IsAdministrator())
         {
-            OutputError("Admin rights required: This application requires administrator privileges to access hardware sensors.");
+            Log("error", "Not running as administrator");
+            OutputError("requires_admin", "Admin rights required: This application requires administrator privileges to access hardware sensors.", retryable: false);
             return;
         }
 
+        if (parentPid is int pid)
+        {
+            new Thread(() => WatchParent(pid)) { IsBackground = true, Name = "parent-watch" }.Start();
+        }
+
+        if (pipeName != null && !singleShot)
+        {
+            _pipe = new PipeBroadcaster(pipeName) { ExitWhenIdle = parentPid != null };
+            _pipe.Start();
+            Log("info", $"Serving on \\\\.\\pipe\\{pipeName}");
+        }
+
         try
         {
-            RunMonitor(intervalMs, singleShot);
+            if (!singleShot)
+            {
+                new Thread(ReadCommands) { IsBackground = true, Name = "stdin-commands" }.Start();
+            }
+            RunMonitor(intervalMs, singleShot, hardware);
         }
         catch (Exception ex)
         {
-            OutputError($"Fatal error: {ex.Message}");
+            Log("error", $"Fatal error: {ex.GetType().Name}: {ex.Message}");
+            OutputError("fatal", $"Fatal error: {ex.Message}", retryable: true);
         }
     }
 
-    static void RunMonitor(int intervalMs, bool singleShot)
+    /// <summary>
+    /// Parse a comma-separated hardware class list such as "cpu,gpu,storage"
+    /// </summary>
+    static bool TryParseHardware(string list, out HashSet<string> classes)
     {
-        var computer = new Computer
+        classes = new HashSet<string>(
+            list.Split(',', StringSplitOptions.RemoveEmptyEntries | StringSplitOptions.TrimEntries)
+                .Select(c => c.ToLowerInvariant()));
+
+        var unknown = classes.Where(c => !KnownHardware.Contains(c)).ToList();
+        if (unknown.Count > 0 || classes.Count == 0)
         {
-            IsCpuEnabled = true,
-            IsGpuEnabled = true,
-            IsMemoryEnabled = false,  // We get this from sysinfo
-            IsMotherboardEnabled = false,
-            IsStorageEnabled = false,
-            IsNetworkEnabled = false,
-            IsBatteryEnabled = false,
-            IsControllerEnabled = false,
-            IsPsuEnabled = false
-        };
+            Log("warn", $"Ignoring hardware list \"{list}\": unknown class(es) {string.Join(", ", unknown)}, known: {string.Join(", ", KnownHardware)}");
+            return false;
+        }
+        return true;
+    }
+
+    /// <summary>
+    /// Enable exactly the given hardware classes
+    /// LibreHardwareMonitor opens/closes the hardware when a flag changes
+    /// </summary>
+    static void ApplyHardware(Computer computer, ISet<string> classes)
+    {
+        computer.IsCpuEnabled = classes.Contains("cpu");
+        computer.IsGpuEnabled = classes.Contains("gpu");
+        computer.IsMemoryEnabled = classes.Contains("memory");
+        computer.IsMotherboardEnabled = classes.Contains("motherboard");  // Voltages, fans, VRM temperatures
+        computer.IsStorageEnabled = classes.Contains("storage");
+        computer.IsNetworkEnabled = classes.Contains("network");
+        computer.IsBatteryEnabled = classes.Contains("battery");
+        computer.IsControllerEnabled = classes.Contains("controller");
+        computer.IsPsuEnabled = classes.Contains("psu");
+    }
+
+    /// <summary>
+    /// Read commands from stdin, one per line:
+    ///   hardware cpu,gpu,storage   - change the enabled hardware classes
+    /// </summary>
+    static void ReadCommands()
+    {
+        string? line;
+        while ((line = Console.In.ReadLine()) != null)
+        {
+            var parts = line.Trim().Split(' ', 2, StringSplitOptions.RemoveEmptyEntries);
+            if (parts.Length == 0)
+                continue;
+
+            switch (parts[0])
+            {
+                case "hardware" when parts.Length == 2:
+                    if (TryParseHardware(parts[1], out var classes))
+                        Interlocked.Exchange(ref _pendingHardware, classes);
+                    break;
+                default:
+                    Log("warn", $"Unknown command: {line}");
+                    break;
+            }
+        }
+    }
+
+    static void RunMonitor(int intervalMs, bool singleShot, ISet<string> enabledClasses)
+    {
+        var computer = new Computer();
+        ApplyHardware(computer, enabledClasses);
 
         try
         {
             computer.Open();
             var updateVisitor = new UpdateVisitor();
 
+            foreach (var hardware in computer.Hardware)
+            {
+                Log("info", $"Found {hardware.HardwareType}: {hardware.Name} ({hardware.Sensors.Length} sensors)");
+            }
+
             // Handle Ctrl+C gracefully
             Console.CancelKeyPress += (_, e) =>
             {
@@ -184,13 +455,25 @@ class Program
             {
                 try
                 {
+                    var pending = Interlocked.Exchange(ref _pendingHardware, null);
+                    if (pending != null)
+                    {
+                        ApplyHardware(computer, pending);
+                        Log("info", $"Hardware changed to {string.Join(",", pending)}");
+                        foreach (var hw in computer.Hardware)
+                        {
+                            Log("info", $"Found {hw.HardwareType}: {hw.Name} ({hw.Sensors.Length} sensors)");
+                        }
+                    }
+
                     computer.Accept(updateVisitor);
                     var data = CollectData(computer);
                     OutputJson(data);
                 }
                 catch (Exception ex)
                 {
-                    OutputError($"Collection error: {ex.Message}");
+                    Log("warn", $"Collection error: {ex.GetType().Name}: {ex.Message}");
+                    OutputError("collection_failed", $"Collection error: {ex.Message}", retryable: true);
                 }
 
                 if (!singleShot)
@@ -214,6 +497,8 @@ class Program
 
         foreach (var hardware in computer.Hardware)
         {
+            CollectSensors(hardware, data.Sensors);
+
             switch (hardware.HardwareType)
             {
                 case HardwareType.Cpu:
@@ -231,10 +516,63 @@ class Program
         return data;
     }
 
+    /// <summary>
+    /// Flatten all sensors of a hardware item and its sub-hardware
+    /// (e.g. the SuperIO chip below the motherboard)
+    /// </summary>
+    static void CollectSensors(IHardware hardware, List<SensorData> sensors)
+    {
+        foreach (var sensor in hardware.Sensors)
+        {
+            sensors.Add(new SensorData
+            {
+                Id = sensor.Identifier.ToString(),
+                HardwareId = hardware.Identifier.ToString(),
+                HardwareName = hardware.Name,
+                HardwareType = hardware.HardwareType.ToString(),
+                SensorType = sensor.SensorType.ToString(),
+                Name = sensor.Name,
+                Value = sensor.Value,
+                Min = sensor.Min,
+                Max = sensor.Max,
+                Unit = UnitFor(sensor.SensorType)
+            });
+        }
+
+        foreach (var subHardware in hardware.SubHardware)
+        {
+            CollectSensors(subHardware, sensors);
+        }
+    }
+
+    static string UnitFor(SensorType type) => type switch
+    {
+        SensorType.Voltage => "V",
+        SensorType.Current => "A",
+        SensorType.Power => "W",
+        SensorType.Clock => "MHz",
+        SensorType.Temperature => "°C",
+        SensorType.Load => "%",
+        SensorType.Frequency => "Hz",
+        SensorType.Fan => "RPM",
+        SensorType.Flow => "L/h",
+        SensorType.Control => "%",
+        SensorType.Level => "%",
+        SensorType.Humidity => "%",
+        SensorType.Data => "GB",
+        SensorType.SmallData => "MB",
+        SensorType.Throughput => "B/s",
+        SensorType.TimeSpan => "s",
+        SensorType.Energy => "mWh",
+        SensorType.Noise => "dBA",
+        _ => ""
+    };
+
     static CpuData CollectCpuData(IHardware hardware)
     {
         var cpu = new CpuData { Name = hardware.Name };
         var coreTemps = new SortedDictionary<int, float?>();
+        var coreDistances = new SortedDictionary<int, float?>();
         var corePowers = new SortedDictionary<int, float?>();
 
         foreach (var sensor in hardware.Sensors)
@@ -244,7 +582,14 @@ class Program
             switch (sensor.SensorType)
             {
                 case SensorType.Temperature:
-                    if (name.Contains("package") || name.Contains("cpu total"))
+                    // Must come first - "CPU Core #1 Distance to TjMax" also
+                    // matches the "max" and "cpu core" checks below
+                    if (name.Contains("distance to tjmax"))
+                    {
+                        if (TryParseCoreNumber(name, out int coreNum))
+                            coreDistances[coreNum] = sensor.Value;
+                    }
+                    else if (name.Contains("package") || name.Contains("cpu total"))
                     {
                         cpu.PackageTemperature = sensor.Value;
                     }
@@ -254,16 +599,12 @@ class Program
                     }
                     else if (name.StartsWith("cpu core") || name.StartsWith("core"))
                     {
-                        // Extract core number
-                        var parts = name.Split(' ', '#');
-                        foreach (var part in parts)
-                        {
-                            if (int.TryParse(part, out int coreNum))
-                            {
-                                coreTemps[coreNum] = sensor.Value;
-                                break;
-                            }
-                        }
+                        if (TryParseCoreNumber(name, out int coreNum))
+                            coreTemps[coreNum] = sensor.Value;
+
+                        // Intel exposes TjMax as a parameter of the core sensors
+                        cpu.TjMax ??= sensor.Parameters
+                            .FirstOrDefault(p => p.Name.StartsWith("TjMax"))?.Value;
                     }
                     break;
 
@@ -274,20 +615,19 @@ class Program
                     }
                     else if (name.StartsWith("cpu core") || name.StartsWith("core"))
                     {
-                        var parts = name.Split(' ', '#');
-                        foreach (var part in parts)
-                        {
-                            if (int.TryParse(part, out int coreNum))
-                            {
-                                corePowers[coreNum] = sensor.Value;
-                                break;
-                            }
-                        }
+                        if (TryParseCoreNumber(name, out int coreNum))
+                            corePowers[coreNum] = sensor.Value;
                     }
                     break;
             }
         }
 
+        // Fall back to core temperature + distance to TjMax
+        cpu.TjMax ??= coreDistances
+            .Where(d => d.Value.HasValue && coreTemps.GetValueOrDefault(d.Key).HasValue)
+            .Select(d => (float?)(coreTemps[d.Key]!.Value + d.Value!.Value))
+            .FirstOrDefault();
+
         // Set primary temperature (package or max of cores)
         cpu.Temperature = cpu.PackageTemperature
             ?? cpu.MaxTemperature
@@ -313,6 +653,21 @@ class Program
         return cpu;
     }
 
+    /// <summary>
+    /// Extract the core number from names like "cpu core #3" or "core 3"
+    /// </summary>
+    static bool TryParseCoreNumber(string name, out int coreNum)
+    {
+        foreach (var part in name.Split(' ', '#'))
+        {
+            if (int.TryParse(part, out coreNum))
+                return true;
+        }
+
+        coreNum = 0;
+        return false;
+    }
+
     static GpuData CollectGpuData(IHardware hardware)
     {
         var gpu = new GpuData
@@ -369,6 +724,26 @@ class Program
         return gpu;
     }
 
+    /// <summary>
+    /// Exit when the app that launched us exits, so an elevated sidecar
+    /// started through UAC doesn't outlive it
+    /// </summary>
+    static void WatchParent(int pid)
+    {
+        try
+        {
+            using var parent = Process.GetProcessById(pid);
+            parent.WaitForExit();
+        }
+        catch (ArgumentException)
+        {
+            // Already gone
+        }
+
+        Log("info", $"Parent process {pid} exited, exiting");
+        Environment.Exit(0);
+    }
+
     static bool IsAdministrator()
     {
         using var identity = System.Security.Principal.WindowsIdentity.GetCurrent();
@@ -379,20 +754,35 @@ class Program
     static void OutputJson(HardwareData data)
     {
         var json = JsonSerializer.Serialize(data, AppJsonContext.Default.HardwareData);
+        if (_pipe != null)
+        {
+            _pipe.WriteLine(json);
+            return;
+        }
         Console.WriteLine(json);
         Console.Out.Flush();
     }
 
-    static void OutputError(string message)
+    static void OutputError(string code, string message, bool retryable)
     {
         var data = new HardwareData
         {
             Timestamp = DateTimeOffset.UtcNow.ToUnixTimeMilliseconds(),
-            Error = message
+            Error = new ErrorData { Code = code, Message = message, Retryable = retryable }
         };
         OutputJson(data);
     }
 
+    /// <summary>
+    /// Write a diagnostic line to stderr as "[level] message"
+    /// stdout is reserved for the JSON protocol
+    /// </summary>
+    internal static void Log(string level, string message)
+    {
+        Console.Error.WriteLine($"[{level}] {message}");
+        Console.Error.Flush();
+    }
+
     static void PrintHelp()
     {
         Console.WriteLine("""
@@ -402,10 +792,23 @@ class Program
             
             Options:
               -i, --interval <ms>   Update interval in milliseconds (default: 1000, min: 100)
-              -s, --single          Single shot mode - output once and exit
+              -s, --single, --once  Single shot mode - output once and exit
+              -p, --pipe <name>     Serve JSON lines on \\.\pipe\<name> instead of stdout,
+                                    e.g. as a long-lived elevated collector
+                  --parent <pid>    Exit when process <pid> exits or, with --pipe,
+                                    when the last pipe client disconnects
+              -w, --hardware <list> Comma-separated hardware classes to read (default:
+                                    cpu,gpu,memory,motherboard,storage,controller,psu)
+                                    known: cpu, gpu, memory, motherboard, storage,
+                                    network, battery, controller, psu
               -h, --help            Show this help message
             
+            Commands (stdin, one per line):
+              hardware <list>       Change the hardware classes without restarting
+            
             Output: JSON lines to stdout with CPU and GPU temperature/power data
+                    plus every sensor of the enabled hardware in "sensors",
+                    diagnostics as "[level] message" lines to stderr
             
             Note: Requires administrator privileges to access hardware sensors.
             """);
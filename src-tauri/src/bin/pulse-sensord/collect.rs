@@ -0,0 +1,606 @@
+//! Builds one `HardwareData` sample from sysfs
+
+use std::collections::{HashMap, HashSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::energy::EnergyMeter;
+use crate::protocol::{CpuData, GpuData, HardwareData, SensorData};
+use crate::sysfs::{self, DrmCard, HwmonChip, ReadingKind, Sysfs};
+
+/// Hardware classes understood by `--hardware`, same names as lhm-sidecar
+pub const KNOWN_CLASSES: [&str; 9] = [
+    "cpu",
+    "gpu",
+    "memory",
+    "motherboard",
+    "storage",
+    "network",
+    "battery",
+    "controller",
+    "psu",
+];
+
+/// LHM hardware class and type of a hwmon driver
+fn chip_class(name: &str) -> (&'static str, &'static str) {
+    match name {
+        "coretemp" | "k10temp" | "zenpower" | "amd_energy" | "cpu_thermal" => ("cpu", "Cpu"),
+        "amdgpu" | "radeon" => ("gpu", "GpuAmd"),
+        "nouveau" => ("gpu", "GpuNvidia"),
+        "i915" | "xe" => ("gpu", "GpuIntel"),
+        "nvme" | "drivetemp" => ("storage", "Storage"),
+        "spd5118" | "jc42" => ("memory", "Memory"),
+        "corsairpsu" => ("psu", "Psu"),
+        _ if name.starts_with("BAT") || name == "battery" => ("battery", "Battery"),
+        _ if name.starts_with("nzxt")
+            || name.starts_with("corsair")
+            || name.starts_with("aquacomputer") =>
+        {
+            ("controller", "Cooler")
+        }
+        _ if name.contains("phy") || name.starts_with("r8169") || name.starts_with("iwlwifi") => {
+            ("network", "Network")
+        }
+        _ if ["nct", "it8", "w83", "f71"]
+            .iter()
+            .any(|p| name.starts_with(p)) =>
+        {
+            ("motherboard", "SuperIO")
+        }
+        _ => ("motherboard", "Motherboard"),
+    }
+}
+
+/// Collects samples, keeping energy counters and min/max between calls
+pub struct Collector {
+    sysfs: Sysfs,
+    classes: HashSet<String>,
+    energy: EnergyMeter,
+    /// Lowest and highest value per sensor id since start
+    extremes: HashMap<String, (f32, f32)>,
+}
+
+impl Collector {
+    pub fn new(sysfs: Sysfs, classes: HashSet<String>) -> Self {
+        Self {
+            sysfs,
+            classes,
+            energy: EnergyMeter::default(),
+            extremes: HashMap::new(),
+        }
+    }
+
+    /// Change the hardware classes, keeping energy counters and min/max
+    pub fn set_classes(&mut self, classes: HashSet<String>) {
+        self.classes = classes;
+    }
+
+    fn enabled(&self, class: &str) -> bool {
+        self.classes.contains(class)
+    }
+
+    /// Read one sample
+    pub fn collect(&mut self) -> HardwareData {
+        let chips = sysfs::hwmon_chips(&self.sysfs);
+        let ids = hardware_ids(&chips);
+        let mut sensors = Vec::new();
+
+        let cpu = if self.enabled("cpu") {
+            self.collect_cpu(&chips, &ids, &mut sensors)
+        } else {
+            None
+        };
+        let gpu = if self.enabled("gpu") {
+            self.collect_gpus(&mut sensors)
+        } else {
+            Vec::new()
+        };
+
+        // Everything else only goes into the sensor tree
+        for (chip, hardware_id) in chips.iter().zip(&ids) {
+            let (class, hardware_type) = chip_class(&chip.name);
+            if class == "cpu" || class == "gpu" || !self.enabled(class) {
+                continue;
+            }
+            let hardware_name = chip.model.clone().unwrap_or_else(|| chip.name.clone());
+            self.push_chip(
+                &mut sensors,
+                chip,
+                hardware_id,
+                &hardware_name,
+                hardware_type,
+            );
+        }
+
+        HardwareData {
+            cpu,
+            gpu,
+            sensors,
+            timestamp: now_millis(),
+            error: None,
+        }
+    }
+
+    fn collect_cpu(
+        &mut self,
+        chips: &[HwmonChip],
+        ids: &[String],
+        sensors: &mut Vec<SensorData>,
+    ) -> Option<CpuData> {
+        let name = sysfs::cpu_model_name(&self.sysfs);
+        let hardware_name = name.clone().unwrap_or_else(|| "CPU".to_string());
+        let mut cpu = CpuData {
+            name,
+            ..Default::default()
+        };
+        let mut found = false;
+        let mut packages = Vec::new();
+
+        for (chip, hardware_id) in chips
+            .iter()
+            .zip(ids)
+            .filter(|(c, _)| chip_class(&c.name).0 == "cpu")
+        {
+            found = true;
+
+            match chip.name.as_str() {
+                "coretemp" => {
+                    for reading in chip.readings(ReadingKind::Temperature) {
+                        if reading.label.starts_with("Package id") {
+                            packages.push(reading.value as f32);
+                            // coretemp's critical limit is TjMax
+                            if cpu.tj_max.is_none() {
+                                cpu.tj_max = reading.crit.map(|c| c as f32);
+                            }
+                        } else if reading.label.starts_with("Core") {
+                            cpu.core_temperatures.push(Some(reading.value as f32));
+                        }
+                    }
+                }
+                "k10temp" | "zenpower" => {
+                    // Tdie is the real die temperature, Tctl may carry an offset
+                    let die = chip
+                        .labeled(ReadingKind::Temperature, "Tdie")
+                        .or_else(|| chip.labeled(ReadingKind::Temperature, "Tctl"));
+                    if let Some(die) = die {
+                        packages.push(die.value as f32);
+                    }
+                }
+                "cpu_thermal" => {
+                    if let Some(reading) = chip.readings(ReadingKind::Temperature).next() {
+                        packages.push(reading.value as f32);
+                    }
+                }
+                "amd_energy" => {
+                    let mut socket_power = None;
+                    for reading in chip.readings(ReadingKind::Energy) {
+                        let key = format!("{}/{}", hardware_id, reading.label);
+                        let power = self.energy.power(&key, reading.value, None);
+                        if reading.label.starts_with("Ecore") {
+                            cpu.core_powers.push(power);
+                        } else if reading.label.starts_with("Esocket") {
+                            socket_power = Some(socket_power.unwrap_or(0.0) + power.unwrap_or(0.0));
+                        }
+                    }
+                    if cpu.power.is_none() {
+                        cpu.power = socket_power;
+                    }
+                }
+                _ => {}
+            }
+
+            self.push_chip(sensors, chip, hardware_id, &hardware_name, "Cpu");
+        }
+
+        // RAPL package power takes precedence over amd_energy sockets
+        let mut rapl_power: Option<f32> = None;
+        for domain in sysfs::rapl_packages(&self.sysfs) {
+            found = true;
+            let key = format!("/intel-rapl/{}", domain.id);
+            let power = self
+                .energy
+                .power(&key, domain.energy_uj, domain.max_energy_uj);
+            if let Some(power) = power {
+                rapl_power = Some(rapl_power.unwrap_or(0.0) + power);
+            }
+            self.push_sensor(
+                sensors,
+                SensorData {
+                    id: format!("{}/power/0", key),
+                    hardware_id: key.clone(),
+                    hardware_name: hardware_name.clone(),
+                    hardware_type: "Cpu".to_string(),
+                    sensor_type: "Power".to_string(),
+                    name: format!("CPU {}", domain.name),
+                    value: power,
+                    min: None,
+                    max: None,
+                    unit: "W".to_string(),
+                },
+            );
+        }
+        if rapl_power.is_some() {
+            cpu.power = rapl_power;
+        }
+
+        if !found {
+            return None;
+        }
+
+        let max_core = cpu
+            .core_temperatures
+            .iter()
+            .flatten()
+            .copied()
+            .reduce(f32::max);
+        cpu.package_temperature = packages.into_iter().reduce(f32::max);
+        cpu.max_temperature = max_core;
+        cpu.temperature = cpu.package_temperature.or(max_core);
+        Some(cpu)
+    }
+
+    fn collect_gpus(&mut self, sensors: &mut Vec<SensorData>) -> Vec<GpuData> {
+        sysfs::drm_cards(&self.sysfs)
+            .into_iter()
+            .map(|card| self.collect_gpu(card, sensors))
+            .collect()
+    }
+
+    fn collect_gpu(&mut self, card: DrmCard, sensors: &mut Vec<SensorData>) -> GpuData {
+        let vendor = card.vendor.clone();
+        let name = format!(
+            "{} GPU{}",
+            vendor.as_deref().unwrap_or("Unknown"),
+            card.pci_id
+                .as_deref()
+                .map(|id| format!(" ({})", id))
+                .unwrap_or_default()
+        );
+        let hardware_id = format!("/gpu/{}", card.id);
+
+        let mut gpu = GpuData {
+            name: Some(name.clone()),
+            vendor,
+            load: card.busy_percent.map(|l| l as f32),
+            core_clock: card.gt_freq_mhz.map(|f| f as f32),
+            ..Default::default()
+        };
+
+        if let Some(chip) = &card.hwmon {
+            let temps: Vec<_> = chip.readings(ReadingKind::Temperature).collect();
+            gpu.temperature = chip
+                .labeled(ReadingKind::Temperature, "edge")
+                .or(temps.first().copied())
+                .map(|r| r.value as f32);
+            gpu.hot_spot_temperature = chip
+                .labeled(ReadingKind::Temperature, "junction")
+                .map(|r| r.value as f32);
+
+            gpu.power = match chip.readings(ReadingKind::Power).next() {
+                Some(reading) => Some(reading.value as f32),
+                // i915/xe only expose an energy counter
+                None => match chip.readings(ReadingKind::Energy).next() {
+                    Some(reading) => {
+                        let key = format!("{}/energy/{}", hardware_id, reading.index);
+                        self.energy.power(&key, reading.value, None)
+                    }
+                    None => None,
+                },
+            };
+
+            if let Some(sclk) = chip.labeled(ReadingKind::Clock, "sclk") {
+                gpu.core_clock = Some(sclk.value as f32);
+            }
+            gpu.memory_clock = chip
+                .labeled(ReadingKind::Clock, "mclk")
+                .map(|r| r.value as f32);
+            gpu.fan_speed = chip
+                .readings(ReadingKind::Fan)
+                .next()
+                .map(|r| r.value as f32);
+
+            let hardware_type = chip_class(&chip.name).1;
+            self.push_chip(sensors, chip, &hardware_id, &name, hardware_type);
+        }
+
+        if let Some(load) = gpu.load {
+            let hardware_type = card
+                .hwmon
+                .as_ref()
+                .map(|c| chip_class(&c.name).1)
+                .unwrap_or("Gpu");
+            self.push_sensor(
+                sensors,
+                SensorData {
+                    id: format!("{}/load/0", hardware_id),
+                    hardware_id: hardware_id.clone(),
+                    hardware_name: name,
+                    hardware_type: hardware_type.to_string(),
+                    sensor_type: "Load".to_string(),
+                    name: "GPU Core".to_string(),
+                    value: Some(load),
+                    min: None,
+                    max: None,
+                    unit: "%".to_string(),
+                },
+            );
+        }
+
+        gpu
+    }
+
+    /// Add every reading of a chip to the sensor tree (raw energy counters excluded)
+    fn push_chip(
+        &mut self,
+        sensors: &mut Vec<SensorData>,
+        chip: &HwmonChip,
+        hardware_id: &str,
+        hardware_name: &str,
+        hardware_type: &str,
+    ) {
+        for reading in chip
+            .readings
+            .iter()
+            .filter(|r| r.kind != ReadingKind::Energy)
+        {
+            self.push_sensor(
+                sensors,
+                SensorData {
+                    id: format!(
+                        "{}/{}/{}",
+                        hardware_id,
+                        reading.kind.sensor_type().to_lowercase(),
+                        reading.index
+                    ),
+                    hardware_id: hardware_id.to_string(),
+                    hardware_name: hardware_name.to_string(),
+                    hardware_type: hardware_type.to_string(),
+                    sensor_type: reading.kind.sensor_type().to_string(),
+                    name: reading.label.clone(),
+                    value: Some(reading.value as f32),
+                    min: None,
+                    max: None,
+                    unit: reading.kind.unit().to_string(),
+                },
+            );
+        }
+    }
+
+    /// Fill in min/max since start and add the sensor
+    fn push_sensor(&mut self, sensors: &mut Vec<SensorData>, mut sensor: SensorData) {
+        if let Some(value) = sensor.value {
+            let extremes = self
+                .extremes
+                .entry(sensor.id.clone())
+                .or_insert((value, value));
+            extremes.0 = extremes.0.min(value);
+            extremes.1 = extremes.1.max(value);
+            sensor.min = Some(extremes.0);
+            sensor.max = Some(extremes.1);
+        } else if let Some(&(min, max)) = self.extremes.get(&sensor.id) {
+            sensor.min = Some(min);
+            sensor.max = Some(max);
+        }
+        sensors.push(sensor);
+    }
+}
+
+/// Stable ids like "/coretemp/0", numbered per driver name
+fn hardware_ids(chips: &[HwmonChip]) -> Vec<String> {
+    let mut instances: HashMap<&str, u32> = HashMap::new();
+    chips
+        .iter()
+        .map(|chip| {
+            let instance = instances.entry(chip.name.as_str()).or_insert(0);
+            let id = format!("/{}/{}", chip.name, instance);
+            *instance += 1;
+            id
+        })
+        .collect()
+}
+
+/// Current time as a Unix timestamp in milliseconds
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, format!("{}\n", content)).unwrap();
+    }
+
+    /// Fake tree with an Intel CPU, an AMD GPU and an NVMe drive
+    fn fake_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("pulse-sensord-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        write(
+            &root,
+            "proc/cpuinfo",
+            "processor\t: 0\nmodel name\t: Intel(R) Core(TM) i7-12700K\n",
+        );
+
+        write(&root, "sys/class/hwmon/hwmon0/name", "coretemp");
+        write(&root, "sys/class/hwmon/hwmon0/temp1_label", "Package id 0");
+        write(&root, "sys/class/hwmon/hwmon0/temp1_input", "65000");
+        write(&root, "sys/class/hwmon/hwmon0/temp1_crit", "100000");
+        write(&root, "sys/class/hwmon/hwmon0/temp2_label", "Core 0");
+        write(&root, "sys/class/hwmon/hwmon0/temp2_input", "61000");
+        write(&root, "sys/class/hwmon/hwmon0/temp3_label", "Core 1");
+        write(&root, "sys/class/hwmon/hwmon0/temp3_input", "67000");
+
+        write(&root, "sys/class/hwmon/hwmon1/name", "nvme");
+        write(
+            &root,
+            "sys/class/hwmon/hwmon1/device/model",
+            "Samsung SSD 980 PRO 1TB",
+        );
+        write(&root, "sys/class/hwmon/hwmon1/temp1_label", "Composite");
+        write(&root, "sys/class/hwmon/hwmon1/temp1_input", "42850");
+
+        write(&root, "sys/class/hwmon/hwmon2/name", "nct6798");
+        write(&root, "sys/class/hwmon/hwmon2/in0_input", "1250");
+        write(&root, "sys/class/hwmon/hwmon2/fan1_input", "1100");
+
+        write(&root, "sys/class/powercap/intel-rapl:0/name", "package-0");
+        write(
+            &root,
+            "sys/class/powercap/intel-rapl:0/energy_uj",
+            "1000000",
+        );
+        write(
+            &root,
+            "sys/class/powercap/intel-rapl:0/max_energy_range_uj",
+            "262143328850",
+        );
+
+        let card = "sys/class/drm/card0/device";
+        write(&root, &format!("{}/vendor", card), "0x1002");
+        write(
+            &root,
+            &format!("{}/uevent", card),
+            "DRIVER=amdgpu\nPCI_ID=1002:73BF",
+        );
+        write(&root, &format!("{}/gpu_busy_percent", card), "37");
+        let gpu_hwmon = format!("{}/hwmon/hwmon3", card);
+        write(&root, &format!("{}/name", gpu_hwmon), "amdgpu");
+        write(&root, &format!("{}/temp1_label", gpu_hwmon), "edge");
+        write(&root, &format!("{}/temp1_input", gpu_hwmon), "55000");
+        write(&root, &format!("{}/temp2_label", gpu_hwmon), "junction");
+        write(&root, &format!("{}/temp2_input", gpu_hwmon), "71000");
+        write(&root, &format!("{}/power1_average", gpu_hwmon), "180000000");
+        write(&root, &format!("{}/freq1_label", gpu_hwmon), "sclk");
+        write(&root, &format!("{}/freq1_input", gpu_hwmon), "2400000000");
+        write(&root, &format!("{}/freq2_label", gpu_hwmon), "mclk");
+        write(&root, &format!("{}/freq2_input", gpu_hwmon), "1000000000");
+        write(&root, &format!("{}/fan1_input", gpu_hwmon), "1450");
+        // Connector directories aren't cards
+        write(&root, "sys/class/drm/card0-DP-1/status", "connected");
+
+        root
+    }
+
+    fn all_classes() -> HashSet<String> {
+        KNOWN_CLASSES.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_collects_cpu_and_gpu() {
+        let root = fake_root("full");
+        let mut collector = Collector::new(Sysfs::new(&root), all_classes());
+        let data = collector.collect();
+
+        let cpu = data.cpu.unwrap();
+        assert_eq!(cpu.name.as_deref(), Some("Intel(R) Core(TM) i7-12700K"));
+        assert_eq!(cpu.package_temperature, Some(65.0));
+        assert_eq!(cpu.temperature, Some(65.0));
+        assert_eq!(cpu.core_temperatures, vec![Some(61.0), Some(67.0)]);
+        assert_eq!(cpu.max_temperature, Some(67.0));
+        assert_eq!(cpu.tj_max, Some(100.0));
+        // First RAPL sample has nothing to diff against
+        assert_eq!(cpu.power, None);
+
+        assert_eq!(data.gpu.len(), 1);
+        let gpu = &data.gpu[0];
+        assert_eq!(gpu.vendor.as_deref(), Some("AMD"));
+        assert_eq!(gpu.name.as_deref(), Some("AMD GPU (1002:73BF)"));
+        assert_eq!(gpu.temperature, Some(55.0));
+        assert_eq!(gpu.hot_spot_temperature, Some(71.0));
+        assert_eq!(gpu.power, Some(180.0));
+        assert_eq!(gpu.core_clock, Some(2400.0));
+        assert_eq!(gpu.memory_clock, Some(1000.0));
+        assert_eq!(gpu.fan_speed, Some(1450.0));
+        assert_eq!(gpu.load, Some(37.0));
+
+        let nvme = data
+            .sensors
+            .iter()
+            .find(|s| s.hardware_type == "Storage")
+            .unwrap();
+        assert_eq!(nvme.hardware_name, "Samsung SSD 980 PRO 1TB");
+        assert_eq!(nvme.value, Some(42.85));
+        assert_eq!(nvme.unit, "°C");
+
+        let vcore = data
+            .sensors
+            .iter()
+            .find(|s| s.id == "/nct6798/0/voltage/0")
+            .unwrap();
+        assert_eq!(vcore.hardware_type, "SuperIO");
+        assert_eq!(vcore.value, Some(1.25));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_rapl_power_and_min_max() {
+        let root = fake_root("rapl");
+        let mut collector = Collector::new(Sysfs::new(&root), all_classes());
+        collector.collect();
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        write(
+            &root,
+            "sys/class/powercap/intel-rapl:0/energy_uj",
+            "4000000",
+        );
+        write(&root, "sys/class/hwmon/hwmon0/temp2_input", "58000");
+        let data = collector.collect();
+
+        // 3 J in ~0.1 s
+        let power = data.cpu.unwrap().power.unwrap();
+        assert!(power > 10.0 && power < 31.0, "power {}", power);
+
+        let core0 = data
+            .sensors
+            .iter()
+            .find(|s| s.id == "/coretemp/0/temperature/2")
+            .unwrap();
+        assert_eq!(core0.value, Some(58.0));
+        assert_eq!(core0.min, Some(58.0));
+        assert_eq!(core0.max, Some(61.0));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_hardware_classes_filter_output() {
+        let root = fake_root("filter");
+        let classes = ["cpu".to_string()].into_iter().collect();
+        let data = Collector::new(Sysfs::new(&root), classes).collect();
+
+        assert!(data.cpu.is_some());
+        assert!(data.gpu.is_empty());
+        assert!(data.sensors.iter().all(|s| s.hardware_type == "Cpu"));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_amd_cpu() {
+        let root = std::env::temp_dir().join(format!("pulse-sensord-amd-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        write(&root, "sys/class/hwmon/hwmon0/name", "k10temp");
+        write(&root, "sys/class/hwmon/hwmon0/temp1_label", "Tctl");
+        write(&root, "sys/class/hwmon/hwmon0/temp1_input", "74500");
+        write(&root, "sys/class/hwmon/hwmon0/temp3_label", "Tccd1");
+        write(&root, "sys/class/hwmon/hwmon0/temp3_input", "70000");
+
+        let cpu = Collector::new(Sysfs::new(&root), all_classes())
+            .collect()
+            .cpu
+            .unwrap();
+        assert_eq!(cpu.temperature, Some(74.5));
+        assert!(cpu.core_temperatures.is_empty());
+        // AMD doesn't report TjMax through k10temp
+        assert_eq!(cpu.tj_max, None);
+        let _ = fs::remove_dir_all(root);
+    }
+}
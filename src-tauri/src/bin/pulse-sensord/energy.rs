@@ -0,0 +1,87 @@
+//! Turns cumulative energy counters (RAPL, amd_energy, i915) into power
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Remembers the previous counter value per key
+#[derive(Default)]
+pub struct EnergyMeter {
+    last: HashMap<String, (f64, Instant)>,
+}
+
+impl EnergyMeter {
+    /// Average power in watts since the previous call for `key`
+    /// None on the first sample of a counter
+    pub fn power(&mut self, key: &str, energy_uj: f64, max_range_uj: Option<f64>) -> Option<f32> {
+        self.power_at(key, energy_uj, max_range_uj, Instant::now())
+    }
+
+    fn power_at(
+        &mut self,
+        key: &str,
+        energy_uj: f64,
+        max_range_uj: Option<f64>,
+        now: Instant,
+    ) -> Option<f32> {
+        let previous = self.last.insert(key.to_string(), (energy_uj, now))?;
+        let (last_energy, last_time) = previous;
+
+        let elapsed = now.duration_since(last_time).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+
+        let mut delta = energy_uj - last_energy;
+        if delta < 0.0 {
+            // Counter wrapped around
+            delta += max_range_uj?;
+        }
+
+        Some((delta / 1_000_000.0 / elapsed) as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_power_from_energy() {
+        let mut meter = EnergyMeter::default();
+        let start = Instant::now();
+
+        assert_eq!(meter.power_at("pkg", 1_000_000.0, None, start), None);
+        // 30 J in 2 s = 15 W
+        let power = meter
+            .power_at("pkg", 31_000_000.0, None, start + Duration::from_secs(2))
+            .unwrap();
+        assert!((power - 15.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        let mut meter = EnergyMeter::default();
+        let start = Instant::now();
+
+        meter.power_at("pkg", 99_000_000.0, Some(100_000_000.0), start);
+        // Wrapped: 1 J before the wrap + 9 J after = 10 J in 1 s
+        let power = meter
+            .power_at(
+                "pkg",
+                9_000_000.0,
+                Some(100_000_000.0),
+                start + Duration::from_secs(1),
+            )
+            .unwrap();
+        assert!((power - 10.0).abs() < 0.01);
+
+        // Unknown range - can't tell how far it wrapped
+        let mut meter = EnergyMeter::default();
+        meter.power_at("pkg", 99.0, None, start);
+        assert_eq!(
+            meter.power_at("pkg", 9.0, None, start + Duration::from_secs(1)),
+            None
+        );
+    }
+}
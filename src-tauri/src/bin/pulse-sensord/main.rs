@@ -0,0 +1,449 @@
+//! pulse-sensord - Linux sensor sidecar
+//!
+//! Reads hwmon, RAPL and DRM sysfs and prints the same JSON lines as
+//! lhm-sidecar, so Pulse's sidecar supervisor works unchanged on Linux.
+//! With `--listen` it serves the lines on a Unix socket instead, to run as a
+//! long-lived privileged collector (RAPL energy needs root on most kernels).
+
+mod collect;
+mod energy;
+mod protocol;
+mod sysfs;
+
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use collect::{now_millis, Collector, KNOWN_CLASSES};
+use protocol::{ErrorData, HardwareData};
+use sysfs::Sysfs;
+
+/// Hardware classes enabled when no --hardware flag is given (same as lhm-sidecar)
+const DEFAULT_CLASSES: [&str; 7] = [
+    "cpu",
+    "gpu",
+    "memory",
+    "motherboard",
+    "storage",
+    "controller",
+    "psu",
+];
+
+/// Where samples go - stdout or the socket clients
+/// An error means nobody can read them anymore
+type Output = Box<dyn FnMut(&str) -> io::Result<()>>;
+
+struct Options {
+    interval_ms: u64,
+    once: bool,
+    classes: HashSet<String>,
+    listen: Option<String>,
+    group: Option<String>,
+    root: String,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => return,
+        Err(e) => {
+            log("error", &e);
+            std::process::exit(2);
+        }
+    };
+
+    log(
+        "info",
+        &format!(
+            "Starting (interval {} ms, once: {}, hardware: {})",
+            options.interval_ms,
+            options.once,
+            sorted(&options.classes).join(",")
+        ),
+    );
+
+    let mut output: Output = match &options.listen {
+        #[cfg(unix)]
+        Some(path) => match listen::Broadcaster::bind(path, options.group.as_deref()) {
+            Ok(broadcaster) => {
+                log("info", &format!("Serving on {}", path));
+                Box::new(move |line| {
+                    broadcaster.send(line);
+                    Ok(())
+                })
+            }
+            Err(e) => {
+                log("error", &format!("Failed to listen on {}: {}", path, e));
+                output_error(
+                    "fatal",
+                    &format!("Failed to listen on {}: {}", path, e),
+                    false,
+                );
+                std::process::exit(1);
+            }
+        },
+        #[cfg(not(unix))]
+        Some(_) => {
+            log("error", "--listen is only supported on Unix");
+            std::process::exit(2);
+        }
+        None => Box::new(|line| {
+            let mut stdout = io::stdout().lock();
+            writeln!(stdout, "{}", line)?;
+            stdout.flush()
+        }),
+    };
+
+    let commands = spawn_command_reader(options.once);
+    let mut collector = Collector::new(Sysfs::new(&options.root), options.classes);
+
+    loop {
+        match commands.try_recv() {
+            Ok(classes) => {
+                log(
+                    "info",
+                    &format!("Hardware changed to {}", sorted(&classes).join(",")),
+                );
+                collector.set_classes(classes);
+            }
+            // Nobody left to read us - the app is gone without killing us
+            Err(mpsc::TryRecvError::Disconnected) if !options.once && options.listen.is_none() => {
+                log("info", "stdin closed, exiting");
+                break;
+            }
+            Err(_) => {}
+        }
+
+        let data = collector.collect();
+        match serde_json::to_string(&data) {
+            Ok(json) => {
+                if let Err(e) = output(&json) {
+                    log(
+                        "info",
+                        &format!("Failed to write to stdout ({}), exiting", e),
+                    );
+                    break;
+                }
+            }
+            Err(e) => log("warn", &format!("Failed to serialize sample: {}", e)),
+        }
+
+        if options.once {
+            break;
+        }
+        thread::sleep(Duration::from_millis(options.interval_ms));
+    }
+}
+
+/// Parse the command line, Ok(None) after printing the help
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        interval_ms: 1000,
+        once: false,
+        classes: DEFAULT_CLASSES.iter().map(|c| c.to_string()).collect(),
+        listen: None,
+        group: None,
+        root: "/".to_string(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("{} needs a value", name));
+        match arg.as_str() {
+            "--interval" | "-i" => {
+                let interval: u64 = value("--interval")?
+                    .parse()
+                    .map_err(|_| "--interval must be a number of milliseconds".to_string())?;
+                options.interval_ms = interval.max(100); // Minimum 100ms
+            }
+            "--once" | "--single" | "-s" => options.once = true,
+            "--hardware" | "-w" => options.classes = parse_classes(&value("--hardware")?)?,
+            "--listen" | "-l" => options.listen = Some(value("--listen")?),
+            "--group" | "-g" => options.group = Some(value("--group")?),
+            "--root" => options.root = value("--root")?,
+            "--help" | "-h" => {
+                print_help();
+                return Ok(None);
+            }
+            other => log("warn", &format!("Ignoring unknown argument {}", other)),
+        }
+    }
+    Ok(Some(options))
+}
+
+/// Parse a comma-separated hardware class list such as "cpu,gpu,storage"
+fn parse_classes(list: &str) -> Result<HashSet<String>, String> {
+    let classes: HashSet<String> = list
+        .split(',')
+        .map(|c| c.trim().to_lowercase())
+        .filter(|c| !c.is_empty())
+        .collect();
+
+    let unknown: Vec<&str> = classes
+        .iter()
+        .map(String::as_str)
+        .filter(|c| !KNOWN_CLASSES.contains(c))
+        .collect();
+    if !unknown.is_empty() || classes.is_empty() {
+        return Err(format!(
+            "Unknown hardware class(es) {:?} in \"{}\", known: {}",
+            unknown,
+            list,
+            KNOWN_CLASSES.join(", ")
+        ));
+    }
+    Ok(classes)
+}
+
+/// Read `hardware <list>` commands from stdin, like lhm-sidecar
+/// The channel disconnects once stdin is closed
+fn spawn_command_reader(once: bool) -> mpsc::Receiver<HashSet<String>> {
+    let (tx, rx) = mpsc::channel();
+    if once {
+        return rx;
+    }
+
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match line.trim().split_once(' ') {
+                Some(("hardware", list)) => match parse_classes(list) {
+                    Ok(classes) => {
+                        if tx.send(classes).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => log("warn", &e),
+                },
+                _ if line.trim().is_empty() => {}
+                _ => log("warn", &format!("Unknown command: {}", line.trim())),
+            }
+        }
+    });
+    rx
+}
+
+fn sorted(classes: &HashSet<String>) -> Vec<&str> {
+    let mut sorted: Vec<&str> = classes.iter().map(String::as_str).collect();
+    sorted.sort_unstable();
+    sorted
+}
+
+fn output_error(code: &str, message: &str, retryable: bool) {
+    let data = HardwareData {
+        error: Some(ErrorData {
+            code: code.to_string(),
+            message: message.to_string(),
+            retryable,
+        }),
+        timestamp: now_millis(),
+        ..Default::default()
+    };
+    if let Ok(json) = serde_json::to_string(&data) {
+        println!("{}", json);
+    }
+}
+
+/// Write a diagnostic line to stderr as "[level] message"
+/// stdout is reserved for the JSON protocol
+fn log(level: &str, message: &str) {
+    eprintln!("[{}] {}", level, message);
+}
+
+fn print_help() {
+    println!(
+        "pulse-sensord - Linux hardware monitoring using hwmon, RAPL and DRM sysfs
+
+Usage: pulse-sensord [options]
+
+Options:
+  -i, --interval <ms>   Update interval in milliseconds (default: 1000, min: 100)
+  -s, --once            Output once and exit (--single is an alias)
+  -w, --hardware <list> Comma-separated hardware classes to read (default:
+                        cpu,gpu,memory,motherboard,storage,controller,psu)
+  -l, --listen <path>   Serve JSON lines on a Unix socket instead of stdout,
+                        e.g. as a long-lived root collector
+  -g, --group <name>    Let members of group <name> read the socket (default:
+                        only this user and its primary group)
+      --root <dir>      Read /sys and /proc below <dir> (for testing)
+  -h, --help            Show this help message
+
+Commands (stdin, one per line):
+  hardware <list>       Change the hardware classes without restarting
+
+Without --listen, exits once stdin is closed or stdout can't be written,
+so it ends with the app that started it.
+
+Output: JSON lines in the lhm-sidecar format, diagnostics as
+        \"[level] message\" lines to stderr
+
+Note: RAPL CPU power needs root on kernels with the PLATYPUS mitigation."
+    );
+}
+
+#[cfg(unix)]
+mod listen {
+    use std::ffi::CString;
+    use std::io::{self, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// Sends every line to all clients connected to a Unix socket
+    pub struct Broadcaster {
+        clients: Arc<Mutex<Vec<UnixStream>>>,
+    }
+
+    impl Broadcaster {
+        /// Listen on `path`, readable by the owner and `group` (or the
+        /// owner's primary group)
+        pub fn bind(path: &str, group: Option<&str>) -> io::Result<Self> {
+            // A stale socket from a previous run blocks the bind
+            if std::fs::metadata(path).is_ok() && UnixStream::connect(path).is_err() {
+                std::fs::remove_file(path)?;
+            }
+            let listener = UnixListener::bind(path)?;
+            // Clients also check that we run as root or as their own user
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o660))?;
+            if let Some(group) = group {
+                let gid = group_id(group)?;
+                std::os::unix::fs::chown(path, None, Some(gid))?;
+            }
+
+            let clients = Arc::new(Mutex::new(Vec::new()));
+            let accept_clients = Arc::clone(&clients);
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    // A client that stops reading must not stall the collection loop
+                    if let Err(e) = stream.set_nonblocking(true) {
+                        super::log("warn", &format!("Dropping client: {}", e));
+                        continue;
+                    }
+                    if let Ok(mut clients) = accept_clients.lock() {
+                        clients.push(stream);
+                        super::log(
+                            "info",
+                            &format!("Client connected ({} total)", clients.len()),
+                        );
+                    }
+                }
+            });
+
+            Ok(Self { clients })
+        }
+
+        pub fn send(&self, line: &str) {
+            let Ok(mut clients) = self.clients.lock() else {
+                return;
+            };
+            let message = format!("{}\n", line);
+            let before = clients.len();
+            // Gone or too far behind to take a whole line (WouldBlock) - drop it
+            clients.retain_mut(|client| client.write_all(message.as_bytes()).is_ok());
+            if clients.len() < before {
+                super::log(
+                    "info",
+                    &format!("Client disconnected ({} left)", clients.len()),
+                );
+            }
+        }
+    }
+
+    /// Resolve a group name (or numeric id) to its gid
+    fn group_id(group: &str) -> io::Result<u32> {
+        if let Ok(gid) = group.parse() {
+            return Ok(gid);
+        }
+        let name = CString::new(group)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Invalid group name"))?;
+        // SAFETY: name is NUL-terminated, the returned entry is read
+        // before any other getgr* call could overwrite it
+        let entry = unsafe { libc::getgrnam(name.as_ptr()) };
+        if entry.is_null() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Unknown group {}", group),
+            ));
+        }
+        // SAFETY: checked for null above
+        Ok(unsafe { (*entry).gr_gid })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::time::{Duration, Instant};
+
+        #[test]
+        fn test_drops_stalled_client() {
+            let path =
+                std::env::temp_dir().join(format!("pulse-sensord-{}.sock", std::process::id()));
+            let path = path.to_str().unwrap();
+            let broadcaster = Broadcaster::bind(path, None).unwrap();
+            let mode = std::fs::metadata(path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o660);
+
+            // Connected but never reading
+            let _client = UnixStream::connect(path).unwrap();
+            let started = Instant::now();
+            while broadcaster.clients.lock().unwrap().is_empty() {
+                assert!(started.elapsed() < Duration::from_secs(5));
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            let line = "x".repeat(64 * 1024);
+            let started = Instant::now();
+            while !broadcaster.clients.lock().unwrap().is_empty() {
+                assert!(started.elapsed() < Duration::from_secs(5));
+                broadcaster.send(&line);
+            }
+
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> impl Iterator<Item = String> {
+        list.iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = parse_args(args(&[
+            "--interval",
+            "50",
+            "--once",
+            "--hardware",
+            "cpu,GPU",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(options.interval_ms, 100);
+        assert!(options.once);
+        assert_eq!(sorted(&options.classes), vec!["cpu", "gpu"]);
+
+        // Same flags the supervisor passes to lhm-sidecar
+        let options = parse_args(args(&["--interval", "1000", "--hardware", "cpu,storage"]))
+            .unwrap()
+            .unwrap();
+        assert!(!options.once);
+        assert_eq!(options.interval_ms, 1000);
+    }
+
+    #[test]
+    fn test_rejects_unknown_class() {
+        assert!(parse_args(args(&["--hardware", "cpu,toaster"])).is_err());
+        assert!(parse_args(args(&["--interval"])).is_err());
+    }
+}
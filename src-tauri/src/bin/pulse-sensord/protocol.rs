@@ -0,0 +1,59 @@
+//! JSON-lines output, field for field the same as lhm-sidecar's `HardwareData`
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HardwareData {
+    pub cpu: Option<CpuData>,
+    pub gpu: Vec<GpuData>,
+    pub sensors: Vec<SensorData>,
+    pub timestamp: i64, // Unix timestamp in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorData>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CpuData {
+    pub name: Option<String>,
+    pub temperature: Option<f32>,         // Celsius
+    pub package_temperature: Option<f32>, // Celsius
+    pub core_temperatures: Vec<Option<f32>>,
+    pub max_temperature: Option<f32>, // Hottest core, Celsius
+    pub tj_max: Option<f32>,          // Celsius
+    pub power: Option<f32>,           // Watts
+    pub core_powers: Vec<Option<f32>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GpuData {
+    pub name: Option<String>,
+    pub vendor: Option<String>,
+    pub temperature: Option<f32>,          // Celsius
+    pub hot_spot_temperature: Option<f32>, // Celsius
+    pub power: Option<f32>,                // Watts
+    pub core_clock: Option<f32>,           // MHz
+    pub memory_clock: Option<f32>,         // MHz
+    pub fan_speed: Option<f32>,            // RPM
+    pub load: Option<f32>,                 // Percentage 0-100
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SensorData {
+    pub id: String,
+    pub hardware_id: String,
+    pub hardware_name: String,
+    pub hardware_type: String,
+    pub sensor_type: String,
+    pub name: String,
+    pub value: Option<f32>,
+    pub min: Option<f32>, // Lowest value since start
+    pub max: Option<f32>, // Highest value since start
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorData {
+    pub code: String,
+    pub message: String,
+    pub retryable: bool,
+}
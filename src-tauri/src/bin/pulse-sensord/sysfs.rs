@@ -0,0 +1,314 @@
+//! Readers for the Linux sysfs interfaces pulse-sensord uses:
+//! hwmon (temperatures, fans, voltages, power), powercap/RAPL (CPU package
+//! energy) and DRM (GPU load and clocks)
+//!
+//! All paths are resolved against a root directory, so tests can point
+//! the reader at a fake tree.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Filesystem root that `/sys` and `/proc` are resolved against
+pub struct Sysfs {
+    root: PathBuf,
+}
+
+impl Sysfs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Resolve an absolute path like `/sys/class/hwmon` below the root
+    pub fn path(&self, absolute: &str) -> PathBuf {
+        self.root.join(absolute.trim_start_matches('/'))
+    }
+}
+
+/// Read a sysfs attribute without the trailing newline
+pub fn read_string(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Read a numeric sysfs attribute
+pub fn read_number(path: &Path) -> Option<f64> {
+    read_string(path)?.parse().ok()
+}
+
+/// Entries of a directory sorted by the number at the end of their name
+/// (hwmon2 before hwmon10)
+pub fn numbered_entries(dir: &Path, prefix: &str) -> Vec<(u32, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut numbered: Vec<(u32, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let number = name.strip_prefix(prefix)?.parse().ok()?;
+            Some((number, entry.path()))
+        })
+        .collect();
+    numbered.sort_by_key(|(number, _)| *number);
+    numbered
+}
+
+/// Kind of hwmon channel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingKind {
+    Temperature,
+    Fan,
+    Voltage,
+    Power,
+    Current,
+    Clock,
+    Energy,
+}
+
+impl ReadingKind {
+    const ALL: [ReadingKind; 7] = [
+        ReadingKind::Temperature,
+        ReadingKind::Fan,
+        ReadingKind::Voltage,
+        ReadingKind::Power,
+        ReadingKind::Current,
+        ReadingKind::Clock,
+        ReadingKind::Energy,
+    ];
+
+    /// Attribute prefix in the hwmon ABI
+    fn prefix(self) -> &'static str {
+        match self {
+            ReadingKind::Temperature => "temp",
+            ReadingKind::Fan => "fan",
+            ReadingKind::Voltage => "in",
+            ReadingKind::Power => "power",
+            ReadingKind::Current => "curr",
+            ReadingKind::Clock => "freq",
+            ReadingKind::Energy => "energy",
+        }
+    }
+
+    /// Divisor from the raw hwmon unit to the reported unit
+    fn scale(self) -> f64 {
+        match self {
+            ReadingKind::Temperature => 1000.0, // m°C
+            ReadingKind::Fan => 1.0,            // RPM
+            ReadingKind::Voltage => 1000.0,     // mV
+            ReadingKind::Power => 1_000_000.0,  // µW
+            ReadingKind::Current => 1000.0,     // mA
+            ReadingKind::Clock => 1_000_000.0,  // Hz
+            ReadingKind::Energy => 1.0,         // µJ, kept raw for the energy meter
+        }
+    }
+
+    /// LHM sensor type name
+    pub fn sensor_type(self) -> &'static str {
+        match self {
+            ReadingKind::Temperature => "Temperature",
+            ReadingKind::Fan => "Fan",
+            ReadingKind::Voltage => "Voltage",
+            ReadingKind::Power => "Power",
+            ReadingKind::Current => "Current",
+            ReadingKind::Clock => "Clock",
+            ReadingKind::Energy => "Energy",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            ReadingKind::Temperature => "°C",
+            ReadingKind::Fan => "RPM",
+            ReadingKind::Voltage => "V",
+            ReadingKind::Power => "W",
+            ReadingKind::Current => "A",
+            ReadingKind::Clock => "MHz",
+            ReadingKind::Energy => "µJ",
+        }
+    }
+}
+
+/// One hwmon channel, already converted to its reported unit
+#[derive(Debug, Clone)]
+pub struct HwmonReading {
+    pub kind: ReadingKind,
+    pub index: u32,
+    pub label: String,
+    pub value: f64,
+    pub crit: Option<f64>,
+}
+
+/// A hwmon chip (one `/sys/class/hwmon/hwmonN` directory)
+#[derive(Debug, Clone)]
+pub struct HwmonChip {
+    pub name: String,
+    /// Human-readable device model, if the driver exposes one (e.g. NVMe)
+    pub model: Option<String>,
+    pub readings: Vec<HwmonReading>,
+}
+
+impl HwmonChip {
+    /// Read a chip directory, None if it has no `name`
+    pub fn read(dir: &Path) -> Option<Self> {
+        let name = read_string(&dir.join("name"))?;
+        let model = read_string(&dir.join("device").join("model"));
+
+        let mut readings = Vec::new();
+        for kind in ReadingKind::ALL {
+            readings.extend(read_channels(dir, kind));
+        }
+
+        Some(Self {
+            name,
+            model,
+            readings,
+        })
+    }
+
+    /// Readings of one kind
+    pub fn readings(&self, kind: ReadingKind) -> impl Iterator<Item = &HwmonReading> {
+        self.readings.iter().filter(move |r| r.kind == kind)
+    }
+
+    /// First reading of `kind` with the given label (case-insensitive)
+    pub fn labeled(&self, kind: ReadingKind, label: &str) -> Option<&HwmonReading> {
+        self.readings(kind)
+            .find(|r| r.label.eq_ignore_ascii_case(label))
+    }
+}
+
+/// Read every `<prefix>N_input` channel of a chip
+fn read_channels(dir: &Path, kind: ReadingKind) -> Vec<HwmonReading> {
+    let prefix = kind.prefix();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut indices: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let rest = name.strip_prefix(prefix)?;
+            let (index, attr) = rest.split_once('_')?;
+            if attr == "input" || (kind == ReadingKind::Power && attr == "average") {
+                index.parse().ok()
+            } else {
+                None
+            }
+        })
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let attr = |suffix: &str| dir.join(format!("{}{}_{}", prefix, index, suffix));
+            let raw = read_number(&attr("input")).or_else(|| read_number(&attr("average")))?;
+
+            Some(HwmonReading {
+                kind,
+                index,
+                label: read_string(&attr("label"))
+                    .unwrap_or_else(|| format!("{} {}", kind.sensor_type(), index)),
+                value: raw / kind.scale(),
+                crit: read_number(&attr("crit")).map(|c| c / kind.scale()),
+            })
+        })
+        .collect()
+}
+
+/// All hwmon chips, in hwmonN order
+pub fn hwmon_chips(sysfs: &Sysfs) -> Vec<HwmonChip> {
+    numbered_entries(&sysfs.path("/sys/class/hwmon"), "hwmon")
+        .into_iter()
+        .filter_map(|(_, dir)| HwmonChip::read(&dir))
+        .collect()
+}
+
+/// A RAPL package domain (`/sys/class/powercap/intel-rapl:N`)
+#[derive(Debug, Clone)]
+pub struct RaplDomain {
+    pub id: u32,
+    pub name: String,
+    pub energy_uj: f64,
+    pub max_energy_uj: Option<f64>,
+}
+
+/// Top-level RAPL domains (one per CPU package)
+/// Reading energy_uj needs root on kernels with the PLATYPUS mitigation
+pub fn rapl_packages(sysfs: &Sysfs) -> Vec<RaplDomain> {
+    numbered_entries(&sysfs.path("/sys/class/powercap"), "intel-rapl:")
+        .into_iter()
+        .filter_map(|(id, dir)| {
+            Some(RaplDomain {
+                id,
+                name: read_string(&dir.join("name")).unwrap_or_else(|| format!("package-{}", id)),
+                energy_uj: read_number(&dir.join("energy_uj"))?,
+                max_energy_uj: read_number(&dir.join("max_energy_range_uj")),
+            })
+        })
+        .collect()
+}
+
+/// A DRM card (`/sys/class/drm/cardN`, connectors like card0-DP-1 are skipped)
+#[derive(Debug, Clone)]
+pub struct DrmCard {
+    pub id: u32,
+    pub vendor: Option<String>,
+    pub pci_id: Option<String>,
+    pub busy_percent: Option<f64>,
+    /// Current GT frequency (Intel), MHz
+    pub gt_freq_mhz: Option<f64>,
+    pub hwmon: Option<HwmonChip>,
+}
+
+/// Every DRM card with a PCI device
+pub fn drm_cards(sysfs: &Sysfs) -> Vec<DrmCard> {
+    numbered_entries(&sysfs.path("/sys/class/drm"), "card")
+        .into_iter()
+        .filter_map(|(id, dir)| {
+            let device = dir.join("device");
+            let vendor_id = read_string(&device.join("vendor"))?;
+
+            let pci_id = read_string(&device.join("uevent")).and_then(|uevent| {
+                uevent
+                    .lines()
+                    .find_map(|line| line.strip_prefix("PCI_ID=").map(str::to_string))
+            });
+            let hwmon = numbered_entries(&device.join("hwmon"), "hwmon")
+                .into_iter()
+                .find_map(|(_, chip)| HwmonChip::read(&chip));
+
+            Some(DrmCard {
+                id,
+                vendor: vendor_name(&vendor_id).map(str::to_string),
+                pci_id,
+                busy_percent: read_number(&device.join("gpu_busy_percent")),
+                gt_freq_mhz: read_number(&dir.join("gt_cur_freq_mhz")),
+                hwmon,
+            })
+        })
+        .collect()
+}
+
+fn vendor_name(pci_vendor: &str) -> Option<&'static str> {
+    match pci_vendor.to_lowercase().as_str() {
+        "0x1002" => Some("AMD"),
+        "0x10de" => Some("NVIDIA"),
+        "0x8086" => Some("Intel"),
+        _ => None,
+    }
+}
+
+/// CPU model name from /proc/cpuinfo
+pub fn cpu_model_name(sysfs: &Sysfs) -> Option<String> {
+    let cpuinfo = fs::read_to_string(sysfs.path("/proc/cpuinfo")).ok()?;
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "model name").then(|| value.trim().to_string())
+    })
+}
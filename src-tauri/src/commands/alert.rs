@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::models::AlertEvent;
+use crate::services::AlertEngine;
+
+/// Alerts returned by `get_alert_history` when no limit is given
+const DEFAULT_HISTORY_LIMIT: usize = 200;
+
+/// Shared state for the alert engine
+/// Rules themselves live in `Settings::alerts`
+pub struct AlertState(pub Arc<AlertEngine>);
+
+/// Alerts that fired and haven't resolved yet
+/// Unacknowledged ones come first, then the most severe
+#[tauri::command]
+pub fn get_active_alerts(state: State<'_, AlertState>) -> Result<Vec<AlertEvent>, String> {
+    Ok(state.0.active())
+}
+
+/// Alerts that were active at some point within `from`..=`to` (Unix ms),
+/// optionally of one rule, newest first
+#[tauri::command]
+pub async fn get_alert_history(
+    state: State<'_, AlertState>,
+    from: Option<u64>,
+    to: Option<u64>,
+    rule_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<AlertEvent>, String> {
+    state.0.history(
+        from,
+        to,
+        rule_id.as_deref(),
+        limit.unwrap_or(DEFAULT_HISTORY_LIMIT),
+    )
+}
+
+/// Silence the active alert of a rule, emitting "alert-acknowledged"
+#[tauri::command]
+pub fn acknowledge_alert(
+    app: AppHandle,
+    state: State<'_, AlertState>,
+    rule_id: String,
+) -> Result<AlertEvent, String> {
+    let alert = state
+        .0
+        .acknowledge(&rule_id, chrono::Utc::now().timestamp_millis() as u64)?;
+    println!("[Alert] {} acknowledged", rule_id);
+    let _ = app.emit("alert-acknowledged", &alert);
+    Ok(alert)
+}
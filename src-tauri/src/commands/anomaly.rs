@@ -0,0 +1,15 @@
+use std::sync::Arc;
+use tauri::State;
+
+use crate::models::AnomalyEvent;
+use crate::services::AnomalyDetector;
+
+/// Shared state for the anomaly detector
+/// It only runs while `Settings::anomaly` is enabled
+pub struct AnomalyState(pub Arc<AnomalyDetector>);
+
+/// Anomalies found since app start, newest first
+#[tauri::command]
+pub fn get_recent_anomalies(state: State<'_, AnomalyState>) -> Result<Vec<AnomalyEvent>, String> {
+    Ok(state.0.recent())
+}
@@ -0,0 +1,57 @@
+use std::path::Path;
+use std::sync::Arc;
+use tauri::State;
+
+use crate::models::{ExportFormat, MetricSeries, TimeRange};
+use crate::services::export;
+use crate::services::history::query_history;
+use crate::services::{MetricHistory, MetricStore};
+
+/// Shared state for the metric history
+/// `store` is None when the on-disk database couldn't be opened
+pub struct HistoryState {
+    pub memory: Arc<MetricHistory>,
+    pub store: Option<Arc<MetricStore>>,
+}
+
+/// Get recorded points of `metrics` (all metrics if empty), oldest first
+/// `from`/`to` are Unix ms, `step` averages the points into buckets of that many ms
+/// Ranges older than the in-memory hour are read from the on-disk store
+#[tauri::command]
+pub async fn get_history(
+    state: State<'_, HistoryState>,
+    metrics: Vec<String>,
+    from: Option<u64>,
+    to: Option<u64>,
+    step: Option<u64>,
+) -> Result<Vec<MetricSeries>, String> {
+    query_history(
+        &state.memory,
+        state.store.as_deref(),
+        &metrics,
+        from,
+        to,
+        step,
+    )
+}
+
+/// Write `metrics` (all metrics if empty) within `range` to `path`
+/// Returns the number of rows (timestamps) written
+#[tauri::command]
+pub async fn export_metrics(
+    state: State<'_, HistoryState>,
+    range: TimeRange,
+    metrics: Vec<String>,
+    format: ExportFormat,
+    path: String,
+) -> Result<usize, String> {
+    let series = query_history(
+        &state.memory,
+        state.store.as_deref(),
+        &metrics,
+        range.from,
+        range.to,
+        range.step,
+    )?;
+    export::export_metrics(&series, format, Path::new(&path))
+}
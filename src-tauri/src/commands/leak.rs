@@ -0,0 +1,15 @@
+use std::sync::Arc;
+use tauri::State;
+
+use crate::models::LeakSuspect;
+use crate::services::LeakDetector;
+
+/// Shared state for the per-process leak detector
+/// It only runs while `Settings::leaks` is enabled
+pub struct LeakState(pub Arc<LeakDetector>);
+
+/// Processes whose memory currently keeps growing, fastest first
+#[tauri::command]
+pub fn get_leak_suspects(state: State<'_, LeakState>) -> Result<Vec<LeakSuspect>, String> {
+    Ok(state.0.suspects())
+}
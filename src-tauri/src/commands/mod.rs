@@ -1,5 +1,23 @@
+pub mod alert;
+pub mod anomaly;
+pub mod history;
+pub mod leak;
+pub mod session;
+pub mod settings;
+pub mod sidecar;
+pub mod summary;
 pub mod system_stats;
+pub mod watchdog;
 pub mod window;
 
+pub use alert::*;
+pub use anomaly::*;
+pub use history::*;
+pub use leak::*;
+pub use session::*;
+pub use settings::*;
+pub use sidecar::*;
+pub use summary::*;
 pub use system_stats::*;
+pub use watchdog::*;
 pub use window::*;
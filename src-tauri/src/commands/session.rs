@@ -0,0 +1,142 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::models::{PlaybackStatus, ReportFormat, SessionComparison};
+use crate::services::compare;
+use crate::services::session::{self, SessionRecorder};
+use crate::services::SessionManager;
+
+/// Shared state for session recording and playback
+pub struct SessionState(pub Arc<SessionManager>);
+
+/// Start recording every emitted `SystemStats` to a session file
+/// Without `path` the session goes to `<app data>/sessions/session-<time>.pulse-session`
+#[tauri::command]
+pub fn start_session_recording(
+    app: AppHandle,
+    state: State<'_, SessionState>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?
+            .join("sessions")
+            .join(SessionRecorder::default_file_name()),
+    };
+
+    let path = state.0.start_recording(path)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Stop recording and return the path of the saved session
+#[tauri::command]
+pub fn stop_session_recording(state: State<'_, SessionState>) -> Result<Option<String>, String> {
+    Ok(state.0.stop_recording()?.map(|(path, samples)| {
+        println!("[Session] Saved {} samples to {:?}", samples, path);
+        path.to_string_lossy().into_owned()
+    }))
+}
+
+/// Play a session on the "system-stats" event instead of live data
+/// `speed` scales the original pacing (default 1.0, e.g. 4.0 or 16.0)
+#[tauri::command]
+pub async fn start_session_playback(
+    app: AppHandle,
+    state: State<'_, SessionState>,
+    path: String,
+    speed: Option<f64>,
+) -> Result<PlaybackStatus, String> {
+    let sample_app = app.clone();
+    let end_app = app.clone();
+    let status = state.0.start_playback(
+        Path::new(&path),
+        speed.unwrap_or(1.0),
+        move |stats| {
+            let _ = sample_app.emit("system-stats", stats);
+        },
+        move || {
+            let status = end_app.state::<SessionState>().0.playback_status();
+            let _ = end_app.emit("session-playback", &status);
+        },
+    )?;
+    let _ = app.emit("session-playback", Some(&status));
+    Ok(status)
+}
+
+/// Jump to `position_ms` from the start of the playing session
+#[tauri::command]
+pub fn seek_session_playback(
+    state: State<'_, SessionState>,
+    position_ms: u64,
+) -> Result<PlaybackStatus, String> {
+    state.0.control_playback(Some(position_ms), None)
+}
+
+/// Change the speed of the playing session
+#[tauri::command]
+pub fn set_session_playback_speed(
+    state: State<'_, SessionState>,
+    speed: f64,
+) -> Result<PlaybackStatus, String> {
+    state.0.control_playback(None, Some(speed))
+}
+
+/// Stop playback and go back to live data
+#[tauri::command]
+pub async fn stop_session_playback(
+    app: AppHandle,
+    state: State<'_, SessionState>,
+) -> Result<(), String> {
+    state.0.stop_playback();
+    let _ = app.emit("session-playback", None::<PlaybackStatus>);
+    Ok(())
+}
+
+/// Status of the current playback (None when showing live data)
+#[tauri::command]
+pub fn get_session_playback(
+    state: State<'_, SessionState>,
+) -> Result<Option<PlaybackStatus>, String> {
+    Ok(state.0.playback_status())
+}
+
+/// Compare session `a` ("before") with session `b` ("after")
+/// An empty `metrics` list compares every whole-device metric both sessions have.
+/// With `report_path` a Markdown or HTML report is written too; the format
+/// defaults to HTML for `.html`/`.htm` paths and Markdown otherwise.
+#[tauri::command]
+pub async fn compare_sessions(
+    a: String,
+    b: String,
+    metrics: Option<Vec<String>>,
+    report_path: Option<String>,
+    format: Option<ReportFormat>,
+) -> Result<SessionComparison, String> {
+    let samples_a = session::load_session(Path::new(&a))?;
+    let samples_b = session::load_session(Path::new(&b))?;
+    let comparison =
+        compare::compare_sessions(&a, &samples_a, &b, &samples_b, &metrics.unwrap_or_default());
+
+    if let Some(report_path) = report_path {
+        let path = PathBuf::from(report_path);
+        let format = format.unwrap_or_else(|| match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                ReportFormat::Html
+            }
+            _ => ReportFormat::Markdown,
+        });
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create report dir: {}", e))?;
+        }
+        fs::write(&path, compare::render_report(&comparison, format))
+            .map_err(|e| format!("Failed to write report {:?}: {}", path, e))?;
+        println!("[Session] Wrote comparison report to {:?}", path);
+    }
+
+    Ok(comparison)
+}
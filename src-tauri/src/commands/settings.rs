@@ -0,0 +1,35 @@
+use std::sync::Arc;
+use tauri::State;
+
+use crate::models::Settings;
+use crate::services::SettingsStore;
+use crate::AppState;
+
+/// Shared state for the settings store
+pub struct SettingsState(pub Arc<SettingsStore>);
+
+/// Tauri command to get the current settings
+#[tauri::command]
+pub fn get_settings(state: State<'_, SettingsState>) -> Result<Settings, String> {
+    Ok(state.0.get())
+}
+
+/// Tauri command to validate and save new settings
+/// Hardware classes and the collector endpoint are applied right away
+#[tauri::command]
+pub async fn update_settings(
+    state: State<'_, SettingsState>,
+    app_state: State<'_, AppState>,
+    settings: Settings,
+) -> Result<Settings, String> {
+    let settings = state.0.update(settings)?;
+    app_state
+        .sidecar
+        .set_hardware_classes(&settings.hardware_classes)
+        .map_err(|e| format!("Failed to apply hardware classes: {}", e))?;
+    app_state
+        .sidecar
+        .set_collector_endpoint(settings.collector_endpoint.clone())
+        .map_err(|e| format!("Failed to switch sidecar source: {}", e))?;
+    Ok(settings)
+}
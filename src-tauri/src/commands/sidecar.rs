@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+use crate::models::Sensor;
+use crate::services::sidecar_recording::StreamRecorder;
+use crate::services::{LogEntry, LogLevel, SidecarStatusPayload};
+use crate::AppState;
+
+/// Start the sidecar after it was stopped or failed to start
+#[tauri::command]
+pub async fn start_sidecar(state: State<'_, AppState>) -> Result<SidecarStatusPayload, String> {
+    state.sidecar.start().map_err(|e| e.to_string())?;
+    Ok(state.sidecar.status_payload())
+}
+
+/// Stop the sidecar and disable auto-restart until it's started again
+#[tauri::command]
+pub async fn stop_sidecar(state: State<'_, AppState>) -> Result<SidecarStatusPayload, String> {
+    state.sidecar.stop();
+    Ok(state.sidecar.status_payload())
+}
+
+/// Restart the sidecar, also after the watcher gave up on it
+#[tauri::command]
+pub async fn restart_sidecar(state: State<'_, AppState>) -> Result<SidecarStatusPayload, String> {
+    state.sidecar.restart().map_err(|e| e.to_string())?;
+    Ok(state.sidecar.status_payload())
+}
+
+/// Get buffered sidecar log lines, oldest first
+/// `level` filters out less severe entries, `limit` keeps the most recent N
+#[tauri::command]
+pub fn get_sidecar_logs(
+    state: State<'_, AppState>,
+    level: Option<LogLevel>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    Ok(state.sidecar.state().logs().entries(level, limit))
+}
+
+/// Get every sensor the sidecar reports (voltages, fans, VRM temps, ...)
+#[tauri::command]
+pub fn get_all_sensors(state: State<'_, AppState>) -> Result<Vec<Sensor>, String> {
+    Ok(state.sidecar.state().get_sensors())
+}
+
+/// Start recording raw sidecar output
+/// Without `path` the recording goes to `<app data>/recordings/sidecar-<time>.rec`
+#[tauri::command]
+pub fn start_sidecar_recording(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?
+            .join("recordings")
+            .join(StreamRecorder::default_file_name()),
+    };
+
+    let path = state.sidecar.state().start_recording(path)?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Stop recording and return the path of the saved recording
+#[tauri::command]
+pub fn stop_sidecar_recording(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    Ok(state
+        .sidecar
+        .state()
+        .stop_recording()
+        .map(|p| p.to_string_lossy().into_owned()))
+}
+
+/// Replay a recording instead of live sidecar data
+/// `speed` scales the original pacing (default 1.0 = real time)
+#[tauri::command]
+pub async fn replay_sidecar_recording(
+    state: State<'_, AppState>,
+    path: String,
+    speed: Option<f64>,
+) -> Result<SidecarStatusPayload, String> {
+    state
+        .sidecar
+        .replay(Path::new(&path), speed.unwrap_or(1.0))
+        .map_err(|e| e.to_string())?;
+    Ok(state.sidecar.status_payload())
+}
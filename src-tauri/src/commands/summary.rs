@@ -0,0 +1,21 @@
+use std::sync::Arc;
+use tauri::State;
+
+use crate::models::SensorSummary;
+use crate::services::SensorSummaryTracker;
+
+/// Shared state for the running sensor aggregates
+pub struct SummaryState(pub Arc<SensorSummaryTracker>);
+
+/// Current / min / max / average of every stats field and sidecar sensor since the last reset
+#[tauri::command]
+pub fn get_sensor_summary(state: State<'_, SummaryState>) -> Result<SensorSummary, String> {
+    Ok(state.0.summary())
+}
+
+/// Start the min / max / average columns over from now
+#[tauri::command]
+pub fn reset_sensor_summary(state: State<'_, SummaryState>) -> Result<SensorSummary, String> {
+    state.0.reset();
+    Ok(state.0.summary())
+}
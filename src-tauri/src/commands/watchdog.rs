@@ -0,0 +1,15 @@
+use std::sync::Arc;
+use tauri::State;
+
+use crate::models::WatchdogEvent;
+use crate::services::Watchdog;
+
+/// Shared state for the process watchdog
+/// Rules themselves live in `Settings::watchdogs`
+pub struct WatchdogState(pub Arc<Watchdog>);
+
+/// Watchdog rules that are broken right now, oldest first
+#[tauri::command]
+pub fn get_watchdog_events(state: State<'_, WatchdogState>) -> Result<Vec<WatchdogEvent>, String> {
+    Ok(state.0.active())
+}
@@ -1,8 +1,8 @@
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{
-    Emitter, Manager,
+    Emitter, Manager, RunEvent,
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     menu::{Menu, MenuItem},
 };
@@ -12,91 +12,190 @@ mod models;
 mod services;
 mod utils;
 
-use commands::{get_system_stats, has_gpu_support, hide_mini_window, show_main_window, toggle_mini_mode, MonitorState};
-use services::{SystemMonitor, SidecarState, SidecarStatusInfo, start_sidecar};
+use commands::{
+    acknowledge_alert, compare_sessions, export_metrics, get_alert_history, get_active_alerts,
+    get_all_sensors, get_history, get_leak_suspects, get_recent_anomalies, get_sensor_summary,
+    get_settings, get_sidecar_logs, get_system_stats, get_session_playback, get_watchdog_events,
+    has_gpu_support, hide_mini_window, replay_sidecar_recording, reset_sensor_summary,
+    restart_sidecar, seek_session_playback, set_session_playback_speed, show_main_window,
+    start_session_playback, start_session_recording, start_sidecar, start_sidecar_recording,
+    stop_session_playback, stop_session_recording, stop_sidecar, stop_sidecar_recording,
+    toggle_mini_mode, update_settings, AlertState, AnomalyState, HistoryState, LeakState,
+    MonitorState, SessionState, SettingsState, SummaryState, WatchdogState,
+};
+use services::{
+    AlertEngine, AlertLog, AnomalyDetector, LeakDetector, MetricHistory, MetricStore,
+    SensorSummaryTracker, SessionManager, SystemMonitor, Notifier, SettingsStore, SidecarSupervisor,
+    Watchdog, init_sidecar, merge_sidecar_data, alert::AlertTransition, alert_action,
+    watchdog::{self, WatchdogTransition}, history::HISTORY_CAPACITY, notify::{self, Notification},
+};
+use models::{ActionTrigger, AlertEvent, AlertRule};
 
-/// Shared state for sidecar data
+/// How often old rows are pruned from the metric store
+const PRUNE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Shared state for the sidecar supervisor
 pub struct AppState {
-    pub sidecar: Arc<SidecarState>,
+    pub sidecar: Arc<SidecarSupervisor>,
 }
 
-/// Payload for sidecar status event
-#[derive(serde::Serialize, Clone)]
-struct SidecarStatusPayload {
-    #[serde(flatten)]
-    status: SidecarStatusInfo,
-    restart_count: u32,
-    can_restart: bool,
+/// Everything the stats emitter hands each sample to
+struct SampleSinks {
+    history: Arc<MetricHistory>,
+    store: Option<Arc<MetricStore>>,
+    session: Arc<SessionManager>,
+    summary: Arc<SensorSummaryTracker>,
+    alerts: Arc<AlertEngine>,
+    notifier: Arc<Notifier>,
+    anomalies: Arc<AnomalyDetector>,
+    leaks: Arc<LeakDetector>,
+    watchdog: Arc<Watchdog>,
 }
 
 /// Start a background thread that emits system stats every second
 /// Merges data from sysinfo (basic stats) with sidecar (temperatures)
-fn start_stats_emitter(app: tauri::AppHandle, sidecar_state: Arc<SidecarState>) {
+/// and keeps every sample in the metric history (memory and disk)
+/// and in the running min / max / average summary
+/// Alert rules, and the anomaly detector when enabled, check every live sample
+/// The leak detector, when enabled, follows the memory of every process
+/// and watchdog rules check the processes they match as soon as they're refreshed
+/// While a session plays back, live stats are recorded but not emitted
+fn start_stats_emitter(
+    app: tauri::AppHandle,
+    sidecar: Arc<SidecarSupervisor>,
+    settings: Arc<SettingsStore>,
+    sinks: SampleSinks,
+) {
+    let SampleSinks {
+        history, store, session, summary, alerts, notifier, anomalies, leaks, watchdog,
+    } = sinks;
     thread::spawn(move || {
         let mut monitor = SystemMonitor::new();
+        let sidecar_state = sidecar.state();
+        let mut last_prune: Option<Instant> = None;
+        let mut store_failing = false;
         
         // Wait a bit for sidecar to be ready
         thread::sleep(Duration::from_secs(2));
         
         loop {
-            // Refresh sysinfo data
-            monitor.refresh();
+            // Refresh sysinfo data, supervising the processes of watchdog rules
+            let current = settings.get();
+            monitor.refresh_supervised(&watchdog, &current.watchdogs, |transition, now| {
+                report_watchdog(&app, transition, now)
+            });
             let mut stats = monitor.get_system_stats();
             
-            // Merge temperature data from sidecar if available
-            if let Some(sidecar_data) = sidecar_state.get_data() {
-                // CPU temperature from sidecar
-                if let Some(cpu_data) = &sidecar_data.cpu {
-                    stats.cpu.temperature = cpu_data.temperature;
-                    stats.cpu.power = cpu_data.power;
-                    
-                    // Core temperatures - filter out None values
-                    if !cpu_data.core_temperatures.is_empty() {
-                        let temps: Vec<f32> = cpu_data.core_temperatures
-                            .iter()
-                            .filter_map(|t| *t)
-                            .collect();
-                        if !temps.is_empty() {
-                            stats.cpu.core_temperatures = Some(temps);
+            // Merge temperature data from sidecar while it's fresh
+            let sidecar_data = sidecar_state.get_data();
+            merge_sidecar_data(
+                &mut stats,
+                sidecar_data.as_ref(),
+                sidecar_state.data_age(),
+                &settings.get(),
+            );
+            
+            // Update the min / max / average columns
+            summary.record(&stats, sidecar_data.as_ref());
+            
+            // Check alert rules, notifying and running actions for the ones that ask
+            for transition in alerts.evaluate(&current.alerts, &stats) {
+                match transition {
+                    AlertTransition::Fired(alert) => {
+                        println!(
+                            "[Alert] {} fired: {} = {}",
+                            alert.rule_id, alert.metric, alert.value
+                        );
+                        let _ = app.emit("alert-fired", &alert);
+                        let Some(rule) = current.alerts.iter().find(|r| r.id == alert.rule_id)
+                        else {
+                            continue;
+                        };
+                        if rule.notify {
+                            notifier.notify(
+                                Notification::for_alert(&alert),
+                                &current.notifications,
+                                alert.fired_at,
+                            );
+                        }
+                        run_alert_actions(&app, &alerts, rule, ActionTrigger::Fired, &alert);
+                    }
+                    AlertTransition::Resolved(alert) => {
+                        println!(
+                            "[Alert] {} resolved: {} = {}",
+                            alert.rule_id, alert.metric, alert.value
+                        );
+                        let _ = app.emit("alert-resolved", &alert);
+                        if let Some(rule) = current.alerts.iter().find(|r| r.id == alert.rule_id) {
+                            run_alert_actions(&app, &alerts, rule, ActionTrigger::Resolved, &alert);
                         }
                     }
                 }
+            }
+            
+            // Flag values that are unusual for this machine
+            if current.anomaly.enabled {
+                for anomaly in anomalies.observe(&stats, &current.anomaly) {
+                    println!(
+                        "[Anomaly] {} {:?}: {:.1} (expected {:.1}, confidence {:.2})",
+                        anomaly.metric, anomaly.kind, anomaly.value, anomaly.expected,
+                        anomaly.confidence
+                    );
+                    let _ = app.emit("anomaly-detected", &anomaly);
+                }
+            }
+            
+            // Look for processes whose memory keeps growing
+            if current.leaks.enabled {
+                let processes = monitor.get_processes();
+                for suspect in
+                    leaks.record(&processes, stats.ram.available, stats.timestamp, &current.leaks)
+                {
+                    println!(
+                        "[Leak] {} ({}) grew {:.0} MB/hour for {} min",
+                        suspect.name, suspect.pid, suspect.growth_per_hour / (1024.0 * 1024.0),
+                        suspect.growing_secs / 60
+                    );
+                    let _ = app.emit("leak-suspected", &suspect);
+                }
+            }
+            
+            // Remember the sample so windows opened later can backfill
+            history.record(&stats);
+            
+            // Persist it, logging only the first of a run of failures
+            if let Some(store) = &store {
+                match store.record(&stats) {
+                    Ok(()) => store_failing = false,
+                    Err(e) if !store_failing => {
+                        eprintln!("[History] Failed to store sample: {}", e);
+                        store_failing = true;
+                    }
+                    Err(_) => {}
+                }
                 
-                // GPU data from sidecar (first GPU if available)
-                if let Some(gpu_data) = sidecar_data.gpu.first() {
-                    if let Some(ref mut gpu) = stats.gpu {
-                        // Use sidecar GPU temp if available
-                        if let Some(temp) = gpu_data.temperature {
-                            gpu.temperature = Some(temp);
-                        }
-                        // Hot spot temperature
-                        gpu.hot_spot_temperature = gpu_data.hot_spot_temperature;
-                        // Power consumption
-                        gpu.power = gpu_data.power;
-                        // Core clock
-                        gpu.core_clock = gpu_data.core_clock;
-                        // Memory clock
-                        gpu.memory_clock = gpu_data.memory_clock;
-                        // Use sidecar fan speed if available and we don't have it
-                        if gpu.fan_speed.is_none() {
-                            gpu.fan_speed = gpu_data.fan_speed;
-                        }
+                if last_prune.is_none_or(|t| t.elapsed() >= PRUNE_INTERVAL) {
+                    last_prune = Some(Instant::now());
+                    match store.prune(&settings.get().retention, stats.timestamp) {
+                        Ok(0) => {}
+                        Ok(n) => println!("[History] Pruned {} old rows", n),
+                        Err(e) => eprintln!("[History] {}", e),
                     }
                 }
             }
             
-            // Emit to all windows
-            if let Err(e) = app.emit("system-stats", &stats) {
+            // Append to the session being recorded, if any
+            session.record(&stats);
+            
+            // Emit to all windows, unless they're showing a recorded session
+            if let Some(status) = session.playback_status() {
+                let _ = app.emit("session-playback", Some(&status));
+            } else if let Err(e) = app.emit("system-stats", &stats) {
                 eprintln!("Failed to emit system-stats: {}", e);
             }
             
             // Emit sidecar status
-            let status_payload = SidecarStatusPayload {
-                status: sidecar_state.get_status_info(),
-                restart_count: sidecar_state.get_restart_count(),
-                can_restart: sidecar_state.can_restart(),
-            };
-            let _ = app.emit("sidecar-status", &status_payload);
+            let _ = app.emit("sidecar-status", &sidecar.status_payload());
             
             // Sleep for 1 second
             thread::sleep(Duration::from_secs(1));
@@ -104,6 +203,92 @@ fn start_stats_emitter(app: tauri::AppHandle, sidecar_state: Arc<SidecarState>)
     });
 }
 
+/// Run a rule's actions for an alert in the background
+/// Each outcome is filed in the alert history and emitted as "alert-action"
+fn run_alert_actions(
+    app: &tauri::AppHandle,
+    alerts: &Arc<AlertEngine>,
+    rule: &AlertRule,
+    trigger: ActionTrigger,
+    alert: &AlertEvent,
+) {
+    let actions: Vec<_> = rule
+        .actions
+        .iter()
+        .filter(|a| trigger == ActionTrigger::Fired || a.on_resolve)
+        .cloned()
+        .collect();
+    if actions.is_empty() {
+        return;
+    }
+
+    let app = app.clone();
+    let alerts = alerts.clone();
+    let (rule_id, fired_at) = (alert.rule_id.clone(), alert.fired_at);
+    let silenced = {
+        let (alerts, rule_id) = (alerts.clone(), rule_id.clone());
+        move || alerts.is_silenced(&rule_id, fired_at)
+    };
+    alert_action::spawn_actions(&actions, trigger, alert, silenced, move |outcome| {
+        match &outcome.error {
+            None => println!("[Alert] {} action {} succeeded", rule_id, outcome.action),
+            Some(e) => eprintln!(
+                "[Alert] {} action {} failed after {} attempt(s): {}",
+                rule_id, outcome.action, outcome.attempts, e
+            ),
+        }
+        if let Some(updated) = alerts.record_action(&rule_id, fired_at, outcome) {
+            let _ = app.emit("alert-action", &updated);
+        }
+    });
+}
+
+/// Log and emit a watchdog transition, relaunching the process if it asks to
+fn report_watchdog(app: &tauri::AppHandle, transition: WatchdogTransition, now: u64) {
+    match transition {
+        WatchdogTransition::Triggered(event) => {
+            println!(
+                "[Watchdog] {} {:?} (pid {:?}): {:.0}",
+                event.rule_id, event.condition, event.pid, event.value
+            );
+            let _ = app.emit("watchdog-triggered", &event);
+        }
+        WatchdogTransition::Cleared(event) => {
+            println!("[Watchdog] {} {:?} cleared", event.rule_id, event.condition);
+            let _ = app.emit("watchdog-cleared", &event);
+        }
+        WatchdogTransition::Restart(request) => {
+            let restart = watchdog::relaunch(&request, now);
+            match (&restart.pid, &restart.error) {
+                (Some(pid), _) => println!(
+                    "[Watchdog] {} restarted as pid {} (attempt {})",
+                    restart.rule_id, pid, restart.attempt
+                ),
+                (None, error) => eprintln!(
+                    "[Watchdog] {} failed to restart: {}",
+                    restart.rule_id,
+                    error.as_deref().unwrap_or_default()
+                ),
+            }
+            if restart.attempt >= watchdog::MAX_RESTARTS_PER_HOUR {
+                eprintln!("[Watchdog] {} won't be restarted again this hour", restart.rule_id);
+            }
+            let _ = app.emit("watchdog-restarted", &restart);
+        }
+    }
+}
+
+/// Show the main window and scroll it to a dashboard card
+/// Used as the click action of desktop notifications
+fn open_card(app: &tauri::AppHandle, card: &str) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+    let _ = app.emit("focus-card", card);
+}
+
 /// Setup system tray with menu
 fn setup_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
     // Create menu items
@@ -181,6 +366,34 @@ pub fn run() {
             toggle_mini_mode,
             show_main_window,
             hide_mini_window,
+            start_sidecar,
+            stop_sidecar,
+            restart_sidecar,
+            get_sidecar_logs,
+            get_all_sensors,
+            start_sidecar_recording,
+            stop_sidecar_recording,
+            replay_sidecar_recording,
+            get_settings,
+            update_settings,
+            get_history,
+            export_metrics,
+            start_session_recording,
+            stop_session_recording,
+            start_session_playback,
+            seek_session_playback,
+            set_session_playback_speed,
+            stop_session_playback,
+            get_session_playback,
+            compare_sessions,
+            get_sensor_summary,
+            reset_sensor_summary,
+            get_active_alerts,
+            get_alert_history,
+            acknowledge_alert,
+            get_recent_anomalies,
+            get_leak_suspects,
+            get_watchdog_events,
         ])
         .setup(|app| {
             println!("[App] Starting hardware monitor...");
@@ -190,17 +403,131 @@ pub fn run() {
                 eprintln!("[Tray] Failed to setup tray: {}", e);
             }
             
+            // Load persisted settings
+            let settings = match app.path().app_config_dir() {
+                Ok(dir) => Arc::new(SettingsStore::load(dir.join("settings.json"))),
+                Err(e) => {
+                    eprintln!("[Settings] No config dir, using defaults: {}", e);
+                    Arc::new(SettingsStore::in_memory())
+                }
+            };
+            app.manage(SettingsState(settings.clone()));
+            
+            // Desktop notifications - clicking one opens its card
+            let click_app = app.handle().clone();
+            let notifier = Arc::new(Notifier::new(move |notification| {
+                let app = click_app.clone();
+                notify::show_desktop(notification, move |card| open_card(&app, card));
+            }));
+            
             // Start the sidecar for temperature monitoring
             // The sidecar runs as elevated process and provides sensor data
-            let sidecar_state = start_sidecar(app.handle());
+            let sidecar = init_sidecar(app.handle(), &settings.get());
+            
+            // Tell the user when it keeps crashing and auto-restart gives up
+            let give_up_notifier = notifier.clone();
+            let give_up_settings = settings.clone();
+            sidecar.set_give_up_listener(move |error| {
+                give_up_notifier.notify(
+                    Notification::for_sidecar_failure(error),
+                    &give_up_settings.get().notifications,
+                    chrono::Utc::now().timestamp_millis() as u64,
+                );
+            });
             
-            // Store sidecar state for later access
+            // Store sidecar supervisor for commands and shutdown
             app.manage(AppState {
-                sidecar: sidecar_state.clone(),
+                sidecar: sidecar.clone(),
+            });
+            
+            // Metric history: the last hour in memory for chart backfill,
+            // everything within the retention periods on disk
+            let history = Arc::new(MetricHistory::new(HISTORY_CAPACITY));
+            let store = match app.path().app_data_dir() {
+                Ok(dir) => match MetricStore::open(&dir.join("metrics.db")) {
+                    Ok(store) => Some(Arc::new(store)),
+                    Err(e) => {
+                        eprintln!("[History] {}, keeping history in memory only", e);
+                        None
+                    }
+                },
+                Err(e) => {
+                    eprintln!("[History] No data dir, keeping history in memory only: {}", e);
+                    None
+                }
+            };
+            app.manage(HistoryState {
+                memory: history.clone(),
+                store: store.clone(),
+            });
+            
+            // Session recording and playback
+            let session = Arc::new(SessionManager::new());
+            app.manage(SessionState(session.clone()));
+            
+            // Running min / max / average per sensor
+            let summary = Arc::new(SensorSummaryTracker::new());
+            app.manage(SummaryState(summary.clone()));
+            
+            // Threshold alerts, with their rules in the settings and every
+            // fired alert logged to disk so overnight events are kept
+            let alert_log = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("No data dir: {}", e))
+                .and_then(|dir| AlertLog::open(&dir.join("alerts.db")));
+            let alerts = Arc::new(match alert_log {
+                Ok(log) => {
+                    let now = chrono::Utc::now().timestamp_millis() as u64;
+                    match log.close_interrupted(now) {
+                        Ok(0) => {}
+                        Ok(n) => println!("[Alert] Closed {} alert(s) left open at last exit", n),
+                        Err(e) => eprintln!("[Alert] {}", e),
+                    }
+                    let alert_days = settings.get().retention.alert_days;
+                    if alert_days > 0 {
+                        if let Err(e) = log.prune(alert_days, now) {
+                            eprintln!("[Alert] {}", e);
+                        }
+                    }
+                    AlertEngine::with_log(log)
+                }
+                Err(e) => {
+                    eprintln!("[Alert] {}, keeping alert history in memory only", e);
+                    AlertEngine::new()
+                }
             });
+            app.manage(AlertState(alerts.clone()));
+            
+            // Statistical anomaly detection, off unless enabled in the settings
+            let anomalies = Arc::new(AnomalyDetector::new());
+            app.manage(AnomalyState(anomalies.clone()));
+            
+            // Per-process leak detection
+            let leaks = Arc::new(LeakDetector::new());
+            app.manage(LeakState(leaks.clone()));
+            
+            // Process watchdog, for the rules in the settings
+            let watchdog = Arc::new(Watchdog::new());
+            app.manage(WatchdogState(watchdog.clone()));
             
             // Start the background stats emitter
-            start_stats_emitter(app.handle().clone(), sidecar_state);
+            start_stats_emitter(
+                app.handle().clone(),
+                sidecar,
+                settings,
+                SampleSinks {
+                    history,
+                    store,
+                    session,
+                    summary,
+                    alerts,
+                    notifier,
+                    anomalies,
+                    leaks,
+                    watchdog,
+                },
+            );
             
             // Handle window close event - hide to tray instead of quit
             let main_window = app.get_webview_window("main");
@@ -219,6 +546,18 @@ pub fn run() {
             println!("[App] Initialization complete");
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Kill the sidecar when the app exits (tray quit, last window closed)
+            if let RunEvent::Exit = event {
+                if let Some(state) = app.try_state::<AppState>() {
+                    state.sidecar.shutdown();
+                }
+                // Close the session file so its gzip stream is complete
+                if let Some(state) = app.try_state::<SessionState>() {
+                    let _ = state.0.stop_recording();
+                }
+            }
+        });
 }
@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+/// How urgent an alert is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    #[default]
+    Warning,
+    Critical,
+}
+
+/// Threshold rule evaluated against every stats sample
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertRule {
+    pub id: String,         // Unique, e.g. "cpu-hot"
+    pub condition: String,  // e.g. "cpu.temperature > 90 for 30s"
+    pub hysteresis: f64,    // Resolve only once the value is this far back past the threshold
+    pub cooldown_secs: u64, // Minimum time between two firings of the rule
+    pub severity: Severity,
+    pub enabled: bool,
+    pub notify: bool, // Show a desktop notification when it fires (opt-in)
+    pub actions: Vec<AlertAction>,
+}
+
+impl Default for AlertRule {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            condition: String::new(),
+            hysteresis: 0.0,
+            cooldown_secs: 300,
+            severity: Severity::Warning,
+            enabled: true,
+            notify: false,
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// A rule that fired, sent as "alert-fired" and, once over, "alert-resolved"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertEvent {
+    pub rule_id: String,
+    pub condition: String,
+    pub severity: Severity,
+    pub metric: String,
+    pub threshold: f64,
+    pub value: f64,               // When fired, or when resolved for "alert-resolved"
+    pub peak: f64,                // Furthest past the threshold while active
+    pub fired_at: u64,            // Unix ms
+    pub resolved_at: Option<u64>, // Unix ms (None while active)
+    #[serde(default)]
+    pub acknowledged_at: Option<u64>, // Unix ms, once the user silenced it
+    #[serde(default)]
+    pub actions: Vec<ActionOutcome>, // Filled in as the rule's actions finish
+}
+
+/// What an alert action does with the alert
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ActionKind {
+    /// Run a local program with the alert as JSON on stdin
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// POST the alert as JSON
+    Webhook { url: String },
+    /// Append the alert to a file as one JSON line
+    File { path: String },
+}
+
+/// Something a rule does when it fires (and optionally when it resolves)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertAction {
+    #[serde(flatten)]
+    pub kind: ActionKind,
+    #[serde(default = "default_action_timeout")]
+    pub timeout_secs: u64, // Per attempt
+    #[serde(default = "default_action_retries")]
+    pub retries: u32, // Extra attempts after a failure
+    #[serde(default)]
+    pub on_resolve: bool, // Run again when the alert resolves
+}
+
+fn default_action_timeout() -> u64 {
+    10
+}
+
+fn default_action_retries() -> u32 {
+    2
+}
+
+/// Which transition ran an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionTrigger {
+    Fired,
+    Resolved,
+}
+
+/// How running an action went, kept with its alert
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionOutcome {
+    pub action: String, // e.g. "webhook hooks.example.com"
+    pub trigger: ActionTrigger,
+    pub success: bool,
+    pub attempts: u32,
+    pub error: Option<String>, // Of the last failed attempt
+    pub duration_ms: u64,      // All attempts, including the waits between them
+    pub finished_at: u64,      // Unix ms
+}
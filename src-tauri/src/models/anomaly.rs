@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// What made a value unusual
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    Spike,      // Well above the recent baseline
+    Drop,       // Well below it, e.g. a busy CPU going idle
+    Creep,      // Rising steadily, e.g. memory filling up
+    HotForLoad, // A temperature above normal for the current load
+}
+
+/// An unusual value, sent as "anomaly-detected"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyEvent {
+    pub metric: String,
+    pub kind: AnomalyKind,
+    pub value: f64,
+    pub expected: f64,    // Baseline, or where a creep started
+    pub score: f64,       // Standard deviations off, or %/hour for a creep
+    pub confidence: f64,  // 0 - 1
+    pub detected_at: u64, // Unix ms
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+/// Basic facts about one compared session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub path: String,
+    pub started_at: u64,  // Unix ms of the first sample
+    pub duration_ms: u64, // First to last sample
+    pub samples: usize,
+}
+
+/// Distribution of one metric within a session
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricSummary {
+    pub min: f64,
+    pub avg: f64,
+    pub p95: f64,
+    pub max: f64,
+    pub samples: usize,
+}
+
+/// Session B minus session A
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub min: f64,
+    pub avg: f64,
+    pub p95: f64,
+    pub max: f64,
+}
+
+/// Welch's t-test of the two sessions' 10 s block means
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Significance {
+    pub t: f64,
+    pub p_value: f64,      // Two-sided
+    pub effect_size: f64,  // Cohen's d, positive when B is higher
+    pub significant: bool, // p < 0.05 and at least a small effect
+}
+
+/// Values of both sessions at the same offset from their start
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OverlayPoint {
+    pub offset_ms: u64, // Bucket start, relative to each session's first sample
+    pub a: Option<f64>, // Bucket average of session A
+    pub b: Option<f64>, // Bucket average of session B
+}
+
+/// Comparison of one metric across both sessions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricComparison {
+    pub metric: String,
+    pub unit: String,
+    pub a: Option<MetricSummary>, // None if the session never reported it
+    pub b: Option<MetricSummary>,
+    pub delta: Option<MetricDelta>,
+    pub significance: Option<Significance>, // None with too little data
+    pub overlay: Vec<OverlayPoint>,
+}
+
+/// Result of comparing two recorded sessions ("before" A, "after" B)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionComparison {
+    pub a: SessionInfo,
+    pub b: SessionInfo,
+    pub overlay_step_ms: u64,
+    pub metrics: Vec<MetricComparison>,
+    pub summary: Vec<String>, // Human-readable findings, most important first
+}
+
+/// Format of a written comparison report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
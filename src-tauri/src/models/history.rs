@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// One value of a metric at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistoryPoint {
+    pub timestamp: u64, // Unix timestamp in milliseconds (bucket start when aggregated)
+    pub value: f64,     // Sample value, or the bucket average
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>, // Lowest value in the bucket (aggregated points only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>, // Highest value in the bucket (aggregated points only)
+}
+
+impl HistoryPoint {
+    /// A single raw sample
+    pub fn sample(timestamp: u64, value: f64) -> Self {
+        Self {
+            timestamp,
+            value,
+            min: None,
+            max: None,
+        }
+    }
+}
+
+/// Time series of a single metric, oldest point first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricSeries {
+    pub metric: String, // e.g. "cpu.usage", "gpu.temperature", "cpu.core_usage.3"
+    pub points: Vec<HistoryPoint>,
+}
+
+/// Granularity of stored history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    Raw,    // Every sample (1 s)
+    Minute, // 1-minute min/avg/max rollups
+    Hour,   // 1-hour min/avg/max rollups
+}
+
+impl Resolution {
+    /// Bucket size in milliseconds (None for raw samples)
+    pub fn bucket_ms(self) -> Option<u64> {
+        match self {
+            Resolution::Raw => None,
+            Resolution::Minute => Some(60_000),
+            Resolution::Hour => Some(3_600_000),
+        }
+    }
+
+    /// Coarsest resolution that still has at least one point per `step` ms
+    pub fn for_step(step: Option<u64>) -> Self {
+        match step {
+            Some(step) if step >= 3_600_000 => Resolution::Hour,
+            Some(step) if step >= 60_000 => Resolution::Minute,
+            _ => Resolution::Raw,
+        }
+    }
+}
+
+/// Time range of a history query or export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimeRange {
+    pub from: Option<u64>, // Unix ms, inclusive (None = oldest available)
+    pub to: Option<u64>,   // Unix ms, inclusive (None = newest available)
+    pub step: Option<u64>, // Average into buckets of this many ms (None = every point)
+}
+
+/// File format of a metric export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,     // HWiNFO-style columns: Date, Time, then one column per metric
+    Ndjson,  // One JSON object per timestamp
+    Parquet, // Same columns as CSV, timestamp as a UTC millisecond timestamp
+}
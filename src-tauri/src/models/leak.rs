@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+/// A process whose memory keeps growing, sent as "leak-suspected"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LeakSuspect {
+    pub pid: u32,
+    pub name: String,
+    pub memory: u64,                  // Current RSS in bytes
+    pub growth_per_hour: f64,         // Bytes, from the fitted trend
+    pub growing_secs: u64,            // How long the trend covers
+    pub fit: f64,                     // r² of the trend (0 - 1)
+    pub exhaustion_secs: Option<u64>, // Until available memory runs out at this rate
+    pub detected_at: u64,             // Unix ms
+}
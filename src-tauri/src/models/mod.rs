@@ -1,3 +1,23 @@
+pub mod alert;
+pub mod anomaly;
+pub mod compare;
+pub mod history;
+pub mod leak;
+pub mod sensor;
+pub mod session;
+pub mod settings;
 pub mod stats;
+pub mod summary;
+pub mod watchdog;
 
+pub use alert::*;
+pub use anomaly::*;
+pub use compare::*;
+pub use history::*;
+pub use leak::*;
+pub use sensor::*;
+pub use session::*;
+pub use settings::*;
 pub use stats::*;
+pub use summary::*;
+pub use watchdog::*;
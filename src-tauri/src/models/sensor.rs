@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// A single reading from the sidecar's full sensor tree
+/// (every LibreHardwareMonitor sensor, not just the ones in `SystemStats`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sensor {
+    #[serde(default)]
+    pub id: String, // Stable identifier, e.g. "/lpc/nct6798d/voltage/0"
+    #[serde(default)]
+    pub hardware_id: String, // e.g. "/lpc/nct6798d"
+    #[serde(default)]
+    pub hardware_name: String, // e.g. "Nuvoton NCT6798D"
+    #[serde(default)]
+    pub hardware_type: String, // LHM hardware type, e.g. "SuperIO", "Storage"
+    #[serde(default)]
+    pub sensor_type: String, // LHM sensor type, e.g. "Voltage", "Fan"
+    #[serde(default)]
+    pub name: String,
+    pub value: Option<f32>,
+    pub min: Option<f32>, // Lowest value since the sidecar started
+    pub max: Option<f32>, // Highest value since the sidecar started
+    #[serde(default)]
+    pub unit: String, // e.g. "V", "RPM", "°C"
+}
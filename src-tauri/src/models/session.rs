@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Progress of a session playback, emitted as "session-playback"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaybackStatus {
+    pub path: String,
+    pub position_ms: u64, // Offset of the playhead from the session start
+    pub duration_ms: u64, // Offset of the last sample
+    pub speed: f64,       // 1.0 = real time
+    pub samples: usize,
+    pub started_at: u64, // Unix ms of the first sample
+    pub finished: bool,  // The last sample was emitted
+}
@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+
+use super::alert::{AlertRule, Severity};
+use super::watchdog::WatchdogRule;
+
+/// LibreHardwareMonitor hardware class the sidecar can read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HardwareClass {
+    Cpu,
+    Gpu,
+    Memory,
+    Motherboard, // Super I/O voltages, fans, VRM temperatures
+    Storage,     // SSD/HDD temperatures and SMART data
+    Network,
+    Battery,
+    Controller, // Fan/RGB controllers, AIO pumps
+    Psu,
+}
+
+impl HardwareClass {
+    /// Name used on the sidecar command line
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HardwareClass::Cpu => "cpu",
+            HardwareClass::Gpu => "gpu",
+            HardwareClass::Memory => "memory",
+            HardwareClass::Motherboard => "motherboard",
+            HardwareClass::Storage => "storage",
+            HardwareClass::Network => "network",
+            HardwareClass::Battery => "battery",
+            HardwareClass::Controller => "controller",
+            HardwareClass::Psu => "psu",
+        }
+    }
+
+    /// Comma-separated list as understood by `--hardware`
+    pub fn join(classes: &[HardwareClass]) -> String {
+        classes
+            .iter()
+            .map(|c| c.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// How long stored metric history is kept, per resolution, and the alert log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionSettings {
+    pub raw_hours: u32,   // Every 1 s sample
+    pub minute_days: u32, // 1-minute rollups
+    pub hour_days: u32,   // 1-hour rollups
+    pub alert_days: u32,  // Resolved alerts (0 keeps them forever)
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            raw_hours: 6,
+            minute_days: 7,
+            hour_days: 365,
+            alert_days: 0,
+        }
+    }
+}
+
+/// Desktop notification limits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub min_interval_secs: u64, // Per alert rule (or the sidecar)
+    pub max_per_hour: u32,      // Across everything
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_interval_secs: 300,
+            max_per_hour: 12,
+        }
+    }
+}
+
+/// Statistical anomaly detection (see `services::anomaly`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalySettings {
+    pub enabled: bool,
+    /// Standard deviations from the baseline that count as unusual
+    pub sensitivity: f64,
+    /// How far back the short-term baseline looks
+    pub baseline_minutes: u32,
+    /// Metrics checked for spikes and drops
+    pub metrics: Vec<String>,
+    /// Steady RAM growth that is flagged as a leak
+    pub memory_creep_percent_per_hour: f64,
+}
+
+impl Default for AnomalySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sensitivity: 4.0,
+            baseline_minutes: 10,
+            metrics: [
+                "cpu.usage",
+                "cpu.temperature",
+                "ram.usage_percent",
+                "gpu.usage",
+                "gpu.temperature",
+                "gpu.power",
+            ]
+            .map(String::from)
+            .to_vec(),
+            memory_creep_percent_per_hour: 5.0,
+        }
+    }
+}
+
+/// Per-process memory leak detection (see `services::leak`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeakSettings {
+    pub enabled: bool,
+    /// Steady growth below this isn't reported
+    pub min_growth_mb_per_hour: f64,
+    /// How long a process has to keep growing before it's a suspect
+    pub min_duration_minutes: u32,
+}
+
+impl Default for LeakSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_growth_mb_per_hour: 100.0,
+            min_duration_minutes: 30,
+        }
+    }
+}
+
+/// User settings, persisted as JSON in the app config dir
+/// Missing fields fall back to their defaults, so old files keep loading
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Sidecar readings older than this are dropped and marked stale (ms)
+    pub stale_threshold_ms: u64,
+    /// TjMax to use for thermal headroom when the CPU doesn't report one
+    /// (e.g. AMD) or to override it (Celsius)
+    pub tj_max_override: Option<f32>,
+    /// Read sensors from an already running collector instead of spawning
+    /// the sidecar, e.g. `/run/pulse/collector.sock` or `\\.\pipe\pulse-collector`
+    pub collector_endpoint: Option<String>,
+    /// Hardware classes the sidecar enables - more classes mean more
+    /// sensors but slower polling
+    pub hardware_classes: Vec<HardwareClass>,
+    /// How long the on-disk metric history keeps each resolution
+    pub retention: RetentionSettings,
+    /// Threshold alerts checked against every sample
+    pub alerts: Vec<AlertRule>,
+    /// Desktop notifications for opted-in alerts and sidecar failures
+    pub notifications: NotificationSettings,
+    /// Flag values that are unusual for this machine (off by default)
+    pub anomaly: AnomalySettings,
+    /// Processes whose memory keeps growing
+    pub leaks: LeakSettings,
+    /// Rules for processes that must keep running or stay within limits
+    pub watchdogs: Vec<WatchdogRule>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            stale_threshold_ms: 5000,
+            tj_max_override: None,
+            collector_endpoint: None,
+            hardware_classes: vec![
+                HardwareClass::Cpu,
+                HardwareClass::Gpu,
+                HardwareClass::Memory,
+                HardwareClass::Motherboard,
+                HardwareClass::Storage,
+                HardwareClass::Controller,
+                HardwareClass::Psu,
+            ],
+            retention: RetentionSettings::default(),
+            alerts: default_alerts(),
+            notifications: NotificationSettings::default(),
+            anomaly: AnomalySettings::default(),
+            leaks: LeakSettings::default(),
+            watchdogs: Vec::new(),
+        }
+    }
+}
+
+/// Alerts for the usual overheating and memory pressure cases, with notifications
+fn default_alerts() -> Vec<AlertRule> {
+    let rule = |id: &str, condition: &str, severity, hysteresis| AlertRule {
+        id: id.to_string(),
+        condition: condition.to_string(),
+        severity,
+        hysteresis,
+        ..Default::default()
+    };
+    vec![
+        rule(
+            "cpu-hot",
+            "cpu.temperature > 90 for 30s",
+            Severity::Warning,
+            5.0,
+        ),
+        rule(
+            "gpu-hot-spot",
+            "gpu.hot_spot_temperature > 100 for 10s",
+            Severity::Critical,
+            5.0,
+        ),
+        rule(
+            "ram-full",
+            "ram.usage_percent > 95 for 60s",
+            Severity::Warning,
+            2.0,
+        ),
+    ]
+}
@@ -36,6 +36,12 @@ pub struct CpuStats {
     pub temperature: Option<f32>,            // Celsius (from sidecar)
     pub core_temperatures: Option<Vec<f32>>, // Per-core temps (from sidecar)
     pub power: Option<f32>,                  // Watts (from sidecar)
+    pub package_temperature: Option<f32>,    // Celsius (from sidecar)
+    pub max_temperature: Option<f32>,        // Hottest core, Celsius (from sidecar)
+    pub tj_max: Option<f32>,                 // Throttle temperature, Celsius
+    pub thermal_headroom: Option<f32>,       // TjMax - package temperature
+    pub core_thermal_headroom: Option<Vec<f32>>, // TjMax - core temperature, per core
+    pub core_powers: Option<Vec<f32>>,       // Per-core Watts (from sidecar)
 }
 
 /// RAM/Memory statistics
@@ -60,10 +66,28 @@ pub struct GpuStats {
     pub power: Option<f32>,                // Watts (from sidecar)
     pub core_clock: Option<f32>,           // MHz (from sidecar)
     pub memory_clock: Option<f32>,         // MHz (from sidecar)
+    pub load: Option<f32>,                 // 0-100% core load (from sidecar)
+    pub vendor: Option<String>,            // "NVIDIA", "AMD" or "Intel"
+}
+
+/// Freshness of one data source
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SourceFreshness {
+    pub timestamp: Option<u64>, // Unix ms of the last sample (None = never received)
+    pub age_ms: Option<u64>,    // Age of that sample when these stats were built
+    pub stale: bool,            // Older than the configured threshold
+}
+
+/// Per-source sample timestamps and the fields dropped because they're stale
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataFreshness {
+    pub sysinfo: SourceFreshness,
+    pub sidecar: SourceFreshness,
+    pub stale_fields: Vec<String>, // e.g. "cpu.temperature", "gpu.power"
 }
 
 /// Combined system statistics payload
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SystemStats {
     pub cpu: CpuStats,
     pub ram: RamStats,
@@ -71,6 +95,7 @@ pub struct SystemStats {
     pub system_info: SystemInfo,
     pub processes: Vec<ProcessInfo>,
     pub timestamp: u64, // Unix timestamp in milliseconds
+    pub freshness: DataFreshness,
 }
 
 impl Default for CpuStats {
@@ -85,6 +110,12 @@ impl Default for CpuStats {
             temperature: None,
             core_temperatures: None,
             power: None,
+            package_temperature: None,
+            max_temperature: None,
+            tj_max: None,
+            thermal_headroom: None,
+            core_thermal_headroom: None,
+            core_powers: None,
         }
     }
 }
@@ -113,6 +144,8 @@ impl Default for GpuStats {
             power: None,
             core_clock: None,
             memory_clock: None,
+            load: None,
+            vendor: None,
         }
     }
 }
@@ -133,16 +166,3 @@ impl Default for SystemInfo {
         }
     }
 }
-
-impl Default for SystemStats {
-    fn default() -> Self {
-        Self {
-            cpu: CpuStats::default(),
-            ram: RamStats::default(),
-            gpu: None,
-            system_info: SystemInfo::default(),
-            processes: Vec::new(),
-            timestamp: 0,
-        }
-    }
-}
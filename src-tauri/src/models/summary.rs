@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// Current / min / max / average of one value since the last reset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SummaryEntry {
+    pub id: String,           // Stats field path (e.g. "cpu.usage") or sensor id
+    pub name: String,         // Display name
+    pub unit: String,         // e.g. "°C", "%", "V" ("" when unitless)
+    pub current: Option<f64>, // None when the latest sample didn't have it
+    pub min: f64,
+    pub max: f64,
+    pub average: f64,
+    pub samples: u64,
+}
+
+/// Running aggregates of every numeric stats field and sidecar sensor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SensorSummary {
+    pub since: u64,                 // Unix ms of the last reset (or app start)
+    pub updated: Option<u64>,       // Unix ms of the newest sample (None = nothing yet)
+    pub stats: Vec<SummaryEntry>,   // SystemStats fields, ordered by id
+    pub sensors: Vec<SummaryEntry>, // Sidecar sensors, in sidecar order
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Supervision rule for the processes whose name or command line matches a pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogRule {
+    pub id: String,      // Unique, e.g. "api-server"
+    pub pattern: String, // Case-insensitive substring of the name or command line
+    pub enabled: bool,
+    pub must_run: bool,          // Report when no process matches
+    pub max_cpu: Option<f32>,    // Percent of one core
+    pub cpu_for_secs: u64,       // How long a process has to stay above max_cpu
+    pub max_memory: Option<u64>, // Bytes
+    pub restart: bool,           // Relaunch the last matching process when it exits
+}
+
+impl Default for WatchdogRule {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            pattern: String::new(),
+            enabled: true,
+            must_run: false,
+            max_cpu: None,
+            cpu_for_secs: 120,
+            max_memory: None,
+            restart: false,
+        }
+    }
+}
+
+/// What a watchdog rule caught
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogCondition {
+    NotRunning,
+    HighCpu,
+    HighMemory,
+}
+
+/// A broken watchdog rule, sent as "watchdog-triggered" and, once over, "watchdog-cleared"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchdogEvent {
+    pub rule_id: String,
+    pub condition: WatchdogCondition,
+    pub pid: Option<u32>, // Of the offending process (None for not running)
+    pub name: Option<String>,
+    pub value: f64,              // CPU percent or memory bytes (0 for not running)
+    pub limit: f64,              // max_cpu or max_memory (0 for not running)
+    pub triggered_at: u64,       // Unix ms
+    pub cleared_at: Option<u64>, // Unix ms (None while active)
+}
+
+/// A watched process being relaunched, sent as "watchdog-restarted"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchdogRestart {
+    pub rule_id: String,
+    pub command: Vec<String>,
+    pub pid: Option<u32>,      // Of the new process (None if it failed to start)
+    pub error: Option<String>, // Why it failed to start
+    pub attempt: usize,        // Restarts of the rule within the last hour, this one included
+    pub restarted_at: u64,     // Unix ms
+}
+
+/// A process as the watchdog sees it, with the command line to relaunch it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedProcess {
+    pub pid: u32,
+    pub name: String,
+    pub cmd: Vec<String>, // Empty when it can't be read
+    pub cwd: Option<PathBuf>,
+    pub cpu_usage: f32,
+    pub memory: u64,
+}
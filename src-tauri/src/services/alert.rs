@@ -0,0 +1,731 @@
+//! Threshold alert engine
+//!
+//! Rules read like `cpu.temperature > 90 for 30s`: a metric name as used by
+//! the history (`metric_values`), a comparison, a threshold and an optional
+//! duration the condition has to hold before the rule fires. A fired alert
+//! stays active until the value is back past the threshold by the rule's
+//! hysteresis, and a rule won't fire again within its cooldown.
+//!
+//! Fired alerts are also kept in a bounded history - and, with an
+//! `AlertLog` attached, on disk - where later changes (peak, resolution,
+//! acknowledgement and the outcomes of the rule's actions, see
+//! `alert_action`) are filed as they happen.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+use super::alert_action::validate_action;
+use super::alert_log::AlertLog;
+use super::history::metric_values;
+use crate::models::{ActionOutcome, AlertEvent, AlertRule, SystemStats};
+
+/// Fired alerts kept in the in-memory history
+pub const ALERT_HISTORY_CAPACITY: usize = 500;
+
+/// Longest allowed cooldown (one week)
+pub const MAX_COOLDOWN_SECS: u64 = 7 * 24 * 3600;
+
+/// Comparison of a rule condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Above,        // >
+    AboveOrEqual, // >=
+    Below,        // <
+    BelowOrEqual, // <=
+}
+
+impl Comparison {
+    fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::AboveOrEqual => value >= threshold,
+            Comparison::Below => value < threshold,
+            Comparison::BelowOrEqual => value <= threshold,
+        }
+    }
+
+    /// Whether the value has come back past `threshold` by `hysteresis`
+    fn cleared(self, value: f64, threshold: f64, hysteresis: f64) -> bool {
+        match self {
+            Comparison::Above | Comparison::AboveOrEqual => value <= threshold - hysteresis,
+            Comparison::Below | Comparison::BelowOrEqual => value >= threshold + hysteresis,
+        }
+    }
+
+    /// The more extreme of two values in the alerting direction
+    fn worse(self, a: f64, b: f64) -> f64 {
+        match self {
+            Comparison::Above | Comparison::AboveOrEqual => a.max(b),
+            Comparison::Below | Comparison::BelowOrEqual => a.min(b),
+        }
+    }
+}
+
+/// Parsed form of `AlertRule::condition`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub metric: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    pub duration_ms: u64, // How long it has to hold (0 = fire on the first sample)
+}
+
+impl Condition {
+    pub fn parse(condition: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("Invalid condition {:?}: {}", condition, reason);
+
+        let op_start = condition
+            .find(['<', '>'])
+            .ok_or_else(|| invalid("expected one of >, >=, <, <="))?;
+        let metric = condition[..op_start].trim();
+        if metric.is_empty() || metric.contains(char::is_whitespace) {
+            return Err(invalid("expected a metric name before the comparison"));
+        }
+
+        let rest = &condition[op_start..];
+        let (comparison, rest) = if let Some(rest) = rest.strip_prefix(">=") {
+            (Comparison::AboveOrEqual, rest)
+        } else if let Some(rest) = rest.strip_prefix("<=") {
+            (Comparison::BelowOrEqual, rest)
+        } else if let Some(rest) = rest.strip_prefix('>') {
+            (Comparison::Above, rest)
+        } else {
+            (Comparison::Below, &rest[1..])
+        };
+
+        let mut words = rest.split_whitespace();
+        let threshold: f64 = words
+            .next()
+            .and_then(|w| w.parse().ok())
+            .filter(|t: &f64| t.is_finite())
+            .ok_or_else(|| invalid("expected a number after the comparison"))?;
+
+        let duration_ms = match (words.next(), words.next(), words.next()) {
+            (None, _, _) => 0,
+            (Some("for"), Some(duration), None) => parse_duration(duration)
+                .ok_or_else(|| invalid("expected a duration like 30s, 5m or 1h"))?,
+            _ => return Err(invalid("expected \"for <duration>\" after the threshold")),
+        };
+
+        Ok(Self {
+            metric: metric.to_string(),
+            comparison,
+            threshold,
+            duration_ms,
+        })
+    }
+}
+
+/// "500ms", "30s", "5m" or "1h" in milliseconds
+fn parse_duration(text: &str) -> Option<u64> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = text.split_at(split);
+    let amount: u64 = amount.parse().ok()?;
+    let scale = match unit {
+        "ms" => 1,
+        "s" => 1000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        _ => return None,
+    };
+    amount.checked_mul(scale)
+}
+
+/// Check every rule's condition and that ids are unique and non-empty
+pub fn validate_rules(rules: &[AlertRule]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for rule in rules {
+        if rule.id.trim().is_empty() {
+            return Err("alert rule ids must not be empty".to_string());
+        }
+        if !ids.insert(rule.id.as_str()) {
+            return Err(format!("Duplicate alert rule id {:?}", rule.id));
+        }
+        Condition::parse(&rule.condition)?;
+        if rule.cooldown_secs > MAX_COOLDOWN_SECS {
+            return Err(format!(
+                "Alert rule {:?}: cooldown_secs must be at most {}",
+                rule.id, MAX_COOLDOWN_SECS
+            ));
+        }
+        if !rule.hysteresis.is_finite() || rule.hysteresis < 0.0 {
+            return Err(format!(
+                "Alert rule {:?}: hysteresis must not be negative",
+                rule.id
+            ));
+        }
+        for action in &rule.actions {
+            validate_action(action).map_err(|e| format!("Alert rule {:?}: {}", rule.id, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// A rule changing state, to be emitted as an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertTransition {
+    Fired(AlertEvent),
+    Resolved(AlertEvent),
+}
+
+/// Evaluation state of one rule
+#[derive(Debug, Default)]
+struct RuleState {
+    condition: String,          // Condition the state belongs to - reset when edited
+    pending_since: Option<u64>, // First sample of the current breach
+    last_fired: Option<u64>,
+    active: Option<AlertEvent>,
+}
+
+/// Evaluates alert rules against stats samples
+#[derive(Default)]
+pub struct AlertEngine {
+    states: Mutex<HashMap<String, RuleState>>,
+    history: Mutex<VecDeque<AlertEvent>>, // Oldest first
+    log: Option<AlertLog>,
+    /// Changes not written to the log yet, in the order they happened
+    pending: Mutex<Vec<AlertEvent>>,
+    /// Held while writing, so the log sees the changes in order
+    writing: Mutex<()>,
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Engine that also writes every alert to `log`, and reads its history from there
+    pub fn with_log(log: AlertLog) -> Self {
+        Self {
+            log: Some(log),
+            ..Default::default()
+        }
+    }
+
+    /// Run every enabled rule against a sample, returning the alerts that
+    /// fired or resolved with it
+    /// Rules that were removed, disabled or edited lose their state silently.
+    pub fn evaluate(&self, rules: &[AlertRule], stats: &SystemStats) -> Vec<AlertTransition> {
+        let Ok(mut states) = self.states.lock() else {
+            return Vec::new();
+        };
+        states.retain(|id, state| {
+            rules
+                .iter()
+                .any(|r| r.enabled && &r.id == id && r.condition == state.condition)
+        });
+
+        let values: HashMap<String, f64> = metric_values(stats).into_iter().collect();
+        let now = stats.timestamp;
+        let mut transitions = Vec::new();
+
+        for rule in rules.iter().filter(|r| r.enabled) {
+            // Rules are validated when saved, so this only skips hand-edited files
+            let Ok(condition) = Condition::parse(&rule.condition) else {
+                continue;
+            };
+            let state = states.entry(rule.id.clone()).or_insert_with(|| RuleState {
+                condition: rule.condition.clone(),
+                ..Default::default()
+            });
+            // Missing data neither starts, continues nor ends an alert
+            let Some(&value) = values.get(&condition.metric) else {
+                state.pending_since = None;
+                continue;
+            };
+
+            if let Some(active) = &mut state.active {
+                let peak = condition.comparison.worse(active.peak, value);
+                if condition
+                    .comparison
+                    .cleared(value, condition.threshold, rule.hysteresis)
+                {
+                    let mut resolved = state.active.take().unwrap();
+                    resolved.peak = peak;
+                    resolved.value = value;
+                    resolved.resolved_at = Some(now);
+                    state.pending_since = None;
+                    self.save(&resolved);
+                    transitions.push(AlertTransition::Resolved(resolved));
+                } else if peak != active.peak {
+                    active.peak = peak;
+                    self.save(active);
+                }
+                continue;
+            }
+
+            if !condition.comparison.holds(value, condition.threshold) {
+                state.pending_since = None;
+                continue;
+            }
+            let since = *state.pending_since.get_or_insert(now);
+            let held = now.saturating_sub(since) >= condition.duration_ms;
+            let cooled_down = state
+                .last_fired
+                .is_none_or(|t| now.saturating_sub(t) >= rule.cooldown_secs.saturating_mul(1000));
+            if held && cooled_down {
+                let event = AlertEvent {
+                    rule_id: rule.id.clone(),
+                    condition: rule.condition.clone(),
+                    severity: rule.severity,
+                    metric: condition.metric.clone(),
+                    threshold: condition.threshold,
+                    value,
+                    peak: value,
+                    fired_at: now,
+                    resolved_at: None,
+                    acknowledged_at: None,
+                    actions: Vec::new(),
+                };
+                state.last_fired = Some(now);
+                state.active = Some(event.clone());
+                self.save(&event);
+                transitions.push(AlertTransition::Fired(event));
+            }
+        }
+        drop(states);
+        self.flush();
+        transitions
+    }
+
+    /// Alerts that fired and haven't resolved yet
+    /// Unacknowledged ones come first, then the most severe.
+    pub fn active(&self) -> Vec<AlertEvent> {
+        let Ok(states) = self.states.lock() else {
+            return Vec::new();
+        };
+        let mut active: Vec<AlertEvent> =
+            states.values().filter_map(|s| s.active.clone()).collect();
+        active.sort_by(|a, b| {
+            a.acknowledged_at
+                .is_some()
+                .cmp(&b.acknowledged_at.is_some())
+                .then(b.severity.cmp(&a.severity))
+                .then(a.fired_at.cmp(&b.fired_at))
+        });
+        active
+    }
+
+    /// Alerts that were active at some point within `from..=to` (Unix ms),
+    /// optionally of one rule, newest first
+    /// Read from the alert log when there is one, else from memory.
+    pub fn history(
+        &self,
+        from: Option<u64>,
+        to: Option<u64>,
+        rule_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<AlertEvent>, String> {
+        if let Some(log) = &self.log {
+            return log.query(from, to, rule_id, limit);
+        }
+        let history = self
+            .history
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        Ok(history
+            .iter()
+            .rev()
+            .filter(|e| {
+                e.fired_at <= to.unwrap_or(u64::MAX)
+                    && e.resolved_at.is_none_or(|t| t >= from.unwrap_or(0))
+                    && rule_id.is_none_or(|id| e.rule_id == id)
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    /// Silence the active alert of a rule
+    /// It stays active until its value recovers, but sorts after unacknowledged
+    /// ones and runs no more notifications or actions (see `is_silenced`).
+    pub fn acknowledge(&self, rule_id: &str, now: u64) -> Result<AlertEvent, String> {
+        let mut states = self
+            .states
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let active = states
+            .get_mut(rule_id)
+            .and_then(|s| s.active.as_mut())
+            .ok_or_else(|| format!("No active alert for rule {:?}", rule_id))?;
+        if active.acknowledged_at.is_none() {
+            active.acknowledged_at = Some(now);
+            self.save(active);
+        }
+        let acknowledged = active.clone();
+        drop(states);
+        self.flush();
+        Ok(acknowledged)
+    }
+
+    /// Whether the alert of `rule_id` that fired at `fired_at` is still
+    /// active and acknowledged - nothing more should be sent for it until
+    /// it resolves
+    pub fn is_silenced(&self, rule_id: &str, fired_at: u64) -> bool {
+        let Ok(states) = self.states.lock() else {
+            return false;
+        };
+        states
+            .get(rule_id)
+            .and_then(|s| s.active.as_ref())
+            .is_some_and(|a| a.fired_at == fired_at && a.acknowledged_at.is_some())
+    }
+
+    /// File the outcome of an action under the alert it ran for
+    /// Returns the updated alert, or None once it dropped out of the history
+    pub fn record_action(
+        &self,
+        rule_id: &str,
+        fired_at: u64,
+        outcome: ActionOutcome,
+    ) -> Option<AlertEvent> {
+        let mut states = self.states.lock().ok()?;
+        let active = states
+            .get_mut(rule_id)
+            .and_then(|s| s.active.as_mut())
+            .filter(|a| a.fired_at == fired_at);
+        let updated = match active {
+            Some(active) => {
+                active.actions.push(outcome);
+                active.clone()
+            }
+            // Resolve actions finish after their alert resolved
+            None => {
+                let history = self.history.lock().ok()?;
+                let mut event = history
+                    .iter()
+                    .rev()
+                    .find(|e| e.rule_id == rule_id && e.fired_at == fired_at)?
+                    .clone();
+                event.actions.push(outcome);
+                event
+            }
+        };
+        self.save(&updated);
+        drop(states);
+        self.flush();
+        Some(updated)
+    }
+
+    /// Put the latest state of an alert in the history and queue it for the log
+    /// Callers hold the states lock, so it's always taken before the history's
+    /// and the queue keeps the order of the changes. They `flush` once they
+    /// released it, so a slow disk never holds up evaluation.
+    fn save(&self, event: &AlertEvent) {
+        if let Ok(mut history) = self.history.lock() {
+            let existing = history
+                .iter_mut()
+                .rev()
+                .find(|e| e.rule_id == event.rule_id && e.fired_at == event.fired_at);
+            match existing {
+                Some(entry) => *entry = event.clone(),
+                None => {
+                    if history.len() == ALERT_HISTORY_CAPACITY {
+                        history.pop_front();
+                    }
+                    history.push_back(event.clone());
+                }
+            }
+        }
+        if self.log.is_some() {
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.push(event.clone());
+            }
+        }
+    }
+
+    /// Write the queued changes to the log, oldest first
+    fn flush(&self) {
+        let Some(log) = &self.log else {
+            return;
+        };
+        // Whoever writes first takes everything queued so far - later changes
+        // wait for the next flush, so the log never goes back in time
+        let Ok(_writing) = self.writing.lock() else {
+            return;
+        };
+        let events = match self.pending.lock() {
+            Ok(mut pending) => std::mem::take(&mut *pending),
+            Err(_) => return,
+        };
+        for event in &events {
+            if let Err(e) = log.record(event) {
+                eprintln!("[Alert] {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActionTrigger, Severity};
+
+    fn rule(condition: &str) -> AlertRule {
+        AlertRule {
+            id: "test".to_string(),
+            condition: condition.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn cpu_temp(seconds: u64, temperature: f32) -> SystemStats {
+        let mut stats = SystemStats {
+            timestamp: seconds * 1000,
+            ..Default::default()
+        };
+        stats.cpu.temperature = Some(temperature);
+        stats
+    }
+
+    /// Feed one temperature per second, returning (second, fired?) per transition
+    fn run(engine: &AlertEngine, rules: &[AlertRule], temps: &[(u64, f32)]) -> Vec<(u64, bool)> {
+        temps
+            .iter()
+            .flat_map(|&(s, t)| engine.evaluate(rules, &cpu_temp(s, t)))
+            .map(|t| match t {
+                AlertTransition::Fired(e) => (e.fired_at / 1000, true),
+                AlertTransition::Resolved(e) => (e.resolved_at.unwrap() / 1000, false),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_conditions() {
+        let c = Condition::parse("cpu.temperature > 90 for 30s").unwrap();
+        assert_eq!(c.metric, "cpu.temperature");
+        assert_eq!(c.comparison, Comparison::Above);
+        assert_eq!(c.threshold, 90.0);
+        assert_eq!(c.duration_ms, 30_000);
+
+        let c = Condition::parse("ram.usage_percent>=95.5").unwrap();
+        assert_eq!(c.comparison, Comparison::AboveOrEqual);
+        assert_eq!((c.threshold, c.duration_ms), (95.5, 0));
+
+        let c = Condition::parse("cpu.thermal_headroom < 5 for 2m").unwrap();
+        assert_eq!(c.comparison, Comparison::Below);
+        assert_eq!(c.duration_ms, 120_000);
+
+        for bad in [
+            "",
+            "cpu.temperature 90",
+            "> 90",
+            "cpu temperature > 90",
+            "cpu.temperature > hot",
+            "cpu.temperature > 90 for",
+            "cpu.temperature > 90 for 30x",
+            "cpu.temperature > 90 during 30s",
+        ] {
+            assert!(Condition::parse(bad).is_err(), "{:?} parsed", bad);
+        }
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        assert!(validate_rules(&[rule("gpu.power > 300")]).is_ok());
+        assert!(validate_rules(&[rule("gpu.power > 300"), rule("gpu.power > 350")]).is_err());
+        assert!(validate_rules(&[AlertRule {
+            hysteresis: -1.0,
+            ..rule("gpu.power > 300")
+        }])
+        .is_err());
+        assert!(validate_rules(&[AlertRule {
+            cooldown_secs: u64::MAX,
+            ..rule("gpu.power > 300")
+        }])
+        .is_err());
+    }
+
+    #[test]
+    fn test_sustained_duration() {
+        let engine = AlertEngine::new();
+        let rules = [rule("cpu.temperature > 90 for 3s")];
+        // A 2 s spike doesn't fire, 3 s of heat does
+        let transitions = run(
+            &engine,
+            &rules,
+            &[
+                (0, 95.0),
+                (1, 95.0),
+                (2, 80.0),
+                (3, 95.0),
+                (4, 95.0),
+                (5, 95.0),
+                (6, 96.0),
+            ],
+        );
+        assert_eq!(transitions, vec![(6, true)]);
+        assert_eq!(engine.active()[0].peak, 96.0);
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let engine = AlertEngine::new();
+        let rules = [AlertRule {
+            hysteresis: 5.0,
+            ..rule("cpu.temperature > 90")
+        }];
+        let transitions = run(
+            &engine,
+            &rules,
+            &[(0, 91.0), (1, 88.0), (2, 91.0), (3, 85.0), (4, 84.0)],
+        );
+        assert_eq!(transitions, vec![(0, true), (3, false)]);
+        assert!(engine.active().is_empty());
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let engine = AlertEngine::new();
+        let rules = [AlertRule {
+            cooldown_secs: 10,
+            ..rule("cpu.temperature > 90")
+        }];
+        let transitions = run(
+            &engine,
+            &rules,
+            &[(0, 95.0), (1, 80.0), (2, 95.0), (9, 95.0), (10, 95.0)],
+        );
+        // Held back at 2 and 9, fires as soon as the cooldown is over
+        assert_eq!(transitions, vec![(0, true), (1, false), (10, true)]);
+    }
+
+    #[test]
+    fn test_below_and_severity_order() {
+        let engine = AlertEngine::new();
+        let rules = [
+            rule("cpu.temperature < 20"),
+            AlertRule {
+                id: "cold".to_string(),
+                severity: Severity::Critical,
+                ..rule("cpu.temperature < 30")
+            },
+        ];
+        run(&engine, &rules, &[(0, 15.0)]);
+        let active = engine.active();
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].rule_id, "cold");
+        assert_eq!(active[1].threshold, 20.0);
+    }
+
+    #[test]
+    fn test_edited_or_disabled_rule_resets() {
+        let engine = AlertEngine::new();
+        run(&engine, &[rule("cpu.temperature > 90")], &[(0, 95.0)]);
+        assert_eq!(engine.active().len(), 1);
+
+        // Editing the condition drops the old state, so it fires afresh
+        let edited = [rule("cpu.temperature > 92")];
+        assert_eq!(run(&engine, &edited, &[(1, 95.0)]), vec![(1, true)]);
+
+        let disabled = [AlertRule {
+            enabled: false,
+            ..rule("cpu.temperature > 92")
+        }];
+        assert!(run(&engine, &disabled, &[(2, 95.0)]).is_empty());
+        assert!(engine.active().is_empty());
+    }
+
+    #[test]
+    fn test_history_and_action_outcomes() {
+        let engine = AlertEngine::new();
+        let rules = [rule("cpu.temperature > 90")];
+        run(&engine, &rules, &[(0, 95.0), (1, 97.0)]);
+        assert_eq!(engine.history(None, None, None, 10).unwrap()[0].peak, 97.0);
+
+        let outcome = ActionOutcome {
+            action: "file /tmp/alerts.log".to_string(),
+            trigger: ActionTrigger::Fired,
+            success: true,
+            attempts: 1,
+            error: None,
+            duration_ms: 3,
+            finished_at: 1500,
+        };
+        let updated = engine.record_action("test", 0, outcome.clone()).unwrap();
+        assert_eq!(updated.actions, vec![outcome.clone()]);
+        assert_eq!(engine.active()[0].actions.len(), 1);
+        assert!(engine.record_action("test", 999, outcome).is_none());
+
+        run(&engine, &rules, &[(2, 80.0)]);
+        let history = engine.history(None, None, None, 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].resolved_at, Some(2000));
+        assert_eq!((history[0].peak, history[0].actions.len()), (97.0, 1));
+    }
+
+    #[test]
+    fn test_acknowledge() {
+        let engine = AlertEngine::new();
+        let rules = [
+            rule("cpu.temperature > 90"),
+            AlertRule {
+                id: "cold".to_string(),
+                severity: Severity::Critical,
+                ..rule("cpu.temperature < 100")
+            },
+        ];
+        run(&engine, &rules, &[(0, 95.0)]);
+        assert!(engine.acknowledge("missing", 500).is_err());
+
+        let acked = engine.acknowledge("cold", 500).unwrap();
+        assert_eq!(acked.acknowledged_at, Some(500));
+        // Acknowledging again keeps the first time
+        assert_eq!(
+            engine.acknowledge("cold", 900).unwrap().acknowledged_at,
+            Some(500)
+        );
+
+        // Silenced alerts sort last, however severe
+        let active = engine.active();
+        assert_eq!(active[0].rule_id, "test");
+        assert_eq!(active[1].rule_id, "cold");
+        let history = engine.history(None, None, Some("cold"), 10).unwrap();
+        assert_eq!(history[0].acknowledged_at, Some(500));
+
+        // No more notifications or actions for it - until it resolves
+        assert!(engine.is_silenced("cold", 0));
+        assert!(!engine.is_silenced("test", 0));
+        run(&engine, &rules, &[(1, 105.0)]);
+        assert!(!engine.is_silenced("cold", 0));
+    }
+
+    #[test]
+    fn test_alert_log() {
+        let engine = AlertEngine::with_log(AlertLog::open_in_memory().unwrap());
+        let rules = [rule("cpu.temperature > 90")];
+        run(
+            &engine,
+            &rules,
+            &[(0, 95.0), (1, 98.0), (2, 80.0), (400, 91.0)],
+        );
+
+        let history = engine.history(None, None, None, 10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].fired_at, 400_000);
+        assert_eq!(history[1].resolved_at, Some(2000));
+        assert_eq!(history[1].peak, 98.0);
+        // Only the first alert was active within 0..=10 s
+        assert_eq!(
+            engine
+                .history(Some(0), Some(10_000), None, 10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_missing_metric_keeps_alert() {
+        let engine = AlertEngine::new();
+        let rules = [rule("cpu.temperature > 90")];
+        run(&engine, &rules, &[(0, 95.0)]);
+
+        let no_sidecar = SystemStats {
+            timestamp: 1000,
+            ..Default::default()
+        };
+        assert!(engine.evaluate(&rules, &no_sidecar).is_empty());
+        assert_eq!(engine.active().len(), 1);
+    }
+}
@@ -0,0 +1,520 @@
+//! Alert actions
+//!
+//! Besides notifying, a rule can run actions when it fires: a local command
+//! that gets the alert as JSON on stdin, a webhook that gets it POSTed, or a
+//! log file it is appended to as one JSON line. All three get the same
+//! payload, `{"event": "fired" | "resolved", "alert": {...}}`.
+//!
+//! Every action runs on its own thread so a slow webhook can't hold up the
+//! stats emitter. An attempt that fails or runs past `timeout_secs` is
+//! retried up to `retries` times, waiting a little longer each time, and the
+//! final outcome is handed back to be filed with the alert. Acknowledging
+//! the alert stops whatever hasn't run yet.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::models::{ActionKind, ActionOutcome, ActionTrigger, AlertAction, AlertEvent};
+
+/// Wait before the first retry, doubled for every one after it
+const RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// How often a running command is checked for having exited
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Most retries a single action may ask for
+pub const MAX_ACTION_RETRIES: u32 = 10;
+
+/// Check that an action can run at all
+pub fn validate_action(action: &AlertAction) -> Result<(), String> {
+    match &action.kind {
+        ActionKind::Command { program, .. } if program.trim().is_empty() => {
+            return Err("command actions need a program".to_string())
+        }
+        ActionKind::Webhook { url }
+            if !(url.starts_with("http://") || url.starts_with("https://")) =>
+        {
+            return Err(format!("webhook URL {:?} must start with http(s)://", url))
+        }
+        ActionKind::File { path } if path.trim().is_empty() => {
+            return Err("file actions need a path".to_string())
+        }
+        _ => {}
+    }
+    if action.timeout_secs == 0 {
+        return Err("action timeout must be at least 1 second".to_string());
+    }
+    if action.retries > MAX_ACTION_RETRIES {
+        return Err(format!(
+            "actions can be retried at most {} times",
+            MAX_ACTION_RETRIES
+        ));
+    }
+    Ok(())
+}
+
+/// Short, secret-free description of an action for its outcome
+/// Webhooks only show their host, as tokens often live in the path
+pub fn describe(kind: &ActionKind) -> String {
+    match kind {
+        ActionKind::Command { program, .. } => format!("command {}", program),
+        ActionKind::Webhook { url } => {
+            let host = url
+                .split_once("://")
+                .map_or(url.as_str(), |(_, rest)| rest)
+                .split(['/', '?', '#'])
+                .next()
+                .unwrap_or_default();
+            // Drop credentials in "user:password@host"
+            let host = host.rsplit('@').next().unwrap_or(host);
+            format!("webhook {}", host)
+        }
+        ActionKind::File { path } => format!("file {}", path),
+    }
+}
+
+/// The JSON every action gets
+pub fn payload(trigger: ActionTrigger, alert: &AlertEvent) -> String {
+    serde_json::json!({ "event": trigger, "alert": alert }).to_string()
+}
+
+/// Run `actions` in the background, handing each outcome to `on_outcome`
+/// Attempts stop as soon as `silenced` returns true (the alert was acknowledged)
+pub fn spawn_actions(
+    actions: &[AlertAction],
+    trigger: ActionTrigger,
+    alert: &AlertEvent,
+    silenced: impl Fn() -> bool + Send + Sync + 'static,
+    on_outcome: impl Fn(ActionOutcome) + Send + Sync + 'static,
+) {
+    let silenced = Arc::new(silenced);
+    let on_outcome = Arc::new(on_outcome);
+    let payload = Arc::new(payload(trigger, alert));
+    for action in actions {
+        let action = action.clone();
+        let payload = payload.clone();
+        let silenced = silenced.clone();
+        let on_outcome = on_outcome.clone();
+        thread::spawn(move || {
+            on_outcome(run_action(
+                &action,
+                trigger,
+                &payload,
+                RETRY_BACKOFF,
+                &*silenced,
+            ))
+        });
+    }
+}
+
+/// Run an action to completion, retrying failed attempts
+fn run_action(
+    action: &AlertAction,
+    trigger: ActionTrigger,
+    payload: &str,
+    backoff: Duration,
+    silenced: &dyn Fn() -> bool,
+) -> ActionOutcome {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(action.timeout_secs);
+    let mut attempts = 0;
+    let mut error = None;
+
+    while attempts <= action.retries {
+        if attempts > 0 {
+            thread::sleep(backoff * 2u32.saturating_pow(attempts - 1));
+        }
+        if silenced() {
+            error = Some("Skipped, the alert was acknowledged".to_string());
+            break;
+        }
+        attempts += 1;
+        match attempt(&action.kind, payload, timeout) {
+            Ok(()) => {
+                error = None;
+                break;
+            }
+            Err(e) => error = Some(e),
+        }
+    }
+
+    ActionOutcome {
+        action: describe(&action.kind),
+        trigger,
+        success: error.is_none(),
+        attempts,
+        error,
+        duration_ms: started.elapsed().as_millis() as u64,
+        finished_at: now_millis(),
+    }
+}
+
+fn attempt(kind: &ActionKind, payload: &str, timeout: Duration) -> Result<(), String> {
+    match kind {
+        ActionKind::Command { program, args } => run_command(program, args, payload, timeout),
+        ActionKind::Webhook { url } => post_webhook(url, payload, timeout),
+        ActionKind::File { path } => append_line(Path::new(path), payload),
+    }
+}
+
+/// Run a program with the payload on stdin, killing it once `timeout` is up
+fn run_command(
+    program: &str,
+    args: &[String],
+    payload: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+
+    // A program that doesn't read its input closes the pipe early - that's fine
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(payload.as_bytes());
+    }
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => return Err(format!("{} exited with {}", program, status)),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} timed out after {:?}", program, timeout));
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(format!("Failed to wait for {}: {}", program, e)),
+        }
+    }
+}
+
+/// POST the payload, treating any non-2xx response as a failure
+fn post_webhook(url: &str, payload: &str, timeout: Duration) -> Result<(), String> {
+    let agent = ureq::AgentBuilder::new().timeout(timeout).build();
+    match agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(payload)
+    {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, response)) => {
+            Err(format!("HTTP {} {}", code, response.status_text()))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Append the payload as one line, creating the file and its folder
+fn append_line(path: &Path, payload: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", payload).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Severity;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    fn alert() -> AlertEvent {
+        AlertEvent {
+            rule_id: "cpu-hot".to_string(),
+            condition: "cpu.temperature > 90".to_string(),
+            severity: Severity::Warning,
+            metric: "cpu.temperature".to_string(),
+            threshold: 90.0,
+            value: 95.0,
+            peak: 95.0,
+            fired_at: 1000,
+            resolved_at: None,
+            acknowledged_at: None,
+            actions: Vec::new(),
+        }
+    }
+
+    fn action(kind: ActionKind, retries: u32) -> AlertAction {
+        AlertAction {
+            kind,
+            timeout_secs: 5,
+            retries,
+            on_resolve: false,
+        }
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("pulse-action-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Answer `statuses.len()` requests with the given statuses, sending back each body
+    fn serve(statuses: Vec<u16>) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/hooks/secret-token",
+            listener.local_addr().unwrap()
+        );
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                tx.send(String::from_utf8(body).unwrap()).unwrap();
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+            }
+        });
+        (url, rx)
+    }
+
+    #[test]
+    fn test_webhook_retries_until_success() {
+        let (url, bodies) = serve(vec![500, 200]);
+        let host = url.split('/').nth(2).unwrap().to_string();
+        let webhook = action(ActionKind::Webhook { url }, 2);
+        let payload = payload(ActionTrigger::Fired, &alert());
+
+        let outcome = run_action(
+            &webhook,
+            ActionTrigger::Fired,
+            &payload,
+            Duration::ZERO,
+            &|| false,
+        );
+        assert!(outcome.success, "{:?}", outcome.error);
+        assert_eq!(outcome.attempts, 2);
+        assert_eq!(outcome.action, format!("webhook {}", host));
+
+        let body: serde_json::Value = serde_json::from_str(&bodies.recv().unwrap()).unwrap();
+        assert_eq!(body["event"], "fired");
+        assert_eq!(body["alert"]["rule_id"], "cpu-hot");
+        assert_eq!(bodies.recv().unwrap(), payload);
+    }
+
+    #[test]
+    fn test_webhook_gives_up() {
+        let (url, _bodies) = serve(vec![503, 503]);
+        let webhook = action(ActionKind::Webhook { url }, 1);
+
+        let outcome = run_action(
+            &webhook,
+            ActionTrigger::Resolved,
+            "{}",
+            Duration::ZERO,
+            &|| false,
+        );
+        assert!(!outcome.success);
+        assert_eq!(outcome.attempts, 2);
+        assert!(outcome.error.unwrap().starts_with("HTTP 503"));
+    }
+
+    #[test]
+    fn test_file_appends_lines() {
+        let dir = temp_dir("file");
+        let path = dir.join("logs").join("alerts.jsonl");
+        let file = action(
+            ActionKind::File {
+                path: path.to_string_lossy().into_owned(),
+            },
+            0,
+        );
+
+        for trigger in [ActionTrigger::Fired, ActionTrigger::Resolved] {
+            let outcome = run_action(
+                &file,
+                trigger,
+                &payload(trigger, &alert()),
+                Duration::ZERO,
+                &|| false,
+            );
+            assert!(outcome.success, "{:?}", outcome.error);
+        }
+        let lines: Vec<String> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains(r#""event":"resolved""#));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_gets_payload_on_stdin() {
+        let dir = temp_dir("command");
+        fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("stdin.json");
+        let command = action(
+            ActionKind::Command {
+                program: "sh".to_string(),
+                args: vec!["-c".to_string(), format!("cat > '{}'", out.display())],
+            },
+            0,
+        );
+        let payload = payload(ActionTrigger::Fired, &alert());
+
+        let outcome = run_action(
+            &command,
+            ActionTrigger::Fired,
+            &payload,
+            Duration::ZERO,
+            &|| false,
+        );
+        assert!(outcome.success, "{:?}", outcome.error);
+        assert_eq!(fs::read_to_string(&out).unwrap(), payload);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_timeout_and_exit_status() {
+        let sleepy = AlertAction {
+            timeout_secs: 1,
+            ..action(
+                ActionKind::Command {
+                    program: "sleep".to_string(),
+                    args: vec!["10".to_string()],
+                },
+                0,
+            )
+        };
+        let started = Instant::now();
+        let outcome = run_action(&sleepy, ActionTrigger::Fired, "{}", Duration::ZERO, &|| {
+            false
+        });
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(outcome.error.unwrap().contains("timed out"));
+
+        let failing = action(
+            ActionKind::Command {
+                program: "false".to_string(),
+                args: Vec::new(),
+            },
+            2,
+        );
+        let outcome = run_action(
+            &failing,
+            ActionTrigger::Fired,
+            "{}",
+            Duration::ZERO,
+            &|| false,
+        );
+        assert_eq!(outcome.attempts, 3);
+        assert!(outcome.error.unwrap().contains("exited with"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_acknowledged_alert_stops_retries() {
+        let failing = action(
+            ActionKind::Command {
+                program: "false".to_string(),
+                args: Vec::new(),
+            },
+            3,
+        );
+        // Acknowledged after the first attempt
+        let checks = std::cell::Cell::new(0);
+        let silenced = || {
+            checks.set(checks.get() + 1);
+            checks.get() > 1
+        };
+        let outcome = run_action(
+            &failing,
+            ActionTrigger::Fired,
+            "{}",
+            Duration::ZERO,
+            &silenced,
+        );
+        assert!(!outcome.success);
+        assert_eq!(outcome.attempts, 1);
+        assert!(outcome.error.unwrap().contains("acknowledged"));
+
+        let outcome = run_action(
+            &failing,
+            ActionTrigger::Fired,
+            "{}",
+            Duration::ZERO,
+            &|| true,
+        );
+        assert_eq!(outcome.attempts, 0);
+    }
+
+    #[test]
+    fn test_validate_and_describe() {
+        let webhook = |url: &str| {
+            action(
+                ActionKind::Webhook {
+                    url: url.to_string(),
+                },
+                0,
+            )
+        };
+        assert!(validate_action(&webhook("https://hooks.example.com/T0/B1")).is_ok());
+        assert!(validate_action(&webhook("ftp://example.com")).is_err());
+        assert!(validate_action(&AlertAction {
+            retries: MAX_ACTION_RETRIES + 1,
+            ..webhook("http://localhost")
+        })
+        .is_err());
+
+        assert_eq!(
+            describe(&webhook("https://user:pw@hooks.example.com/T0/B1?x=1").kind),
+            "webhook hooks.example.com"
+        );
+
+        // Defaults when only the kind is given
+        let parsed: AlertAction =
+            serde_json::from_str(r#"{"type":"file","path":"/tmp/alerts.log"}"#).unwrap();
+        assert_eq!((parsed.timeout_secs, parsed.retries), (10, 2));
+        assert!(!parsed.on_resolve);
+    }
+}
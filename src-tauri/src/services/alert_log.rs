@@ -0,0 +1,236 @@
+//! On-disk alert history
+//!
+//! Every alert the engine fires is written to a SQLite database in the app
+//! data dir and rewritten as it changes (peak, resolution, acknowledgement,
+//! action outcomes), so events that happened while nobody was watching are
+//! still there after a restart. The event itself is stored as JSON next to
+//! the columns it is queried by.
+
+use rusqlite::{params, Connection};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::models::AlertEvent;
+
+const DAY_MS: u64 = 24 * 3_600_000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS alerts (
+    rule_id TEXT NOT NULL,
+    fired_at INTEGER NOT NULL,
+    resolved_at INTEGER,
+    event TEXT NOT NULL,
+    PRIMARY KEY (rule_id, fired_at)
+);
+CREATE INDEX IF NOT EXISTS alerts_fired_at ON alerts (fired_at);
+";
+
+/// SQLite-backed log of fired alerts
+pub struct AlertLog {
+    conn: Mutex<Connection>,
+}
+
+impl AlertLog {
+    /// Open (or create) the database at `path`
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open alert log {:?}: {}", path, e))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn open_in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(|e| e.to_string())?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to create alert log schema: {}", e))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Insert an alert, or replace it if it was written before
+    pub fn record(&self, event: &AlertEvent) -> Result<(), String> {
+        let json = serde_json::to_string(event).map_err(|e| e.to_string())?;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO alerts (rule_id, fired_at, resolved_at, event)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                event.rule_id,
+                event.fired_at as i64,
+                event.resolved_at.map(|t| t as i64),
+                json
+            ],
+        )
+        .map_err(|e| format!("Failed to store alert: {}", e))?;
+        Ok(())
+    }
+
+    /// Alerts that were active at some point within `from..=to` (Unix ms),
+    /// optionally of one rule, newest first
+    pub fn query(
+        &self,
+        from: Option<u64>,
+        to: Option<u64>,
+        rule_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<AlertEvent>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT event FROM alerts
+                 WHERE fired_at <= ?1
+                   AND (resolved_at IS NULL OR resolved_at >= ?2)
+                   AND (?3 IS NULL OR rule_id = ?3)
+                 ORDER BY fired_at DESC
+                 LIMIT ?4",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(
+                params![
+                    to.map_or(i64::MAX, |t| t as i64),
+                    from.unwrap_or(0) as i64,
+                    rule_id,
+                    limit as i64
+                ],
+                |row| row.get::<_, String>(0),
+            )
+            .map_err(|e| format!("Failed to query alerts: {}", e))?;
+
+        let mut events = Vec::new();
+        for json in rows {
+            let json = json.map_err(|e| format!("Failed to read alert: {}", e))?;
+            // Skip rows a future version wrote in a shape we can't read
+            if let Ok(event) = serde_json::from_str(&json) {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Close alerts left active by a previous run at `now`
+    /// The app wasn't watching in between, so the end time is only an upper bound.
+    pub fn close_interrupted(&self, now: u64) -> Result<usize, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let interrupted: Vec<String> = conn
+            .prepare("SELECT event FROM alerts WHERE resolved_at IS NULL")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get(0))?
+                    .collect::<Result<_, _>>()
+            })
+            .map_err(|e| format!("Failed to query alerts: {}", e))?;
+
+        let mut closed = 0;
+        for json in interrupted {
+            let Ok(mut event) = serde_json::from_str::<AlertEvent>(&json) else {
+                continue;
+            };
+            event.resolved_at = Some(now);
+            let json = serde_json::to_string(&event).map_err(|e| e.to_string())?;
+            closed += conn
+                .execute(
+                    "UPDATE alerts SET resolved_at = ?1, event = ?2
+                     WHERE rule_id = ?3 AND fired_at = ?4",
+                    params![now as i64, json, event.rule_id, event.fired_at as i64],
+                )
+                .map_err(|e| format!("Failed to close alert: {}", e))?;
+        }
+        Ok(closed)
+    }
+
+    /// Delete alerts that ended more than `days` before `now`
+    pub fn prune(&self, days: u32, now: u64) -> Result<usize, String> {
+        let cutoff = now.saturating_sub(days as u64 * DAY_MS) as i64;
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        conn.execute(
+            "DELETE FROM alerts WHERE resolved_at IS NOT NULL AND resolved_at < ?1",
+            params![cutoff],
+        )
+        .map_err(|e| format!("Failed to prune alerts: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Severity;
+
+    fn alert(rule_id: &str, fired_at: u64, resolved_at: Option<u64>) -> AlertEvent {
+        AlertEvent {
+            rule_id: rule_id.to_string(),
+            condition: "cpu.temperature > 90".to_string(),
+            severity: Severity::Warning,
+            metric: "cpu.temperature".to_string(),
+            threshold: 90.0,
+            value: 95.0,
+            peak: 95.0,
+            fired_at,
+            resolved_at,
+            acknowledged_at: None,
+            actions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_replaces_and_queries_overlap() {
+        let log = AlertLog::open_in_memory().unwrap();
+        log.record(&alert("cpu-hot", 1000, None)).unwrap();
+        log.record(&alert("gpu-hot", 5000, Some(6000))).unwrap();
+        log.record(&alert("cpu-hot", 8000, Some(9000))).unwrap();
+
+        // Resolving rewrites the same row
+        let mut resolved = alert("cpu-hot", 1000, Some(4000));
+        resolved.peak = 99.0;
+        log.record(&resolved).unwrap();
+
+        let all = log.query(None, None, None, 100).unwrap();
+        assert_eq!(
+            all.iter().map(|e| e.fired_at).collect::<Vec<_>>(),
+            vec![8000, 5000, 1000]
+        );
+        assert_eq!(all[2].peak, 99.0);
+
+        // Anything active during 3000..=5500, whenever it started
+        let overlapping = log.query(Some(3000), Some(5500), None, 100).unwrap();
+        assert_eq!(overlapping.len(), 2);
+        let cpu = log.query(None, None, Some("cpu-hot"), 1).unwrap();
+        assert_eq!(cpu.len(), 1);
+        assert_eq!(cpu[0].fired_at, 8000);
+    }
+
+    #[test]
+    fn test_close_interrupted_and_prune() {
+        let log = AlertLog::open_in_memory().unwrap();
+        log.record(&alert("cpu-hot", 1000, None)).unwrap();
+        log.record(&alert("gpu-hot", 2000, Some(3000))).unwrap();
+
+        assert_eq!(log.close_interrupted(10 * DAY_MS).unwrap(), 1);
+        let cpu = log.query(None, None, Some("cpu-hot"), 10).unwrap();
+        assert_eq!(cpu[0].resolved_at, Some(10 * DAY_MS));
+
+        assert_eq!(log.prune(7, 10 * DAY_MS).unwrap(), 1);
+        let left = log.query(None, None, None, 10).unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].rule_id, "cpu-hot");
+    }
+}
@@ -0,0 +1,544 @@
+//! Statistical anomaly detection
+//!
+//! Fixed thresholds have to be picked per machine; this flags what is
+//! unusual for *this* machine instead. Every watched metric keeps an
+//! exponentially weighted mean and variance over the last few minutes, and a
+//! value that stays several standard deviations away from it for a few
+//! samples is reported as a spike or a drop - unless it is normal for the
+//! time of day, which an hour-of-day profile kept next to the short baseline
+//! tells. Two more checks look at what the short baseline can't see: memory
+//! usage creeping up steadily for half an hour, and a temperature that is
+//! high for the load it runs at (a baseline per 10 % load bucket).
+//!
+//! The detector is only fed while `AnomalySettings::enabled` is set, and
+//! works on plain (metric, value) samples so it can be run on synthetic series.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use super::history::metric_values;
+use crate::models::{AnomalyEvent, AnomalyKind, AnomalySettings, SystemStats};
+
+const MINUTE_MS: u64 = 60_000;
+const HOUR_MS: u64 = 60 * MINUTE_MS;
+
+/// Consecutive unusual samples before a spike or drop is reported
+const PERSISTENCE: u32 = 5;
+
+/// Minimum time between two reports of the same metric and kind
+const COOLDOWN_MS: u64 = 10 * MINUTE_MS;
+
+/// Samples an hour-of-day bucket averages over (about three days at 1 Hz),
+/// and how many it needs before it's trusted
+const SEASONAL_WINDOW: f64 = 3.0 * 3600.0;
+const SEASONAL_WARMUP: u64 = 1800;
+
+/// Temperatures compared against the load they run at
+const LOAD_PAIRS: [(&str, &str); 2] = [
+    ("cpu.temperature", "cpu.usage"),
+    ("gpu.temperature", "gpu.usage"),
+];
+
+/// Samples a load bucket averages over, and needs before it's trusted
+const LOAD_WINDOW: f64 = 3600.0;
+const LOAD_WARMUP: u64 = 300;
+
+/// Load is smoothed over about this long, as temperatures lag behind it
+const LOAD_SMOOTHING_MS: f64 = 60_000.0;
+
+/// Consecutive hot samples before a temperature is reported
+const LOAD_PERSISTENCE: u32 = 30;
+
+/// Metric watched for memory creep, and how many minutes its trend is fitted over
+const CREEP_METRIC: &str = "ram.usage_percent";
+const CREEP_WINDOW_MINUTES: usize = 30;
+
+/// How well the minute averages have to fit a line to count as a creep
+const CREEP_MIN_R2: f64 = 0.8;
+
+/// Anomalies kept for `recent`
+const RECENT_CAPACITY: usize = 100;
+
+/// Exponentially weighted mean and variance
+#[derive(Debug, Clone, Copy, Default)]
+struct Ewma {
+    mean: f64,
+    variance: f64,
+    samples: u64,
+}
+
+impl Ewma {
+    /// Fold in a value with weight `alpha`
+    /// Young averages weigh every value equally so they settle quickly.
+    fn add(&mut self, value: f64, alpha: f64) {
+        if self.samples == 0 {
+            self.mean = value;
+            self.variance = 0.0;
+        } else {
+            let alpha = alpha.max(1.0 / (self.samples + 1) as f64);
+            let diff = value - self.mean;
+            self.mean += alpha * diff;
+            self.variance = (1.0 - alpha) * (self.variance + alpha * diff * diff);
+        }
+        self.samples += 1;
+    }
+
+    /// How many standard deviations `value` is from the mean
+    /// The spread has a floor so a flat metric doesn't make every wiggle unusual.
+    fn z_score(&self, value: f64) -> f64 {
+        let spread = self.variance.sqrt().max(0.5 + 0.02 * self.mean.abs());
+        (value - self.mean) / spread
+    }
+}
+
+/// Turns a run of unusual samples into a single report
+#[derive(Debug, Default)]
+struct Trigger {
+    streak: u32,
+    reported: bool, // For the current run
+    last_report: Option<u64>,
+}
+
+impl Trigger {
+    /// Whether to report now, given whether the latest sample is unusual
+    fn update(&mut self, unusual: bool, now: u64, persistence: u32) -> bool {
+        if !unusual {
+            self.streak = 0;
+            self.reported = false;
+            return false;
+        }
+        self.streak += 1;
+        let cooled_down = self
+            .last_report
+            .is_none_or(|t| now.saturating_sub(t) >= COOLDOWN_MS);
+        if self.streak >= persistence && !self.reported && cooled_down {
+            self.reported = true;
+            self.last_report = Some(now);
+            return true;
+        }
+        false
+    }
+}
+
+/// Spike / drop state of one metric
+#[derive(Debug, Default)]
+struct MetricState {
+    baseline: Ewma,
+    since: u64, // First sample of the current baseline
+    last_seen: u64,
+    seasonal: [Ewma; 24], // By UTC hour of day
+    high: Trigger,
+    low: Trigger,
+}
+
+impl MetricState {
+    fn observe(
+        &mut self,
+        metric: &str,
+        value: f64,
+        now: u64,
+        settings: &AnomalySettings,
+    ) -> Option<AnomalyEvent> {
+        let baseline_ms = settings.baseline_minutes as u64 * MINUTE_MS;
+        let elapsed = now.saturating_sub(self.last_seen);
+        // After a gap (sleep, the detector turned off) the short baseline is stale
+        if self.baseline.samples == 0 || elapsed > baseline_ms {
+            self.baseline = Ewma::default();
+            self.since = now;
+            self.high = Trigger::default();
+            self.low = Trigger::default();
+        }
+        self.last_seen = now;
+        let hour = ((now / HOUR_MS) % 24) as usize;
+
+        let mut event = None;
+        let warm_for = now.saturating_sub(self.since);
+        if warm_for >= baseline_ms {
+            let z = self.baseline.z_score(value);
+            let seasonal = &self.seasonal[hour];
+            let usual_for_hour = seasonal.samples >= SEASONAL_WARMUP
+                && seasonal.z_score(value).abs() < settings.sensitivity;
+            let unusual = z.abs() >= settings.sensitivity && !usual_for_hour;
+            let high = self.high.update(unusual && z > 0.0, now, PERSISTENCE);
+            let low = self.low.update(unusual && z < 0.0, now, PERSISTENCE);
+            if high || low {
+                // Less sure while the baseline has only just covered its period
+                let maturity = (warm_for as f64 / (2 * baseline_ms) as f64).min(1.0);
+                event = Some(AnomalyEvent {
+                    metric: metric.to_string(),
+                    kind: if high {
+                        AnomalyKind::Spike
+                    } else {
+                        AnomalyKind::Drop
+                    },
+                    value,
+                    expected: self.baseline.mean,
+                    score: z,
+                    confidence: confidence(z.abs(), settings.sensitivity) * maturity,
+                    detected_at: now,
+                });
+            }
+        }
+
+        let alpha = 1.0 - (-(elapsed as f64) / baseline_ms as f64).exp();
+        self.baseline.add(value, alpha);
+        self.seasonal[hour].add(value, 1.0 / SEASONAL_WINDOW);
+        event
+    }
+}
+
+/// Temperature baseline per load bucket
+#[derive(Debug, Default)]
+struct LoadState {
+    load: Option<(f64, u64)>, // Smoothed load and when it was updated
+    buckets: [Ewma; 11],      // 0 %, 10 %, ... 100 %
+    trigger: Trigger,
+}
+
+impl LoadState {
+    fn observe(
+        &mut self,
+        metric: &str,
+        temperature: f64,
+        load: f64,
+        now: u64,
+        sensitivity: f64,
+    ) -> Option<AnomalyEvent> {
+        let load = match self.load {
+            Some((smoothed, at)) => {
+                let alpha = 1.0 - (-(now.saturating_sub(at) as f64) / LOAD_SMOOTHING_MS).exp();
+                smoothed + alpha * (load - smoothed)
+            }
+            None => load,
+        };
+        self.load = Some((load, now));
+
+        let bucket = &mut self.buckets[(load / 10.0).round().clamp(0.0, 10.0) as usize];
+        let z = bucket.z_score(temperature);
+        let unusual = bucket.samples >= LOAD_WARMUP && z >= sensitivity;
+        let event = self
+            .trigger
+            .update(unusual, now, LOAD_PERSISTENCE)
+            .then(|| AnomalyEvent {
+                metric: metric.to_string(),
+                kind: AnomalyKind::HotForLoad,
+                value: temperature,
+                expected: bucket.mean,
+                score: z,
+                confidence: confidence(z, sensitivity),
+                detected_at: now,
+            });
+        bucket.add(temperature, 1.0 / LOAD_WINDOW);
+        event
+    }
+}
+
+/// Minute averages of memory usage and their trend
+#[derive(Debug, Default)]
+struct CreepState {
+    minute: Option<(u64, f64, u32)>, // Current minute, sum and count of its samples
+    averages: VecDeque<f64>,         // Of consecutive complete minutes, oldest first
+    reported: bool,                  // For the current rise
+}
+
+impl CreepState {
+    fn observe(&mut self, value: f64, now: u64, percent_per_hour: f64) -> Option<AnomalyEvent> {
+        let minute = now / MINUTE_MS;
+        if let Some((current, sum, count)) = &mut self.minute {
+            if *current == minute {
+                *sum += value;
+                *count += 1;
+                return None;
+            }
+        }
+
+        // A new minute closes the previous one
+        let (previous, sum, count) = self.minute.replace((minute, value, 1))?;
+        let latest = sum / count as f64;
+        self.averages.push_back(latest);
+        if self.averages.len() > CREEP_WINDOW_MINUTES {
+            self.averages.pop_front();
+        }
+        let complete = self.averages.len() == CREEP_WINDOW_MINUTES;
+        let fit = complete.then(|| {
+            let points: Vec<(f64, f64)> = (0..)
+                .map(f64::from)
+                .zip(self.averages.iter().copied())
+                .collect();
+            linear_fit(&points)
+        });
+        // A trend only holds over consecutive minutes
+        if previous + 1 != minute {
+            self.averages.clear();
+        }
+
+        let (start, slope, r2) = fit?;
+        let rise = slope * 60.0; // Per hour
+        if rise < percent_per_hour / 2.0 {
+            self.reported = false;
+        }
+        if rise < percent_per_hour || r2 < CREEP_MIN_R2 || self.reported {
+            return None;
+        }
+        self.reported = true;
+        Some(AnomalyEvent {
+            metric: CREEP_METRIC.to_string(),
+            kind: AnomalyKind::Creep,
+            value: latest,
+            expected: start,
+            score: rise,
+            confidence: r2 * (rise / (2.0 * percent_per_hour)).min(1.0),
+            detected_at: now,
+        })
+    }
+}
+
+/// Least-squares line through `(x, y)` points: (intercept, slope, r²)
+/// Also used for the per-process memory trends of the leak detector.
+pub(crate) fn linear_fit(points: &[(f64, f64)]) -> (f64, f64, f64) {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in points {
+        let (dx, dy) = (x - mean_x, y - mean_y);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx == 0.0 {
+        return (mean_y, 0.0, 0.0);
+    }
+    let slope = sxy / sxx;
+    let r2 = if syy == 0.0 {
+        0.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+    (mean_y - slope * mean_x, slope, r2)
+}
+
+/// 0.5 right at the sensitivity, approaching 1 as the deviation grows
+fn confidence(deviation: f64, sensitivity: f64) -> f64 {
+    1.0 / (1.0 + (sensitivity - deviation).exp())
+}
+
+#[derive(Default)]
+struct Inner {
+    metrics: HashMap<String, MetricState>,
+    loads: HashMap<&'static str, LoadState>,
+    creep: CreepState,
+    recent: VecDeque<AnomalyEvent>, // Oldest first
+}
+
+/// Baselines of the watched metrics and the anomalies found against them
+#[derive(Default)]
+pub struct AnomalyDetector {
+    inner: Mutex<Inner>,
+}
+
+impl AnomalyDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check a stats sample, returning the anomalies it completes
+    pub fn observe(&self, stats: &SystemStats, settings: &AnomalySettings) -> Vec<AnomalyEvent> {
+        let values: HashMap<String, f64> = metric_values(stats).into_iter().collect();
+        self.observe_values(stats.timestamp, &values, settings)
+    }
+
+    /// Check one sample of named values taken at `timestamp` (Unix ms)
+    pub fn observe_values(
+        &self,
+        timestamp: u64,
+        values: &HashMap<String, f64>,
+        settings: &AnomalySettings,
+    ) -> Vec<AnomalyEvent> {
+        let Ok(mut inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+
+        for metric in &settings.metrics {
+            if let Some(&value) = values.get(metric) {
+                let state = inner.metrics.entry(metric.clone()).or_default();
+                events.extend(state.observe(metric, value, timestamp, settings));
+            }
+        }
+        for (temperature, load) in LOAD_PAIRS {
+            if let (Some(&t), Some(&l)) = (values.get(temperature), values.get(load)) {
+                let state = inner.loads.entry(temperature).or_default();
+                events.extend(state.observe(temperature, t, l, timestamp, settings.sensitivity));
+            }
+        }
+        if let Some(&used) = values.get(CREEP_METRIC) {
+            let threshold = settings.memory_creep_percent_per_hour;
+            events.extend(inner.creep.observe(used, timestamp, threshold));
+        }
+
+        for event in &events {
+            if inner.recent.len() == RECENT_CAPACITY {
+                inner.recent.pop_front();
+            }
+            inner.recent.push_back(event.clone());
+        }
+        events
+    }
+
+    /// Anomalies found since app start, newest first
+    pub fn recent(&self) -> Vec<AnomalyEvent> {
+        match self.inner.lock() {
+            Ok(inner) => inner.recent.iter().rev().cloned().collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic noise in -0.5..0.5
+    fn noise(i: u64) -> f64 {
+        ((i as f64 * 12.9898).sin() * 43758.5453).fract() / 2.0
+    }
+
+    fn settings(metrics: &[&str]) -> AnomalySettings {
+        AnomalySettings {
+            enabled: true,
+            metrics: metrics.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Feed `value(second)` for every `step` seconds in `seconds`, collecting anomalies
+    fn feed(
+        detector: &AnomalyDetector,
+        settings: &AnomalySettings,
+        seconds: std::ops::Range<u64>,
+        step: usize,
+        value: impl Fn(u64) -> Vec<(&'static str, f64)>,
+    ) -> Vec<AnomalyEvent> {
+        seconds
+            .step_by(step)
+            .flat_map(|s| {
+                let values = value(s).into_iter().map(|(m, v)| (m.to_string(), v));
+                detector.observe_values(s * 1000, &values.collect(), settings)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sudden_idle_cpu() {
+        let detector = AnomalyDetector::new();
+        let settings = settings(&["cpu.usage"]);
+
+        // Half an hour of a busy, noisy CPU is nothing unusual
+        let busy = |s| vec![("cpu.usage", 70.0 + 10.0 * noise(s))];
+        assert!(feed(&detector, &settings, 0..1800, 1, busy).is_empty());
+
+        let idle = |s| vec![("cpu.usage", 2.0 + noise(s))];
+        let events = feed(&detector, &settings, 1800..1920, 1, idle);
+        assert_eq!(events.len(), 1);
+        let drop = &events[0];
+        assert_eq!(drop.kind, AnomalyKind::Drop);
+        assert_eq!(drop.detected_at, (1800 + PERSISTENCE as u64 - 1) * 1000);
+        assert!((drop.expected - 70.0).abs() < 2.0);
+        assert!(drop.score < -settings.sensitivity);
+        assert!(drop.confidence > 0.9 && drop.confidence <= 1.0);
+        assert_eq!(detector.recent(), events);
+    }
+
+    #[test]
+    fn test_daily_pattern_is_not_flagged_again() {
+        let detector = AnomalyDetector::new();
+        let settings = settings(&["cpu.usage"]);
+        // A job that runs from 3:00 to 4:00 every night
+        let nightly = |s: u64| {
+            let hour = (s / 3600) % 24;
+            let base = if hour == 3 { 90.0 } else { 10.0 };
+            vec![("cpu.usage", base + noise(s))]
+        };
+
+        let day = 24 * 3600;
+        let first = feed(&detector, &settings, 0..day, 2, nightly);
+        let kinds: Vec<_> = first
+            .iter()
+            .map(|e| (e.detected_at / HOUR_MS, e.kind))
+            .collect();
+        assert_eq!(kinds, vec![(3, AnomalyKind::Spike), (4, AnomalyKind::Drop)]);
+
+        // The second night matches the hour-of-day profile
+        assert!(feed(&detector, &settings, day..2 * day, 2, nightly).is_empty());
+    }
+
+    #[test]
+    fn test_memory_creep() {
+        let settings = settings(&[]);
+
+        let flat = AnomalyDetector::new();
+        let steady = |s| vec![("ram.usage_percent", 40.0 + noise(s))];
+        assert!(feed(&flat, &settings, 0..2700, 1, steady).is_empty());
+
+        // 12 % an hour, well past the 5 % default
+        let leaking = AnomalyDetector::new();
+        let rising = |s: u64| {
+            let percent = 40.0 + 12.0 * s as f64 / 3600.0;
+            vec![("ram.usage_percent", percent + noise(s))]
+        };
+        let events = feed(&leaking, &settings, 0..2700, 1, rising);
+        assert_eq!(events.len(), 1);
+        let creep = &events[0];
+        assert_eq!(creep.kind, AnomalyKind::Creep);
+        assert!((creep.score - 12.0).abs() < 0.5, "{}", creep.score);
+        assert!((creep.expected - 40.0).abs() < 0.5);
+        assert!(creep.confidence > 0.9);
+    }
+
+    #[test]
+    fn test_gpu_hot_for_load() {
+        let detector = AnomalyDetector::new();
+        let settings = settings(&[]);
+        // Load alternates between 20 % and 80 % every 5 minutes, and the
+        // temperature follows it with the same lag the detector assumes
+        let load_at = |s: u64| {
+            if (s / 300).is_multiple_of(2) {
+                20.0
+            } else {
+                80.0
+            }
+        };
+        let smoothed = std::cell::Cell::new(20.0);
+        let sample = |s: u64, extra: f64| {
+            let load: f64 = load_at(s);
+            smoothed.set(
+                smoothed.get()
+                    + (1.0 - (-1000.0 / LOAD_SMOOTHING_MS).exp()) * (load - smoothed.get()),
+            );
+            let temperature = 30.0 + 0.55 * smoothed.get() + noise(s) + extra;
+            vec![("gpu.usage", load), ("gpu.temperature", temperature)]
+        };
+
+        let normal = feed(&detector, &settings, 0..7440, 1, |s| sample(s, 0.0));
+        assert!(normal.is_empty(), "{:?}", normal);
+
+        // A clogged cooler: 15 °C hotter once settled at 20 % load again
+        let events = feed(&detector, &settings, 7440..7500, 1, |s| sample(s, 15.0));
+        assert_eq!(events.len(), 1);
+        let hot = &events[0];
+        assert_eq!(hot.metric, "gpu.temperature");
+        assert_eq!(hot.kind, AnomalyKind::HotForLoad);
+        assert!((hot.expected - 41.0).abs() < 1.5, "{}", hot.expected);
+        assert!(hot.confidence > 0.9);
+    }
+
+    #[test]
+    fn test_linear_fit() {
+        let (intercept, slope, r2) = linear_fit(&[(0.0, 1.0), (1.0, 3.0), (3.0, 7.0), (4.0, 9.0)]);
+        assert!((intercept - 1.0).abs() < 1e-9);
+        assert!((slope - 2.0).abs() < 1e-9);
+        assert!((r2 - 1.0).abs() < 1e-9);
+        assert_eq!(linear_fit(&[(0.0, 4.0), (1.0, 4.0), (2.0, 4.0)]).2, 0.0);
+    }
+}
@@ -0,0 +1,442 @@
+//! Client for a long-lived sensor collector
+//!
+//! Spawning the sidecar as a child needs the whole app elevated. Instead,
+//! Pulse can connect to a collector that already runs as a privileged
+//! background service and speaks the same JSON-lines protocol over a Unix
+//! socket or a Windows named pipe. The client reconnects with backoff and
+//! checks who is on the other end before trusting any data.
+
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::process::read_lines;
+
+/// First reconnect delay, doubled after every failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Upper bound for the reconnect delay
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Longest single sleep while waiting, so stop requests are noticed quickly
+const STOP_POLL: Duration = Duration::from_millis(100);
+
+#[cfg(unix)]
+type Stream = std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type Stream = std::fs::File;
+
+/// Connection state changes reported by the client
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollectorEvent {
+    /// Connected and the peer passed authentication
+    Connected,
+    /// Not connected (yet) - the client keeps retrying
+    Disconnected(String),
+    /// The peer failed authentication - its data was not read
+    Rejected(String),
+}
+
+/// Background connection to a collector endpoint
+/// Dropping the client disconnects it
+pub struct CollectorClient {
+    endpoint: String,
+    stop: Arc<AtomicBool>,
+    /// Clone of the live connection, so `stop` can interrupt a blocked read
+    connection: Arc<Mutex<Option<Stream>>>,
+}
+
+impl CollectorClient {
+    /// Connect to `endpoint` on a background thread and keep reconnecting
+    ///
+    /// `on_line` is called for every non-empty line, `on_event` whenever
+    /// the connection state changes (repeated identical failures are only
+    /// reported once).
+    pub fn start<L, E>(endpoint: &str, mut on_line: L, mut on_event: E) -> Self
+    where
+        L: FnMut(&str) + Send + 'static,
+        E: FnMut(CollectorEvent) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let connection = Arc::new(Mutex::new(None));
+
+        let thread_endpoint = endpoint.to_string();
+        let thread_stop = Arc::clone(&stop);
+        let thread_connection = Arc::clone(&connection);
+
+        thread::spawn(move || {
+            let mut backoff = INITIAL_BACKOFF;
+            let mut last_event = None;
+
+            while !thread_stop.load(Ordering::SeqCst) {
+                let event = match connect(&thread_endpoint) {
+                    Ok(stream) => match authenticate(&stream) {
+                        Ok(()) => {
+                            backoff = INITIAL_BACKOFF;
+                            last_event = Some(CollectorEvent::Connected);
+                            on_event(CollectorEvent::Connected);
+
+                            if let Ok(mut guard) = thread_connection.lock() {
+                                *guard = stream.try_clone().ok();
+                            }
+                            let reader = UntilStopped {
+                                stream,
+                                stop: &thread_stop,
+                            };
+                            read_lines(reader, |line| {
+                                if !thread_stop.load(Ordering::SeqCst) {
+                                    on_line(line);
+                                }
+                            });
+                            if let Ok(mut guard) = thread_connection.lock() {
+                                *guard = None;
+                            }
+
+                            CollectorEvent::Disconnected(
+                                "Collector closed the connection".to_string(),
+                            )
+                        }
+                        Err(e) => CollectorEvent::Rejected(e),
+                    },
+                    Err(e) => CollectorEvent::Disconnected(format!(
+                        "Failed to connect to collector at {}: {}",
+                        thread_endpoint, e
+                    )),
+                };
+
+                if thread_stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                if last_event.as_ref() != Some(&event) {
+                    last_event = Some(event.clone());
+                    on_event(event);
+                }
+
+                sleep_unless_stopped(&thread_stop, backoff);
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+
+        Self {
+            endpoint: endpoint.to_string(),
+            stop,
+            connection,
+        }
+    }
+
+    /// Endpoint this client connects to
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Disconnect and stop reconnecting
+    /// The blocked read is interrupted and the background thread exits
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Ok(mut guard) = self.connection.lock() {
+            // The clone is closed right after, the reader closes its own handle
+            if let Some(stream) = guard.take() {
+                interrupt(&stream);
+            }
+        }
+    }
+}
+
+impl Drop for CollectorClient {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Reads from the connection until `stop` is set, then reports EOF
+/// so the line reader returns instead of waiting for more data
+struct UntilStopped<'a> {
+    stream: Stream,
+    stop: &'a AtomicBool,
+}
+
+impl Read for UntilStopped<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.stop.load(Ordering::SeqCst) {
+            return Ok(0);
+        }
+        self.stream.read(buf)
+    }
+}
+
+fn sleep_unless_stopped(stop: &AtomicBool, duration: Duration) {
+    let mut remaining = duration;
+    while !remaining.is_zero() && !stop.load(Ordering::SeqCst) {
+        let step = remaining.min(STOP_POLL);
+        thread::sleep(step);
+        remaining -= step;
+    }
+}
+
+#[cfg(unix)]
+fn connect(endpoint: &str) -> io::Result<Stream> {
+    Stream::connect(endpoint)
+}
+
+#[cfg(windows)]
+fn connect(endpoint: &str) -> io::Result<Stream> {
+    std::fs::OpenOptions::new().read(true).open(endpoint)
+}
+
+#[cfg(unix)]
+fn interrupt(stream: &Stream) {
+    let _ = stream.shutdown(std::net::Shutdown::Both);
+}
+
+/// Cancel the blocked pipe read, the reader then drops its handle
+/// The clone shares the pipe with the reader's handle, so the cancel
+/// reaches the read and the sidecar sees its client disconnect
+#[cfg(windows)]
+fn interrupt(stream: &Stream) {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::System::IO::CancelIoEx;
+
+    // SAFETY: the handle is open for the duration of the call
+    unsafe {
+        CancelIoEx(stream.as_raw_handle() as HANDLE, std::ptr::null());
+    }
+}
+
+/// Accept peers running as root or as our own user
+/// Anyone else could feed us made-up sensor values
+#[cfg(unix)]
+fn authenticate(stream: &Stream) -> Result<(), String> {
+    use std::os::unix::io::AsRawFd;
+
+    let uid = peer_uid(stream.as_raw_fd())
+        .map_err(|e| format!("Failed to query collector peer: {}", e))?;
+    // SAFETY: geteuid has no preconditions and can't fail
+    let own_uid = unsafe { libc::geteuid() };
+
+    if uid == 0 || uid == own_uid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Collector runs as uid {}, expected root or uid {}",
+            uid, own_uid
+        ))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_uid(fd: std::os::unix::io::RawFd) -> io::Result<u32> {
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+
+    // SAFETY: cred and len point to valid memory of the advertised size
+    let result = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if result == 0 {
+        Ok(cred.uid)
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn peer_uid(fd: std::os::unix::io::RawFd) -> io::Result<u32> {
+    let mut uid = 0;
+    let mut gid = 0;
+
+    // SAFETY: uid and gid are valid out pointers
+    if unsafe { libc::getpeereid(fd, &mut uid, &mut gid) } == 0 {
+        Ok(uid)
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Accept only pipe servers running elevated
+/// A non-elevated process could have created the pipe name first
+#[cfg(windows)]
+fn authenticate(stream: &Stream) -> Result<(), String> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::Pipes::GetNamedPipeServerProcessId;
+    use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    let pipe = stream.as_raw_handle() as HANDLE;
+    let mut server_pid = 0u32;
+
+    // SAFETY: the process handle is checked before use and closed afterwards,
+    // the out pointer refers to a live local of the right size
+    let elevated = unsafe {
+        if GetNamedPipeServerProcessId(pipe, &mut server_pid) == 0 {
+            return Err(format!(
+                "Failed to query collector process: {}",
+                io::Error::last_os_error()
+            ));
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, server_pid);
+        if process.is_null() {
+            return Err(format!(
+                "Failed to open collector process {}: {}",
+                server_pid,
+                io::Error::last_os_error()
+            ));
+        }
+
+        let elevated = super::elevated::process_is_elevated(process);
+        CloseHandle(process);
+        elevated
+    };
+
+    match elevated {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!(
+            "Collector process {} is not elevated, refusing its data",
+            server_pid
+        )),
+        Err(e) => Err(format!(
+            "Failed to query collector process {}: {}",
+            server_pid, e
+        )),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixListener;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+
+    fn socket_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "pulse-collector-{}-{}.sock",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_reads_lines_from_collector() {
+        let path = socket_path("lines");
+        let listener = UnixListener::bind(&path).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+
+        let _client = CollectorClient::start(
+            path.to_str().unwrap(),
+            move |line| tx.send(line.to_string()).unwrap(),
+            move |event| {
+                let _ = event_tx.send(event);
+            },
+        );
+
+        let (mut conn, _) = listener.accept().unwrap();
+        writeln!(conn, "{{\"gpu\":[],\"timestamp\":1}}").unwrap();
+        writeln!(conn, "{{\"gpu\":[],\"timestamp\":2}}").unwrap();
+
+        assert_eq!(
+            event_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            CollectorEvent::Connected
+        );
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            r#"{"gpu":[],"timestamp":1}"#
+        );
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            r#"{"gpu":[],"timestamp":2}"#
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_reconnects_after_collector_restart() {
+        let path = socket_path("reconnect");
+        let (tx, rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+
+        // Collector isn't up yet - the client keeps retrying
+        let _client = CollectorClient::start(
+            path.to_str().unwrap(),
+            move |line| tx.send(line.to_string()).unwrap(),
+            move |event| {
+                let _ = event_tx.send(event);
+            },
+        );
+        assert!(matches!(
+            event_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            CollectorEvent::Disconnected(_)
+        ));
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let (mut conn, _) = listener.accept().unwrap();
+        writeln!(conn, "first").unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "first");
+        drop(conn);
+
+        let (mut conn, _) = listener.accept().unwrap();
+        writeln!(conn, "second").unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "second");
+
+        let events: Vec<CollectorEvent> = event_rx.try_iter().collect();
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| **e == CollectorEvent::Connected)
+                .count(),
+            2
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_stop_interrupts_blocked_read() {
+        let path = socket_path("stop");
+        let listener = UnixListener::bind(&path).unwrap();
+        let (event_tx, event_rx) = mpsc::channel();
+
+        let mut client = CollectorClient::start(
+            path.to_str().unwrap(),
+            |_| {},
+            move |event| {
+                let _ = event_tx.send(event);
+            },
+        );
+        let (mut conn, _) = listener.accept().unwrap();
+        assert_eq!(
+            event_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            CollectorEvent::Connected
+        );
+
+        client.stop();
+        // The collector sees its client go away
+        conn.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(conn.read(&mut [0u8; 16]).unwrap(), 0);
+        // No reconnect attempt after a stop
+        listener.set_nonblocking(true).unwrap();
+        thread::sleep(INITIAL_BACKOFF * 2);
+        assert!(listener.accept().is_err());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_accepts_own_user() {
+        let (a, _b) = Stream::pair().unwrap();
+        assert!(authenticate(&a).is_ok());
+    }
+}
@@ -0,0 +1,625 @@
+//! Compare two recorded sessions
+//!
+//! Answers "did it get better?" for before/after runs such as a CPU repaste
+//! or a new fan curve: per-metric min/avg/p95/max deltas, both sessions
+//! overlaid on a shared time axis, and a significance test. Samples one
+//! second apart are strongly autocorrelated, so the test runs on 10 s block
+//! means rather than on the raw samples.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use super::history::{metric_unit, metric_values};
+use crate::models::{
+    MetricComparison, MetricDelta, MetricSummary, OverlayPoint, ReportFormat, SessionComparison,
+    SessionInfo, Significance, SystemStats,
+};
+
+/// Block size for the significance test
+const BLOCK_MS: u64 = 10_000;
+/// Most overlay points per metric - longer sessions get coarser buckets
+const MAX_OVERLAY_POINTS: u64 = 600;
+/// Significance level of the test
+const ALPHA: f64 = 0.05;
+/// Smallest Cohen's d that counts as a real difference
+const MIN_EFFECT: f64 = 0.2;
+
+/// Metric values of a session, as (offset from start, value) per metric
+type SessionSeries = BTreeMap<String, Vec<(u64, f64)>>;
+
+fn session_series(samples: &[SystemStats]) -> SessionSeries {
+    let first = samples.first().map(|s| s.timestamp).unwrap_or(0);
+    let mut series = SessionSeries::new();
+    for stats in samples {
+        let offset = stats.timestamp.saturating_sub(first);
+        for (metric, value) in metric_values(stats) {
+            series.entry(metric).or_default().push((offset, value));
+        }
+    }
+    series
+}
+
+fn session_info(path: &str, samples: &[SystemStats]) -> SessionInfo {
+    let first = samples.first().map(|s| s.timestamp).unwrap_or(0);
+    let last = samples.last().map(|s| s.timestamp).unwrap_or(0);
+    SessionInfo {
+        path: path.to_string(),
+        started_at: first,
+        duration_ms: last.saturating_sub(first),
+        samples: samples.len(),
+    }
+}
+
+/// Compare session A ("before") with session B ("after")
+///
+/// An empty `metrics` list compares every whole-device metric both
+/// sessions reported; per-core series are only included when asked for.
+pub fn compare_sessions(
+    path_a: &str,
+    a: &[SystemStats],
+    path_b: &str,
+    b: &[SystemStats],
+    metrics: &[String],
+) -> SessionComparison {
+    let info_a = session_info(path_a, a);
+    let info_b = session_info(path_b, b);
+    let series_a = session_series(a);
+    let series_b = session_series(b);
+
+    let names: Vec<String> = if metrics.is_empty() {
+        let keys_a: BTreeSet<&String> = series_a.keys().collect();
+        series_b
+            .keys()
+            .filter(|m| keys_a.contains(m) && m.split('.').count() == 2)
+            .cloned()
+            .collect()
+    } else {
+        metrics.to_vec()
+    };
+
+    let longest = info_a.duration_ms.max(info_b.duration_ms);
+    let overlay_step_ms = overlay_step(longest);
+
+    let metrics: Vec<MetricComparison> = names
+        .into_iter()
+        .map(|metric| {
+            let values_a = series_a.get(&metric).map(Vec::as_slice).unwrap_or(&[]);
+            let values_b = series_b.get(&metric).map(Vec::as_slice).unwrap_or(&[]);
+            compare_metric(metric, values_a, values_b, overlay_step_ms)
+        })
+        .collect();
+
+    let summary = summarize(&metrics);
+    SessionComparison {
+        a: info_a,
+        b: info_b,
+        overlay_step_ms,
+        metrics,
+        summary,
+    }
+}
+
+fn compare_metric(
+    metric: String,
+    a: &[(u64, f64)],
+    b: &[(u64, f64)],
+    overlay_step_ms: u64,
+) -> MetricComparison {
+    let summary_a = summarize_values(a);
+    let summary_b = summarize_values(b);
+    let delta = match (summary_a, summary_b) {
+        (Some(a), Some(b)) => Some(MetricDelta {
+            min: b.min - a.min,
+            avg: b.avg - a.avg,
+            p95: b.p95 - a.p95,
+            max: b.max - a.max,
+        }),
+        _ => None,
+    };
+
+    let overlay_a = bucket_means(a, overlay_step_ms);
+    let overlay_b = bucket_means(b, overlay_step_ms);
+    let offsets: BTreeSet<u64> = overlay_a.keys().chain(overlay_b.keys()).copied().collect();
+    let overlay = offsets
+        .into_iter()
+        .map(|offset_ms| OverlayPoint {
+            offset_ms,
+            a: overlay_a.get(&offset_ms).copied(),
+            b: overlay_b.get(&offset_ms).copied(),
+        })
+        .collect();
+
+    let significance = welch_test(
+        &bucket_means(a, BLOCK_MS).into_values().collect::<Vec<_>>(),
+        &bucket_means(b, BLOCK_MS).into_values().collect::<Vec<_>>(),
+    );
+
+    MetricComparison {
+        unit: metric_unit(&metric).to_string(),
+        metric,
+        a: summary_a,
+        b: summary_b,
+        delta,
+        significance,
+        overlay,
+    }
+}
+
+/// Bucket size giving at most `MAX_OVERLAY_POINTS`, in whole seconds
+fn overlay_step(duration_ms: u64) -> u64 {
+    let step = duration_ms.div_ceil(MAX_OVERLAY_POINTS).max(1000);
+    step.div_ceil(1000) * 1000
+}
+
+fn summarize_values(values: &[(u64, f64)]) -> Option<MetricSummary> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted: Vec<f64> = values.iter().map(|(_, v)| *v).collect();
+    sorted.sort_by(f64::total_cmp);
+
+    // Nearest-rank percentile
+    let rank = ((0.95 * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    Some(MetricSummary {
+        min: sorted[0],
+        avg: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        p95: sorted[rank - 1],
+        max: sorted[sorted.len() - 1],
+        samples: sorted.len(),
+    })
+}
+
+/// Average values into buckets of `step` ms, keyed by bucket start
+fn bucket_means(values: &[(u64, f64)], step: u64) -> BTreeMap<u64, f64> {
+    let mut buckets: BTreeMap<u64, (f64, u32)> = BTreeMap::new();
+    for (offset, value) in values {
+        let bucket = buckets.entry(offset - offset % step).or_default();
+        bucket.0 += value;
+        bucket.1 += 1;
+    }
+    buckets
+        .into_iter()
+        .map(|(offset, (sum, count))| (offset, sum / count as f64))
+        .collect()
+}
+
+fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance)
+}
+
+/// Welch's unequal-variance t-test, None with fewer than two values per
+/// side or when neither side varies at all
+fn welch_test(a: &[f64], b: &[f64]) -> Option<Significance> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let (mean_a, var_a) = mean_and_variance(a);
+    let (mean_b, var_b) = mean_and_variance(b);
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+
+    let se2_a = var_a / n_a;
+    let se2_b = var_b / n_b;
+    let se = (se2_a + se2_b).sqrt();
+    if se == 0.0 {
+        return None;
+    }
+
+    let t = (mean_b - mean_a) / se;
+    let df = (se2_a + se2_b).powi(2) / (se2_a.powi(2) / (n_a - 1.0) + se2_b.powi(2) / (n_b - 1.0));
+    let p_value = student_t_two_sided(t, df);
+
+    let pooled_sd = (((n_a - 1.0) * var_a + (n_b - 1.0) * var_b) / (n_a + n_b - 2.0)).sqrt();
+    let effect_size = (mean_b - mean_a) / pooled_sd;
+
+    Some(Significance {
+        t,
+        p_value,
+        effect_size,
+        significant: p_value < ALPHA && effect_size.abs() >= MIN_EFFECT,
+    })
+}
+
+/// Two-sided p-value of Student's t distribution
+fn student_t_two_sided(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges fast on this side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Continued fraction of the incomplete beta function (modified Lentz)
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    const EPSILON: f64 = 1e-14;
+
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut result = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            result *= d * c;
+        }
+        if (d * c - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    result
+}
+
+/// ln Γ(x) for x > 0 (Lanczos approximation)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000_000_000_190_015, |sum, (i, c)| {
+            sum + c / (x + 1.0 + i as f64)
+        });
+    -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+fn effect_label(effect_size: f64) -> &'static str {
+    match effect_size.abs() {
+        d if d < 0.2 => "negligible",
+        d if d < 0.5 => "small",
+        d if d < 0.8 => "medium",
+        _ => "large",
+    }
+}
+
+/// Findings worth reading first: significant changes, largest effect first
+fn summarize(metrics: &[MetricComparison]) -> Vec<String> {
+    let mut changed: Vec<(&MetricComparison, MetricDelta, Significance)> = metrics
+        .iter()
+        .filter_map(|m| Some((m, m.delta?, m.significance?)))
+        .filter(|(_, _, s)| s.significant)
+        .collect();
+    changed.sort_by(|x, y| y.2.effect_size.abs().total_cmp(&x.2.effect_size.abs()));
+
+    let tested = metrics.iter().filter(|m| m.significance.is_some()).count();
+    let mut summary = vec![format!(
+        "{} of {} compared metrics changed significantly",
+        changed.len(),
+        tested
+    )];
+    for (metric, delta, significance) in changed {
+        summary.push(format!(
+            "{}: {} {} on average ({:+} at p95), p {}, {} effect",
+            metric.metric,
+            if delta.avg < 0.0 { "down" } else { "up" },
+            format_value(delta.avg.abs(), &metric.unit),
+            format_value(delta.p95, &metric.unit),
+            format_p(significance.p_value),
+            effect_label(significance.effect_size)
+        ));
+    }
+    summary
+}
+
+fn format_value(value: f64, unit: &str) -> String {
+    match unit {
+        "" => format!("{:.2}", value),
+        "B" => format!("{:.1} MiB", value / (1024.0 * 1024.0)),
+        unit => format!("{:.1} {}", value, unit),
+    }
+}
+
+fn format_p(p: f64) -> String {
+    if p < 0.001 {
+        "< 0.001".to_string()
+    } else {
+        format!("= {:.3}", p)
+    }
+}
+
+fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{}h {:02}m {:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+fn format_time(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp as i64)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Rows of the report's metrics table
+fn metric_rows(comparison: &SessionComparison) -> Vec<Vec<String>> {
+    let optional = |value: Option<f64>, unit: &str| {
+        value
+            .map(|v| format_value(v, unit))
+            .unwrap_or_else(|| "–".to_string())
+    };
+    comparison
+        .metrics
+        .iter()
+        .map(|m| {
+            let unit = m.unit.as_str();
+            vec![
+                m.metric.clone(),
+                optional(m.a.map(|s| s.avg), unit),
+                optional(m.b.map(|s| s.avg), unit),
+                optional(m.delta.map(|d| d.min), unit),
+                optional(m.delta.map(|d| d.avg), unit),
+                optional(m.delta.map(|d| d.p95), unit),
+                optional(m.delta.map(|d| d.max), unit),
+                m.significance
+                    .map(|s| {
+                        format!(
+                            "p {}, {}{}",
+                            format_p(s.p_value),
+                            effect_label(s.effect_size),
+                            if s.significant { " ✔" } else { "" }
+                        )
+                    })
+                    .unwrap_or_else(|| "–".to_string()),
+            ]
+        })
+        .collect()
+}
+
+const METRIC_HEADERS: [&str; 8] = [
+    "Metric",
+    "A avg",
+    "B avg",
+    "Δ min",
+    "Δ avg",
+    "Δ p95",
+    "Δ max",
+    "Significance",
+];
+
+/// Render a comparison as a Markdown or HTML report
+pub fn render_report(comparison: &SessionComparison, format: ReportFormat) -> String {
+    let sessions = [
+        ("File", comparison.a.path.clone(), comparison.b.path.clone()),
+        (
+            "Started",
+            format_time(comparison.a.started_at),
+            format_time(comparison.b.started_at),
+        ),
+        (
+            "Duration",
+            format_duration(comparison.a.duration_ms),
+            format_duration(comparison.b.duration_ms),
+        ),
+        (
+            "Samples",
+            comparison.a.samples.to_string(),
+            comparison.b.samples.to_string(),
+        ),
+    ];
+    let rows = metric_rows(comparison);
+    let mut out = String::new();
+
+    match format {
+        ReportFormat::Markdown => {
+            let _ = writeln!(out, "# Session comparison\n");
+            let _ = writeln!(out, "| | Session A (before) | Session B (after) |");
+            let _ = writeln!(out, "|---|---|---|");
+            for (label, a, b) in &sessions {
+                let _ = writeln!(out, "| {} | {} | {} |", label, a, b);
+            }
+            let _ = writeln!(out, "\n## Summary\n");
+            for line in &comparison.summary {
+                let _ = writeln!(out, "- {}", line);
+            }
+            let _ = writeln!(out, "\n## Metrics\n");
+            let _ = writeln!(out, "| {} |", METRIC_HEADERS.join(" | "));
+            let _ = writeln!(out, "|{}", "---|".repeat(METRIC_HEADERS.len()));
+            for row in rows {
+                let _ = writeln!(out, "| {} |", row.join(" | "));
+            }
+            let _ = writeln!(
+                out,
+                "\nΔ is B − A. Significance: Welch's t-test on 10 s block means, \
+                 ✔ = p < {} and at least a small effect (Cohen's d ≥ {}).",
+                ALPHA, MIN_EFFECT
+            );
+        }
+        ReportFormat::Html => {
+            let _ = writeln!(
+                out,
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+                 <title>Session comparison</title>\n<style>\n\
+                 body {{ font-family: system-ui, sans-serif; margin: 2rem; }}\n\
+                 table {{ border-collapse: collapse; margin-bottom: 1.5rem; }}\n\
+                 th, td {{ border: 1px solid #ccc; padding: 0.3rem 0.6rem; text-align: left; }}\n\
+                 </style>\n</head>\n<body>\n<h1>Session comparison</h1>"
+            );
+            let _ = writeln!(
+                out,
+                "<table>\n<tr><th></th><th>Session A (before)</th><th>Session B (after)</th></tr>"
+            );
+            for (label, a, b) in &sessions {
+                let _ = writeln!(
+                    out,
+                    "<tr><th>{}</th><td>{}</td><td>{}</td></tr>",
+                    label,
+                    escape_html(a),
+                    escape_html(b)
+                );
+            }
+            let _ = writeln!(out, "</table>\n<h2>Summary</h2>\n<ul>");
+            for line in &comparison.summary {
+                let _ = writeln!(out, "<li>{}</li>", escape_html(line));
+            }
+            let _ = writeln!(out, "</ul>\n<h2>Metrics</h2>\n<table>\n<tr>");
+            for header in METRIC_HEADERS {
+                let _ = write!(out, "<th>{}</th>", header);
+            }
+            let _ = writeln!(out, "</tr>");
+            for row in rows {
+                let cells: String = row
+                    .iter()
+                    .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+                    .collect();
+                let _ = writeln!(out, "<tr>{}</tr>", cells);
+            }
+            let _ = writeln!(
+                out,
+                "</table>\n<p>Δ is B − A. Significance: Welch's t-test on 10 s block means, \
+                 ✔ = p &lt; {} and at least a small effect (Cohen's d ≥ {}).</p>\n</body>\n</html>",
+                ALPHA, MIN_EFFECT
+            );
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CpuStats;
+
+    /// One sample per second with a CPU temperature from `temp(second)`
+    fn session(seconds: u64, temp: impl Fn(u64) -> f32) -> Vec<SystemStats> {
+        (0..seconds)
+            .map(|i| SystemStats {
+                timestamp: 1_700_000_000_000 + i * 1000,
+                cpu: CpuStats {
+                    temperature: Some(temp(i)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_student_t_p_value() {
+        // Reference values from a t table
+        assert!((student_t_two_sided(2.0, 10.0) - 0.0734).abs() < 1e-3);
+        assert!((student_t_two_sided(2.228, 10.0) - 0.05).abs() < 1e-3);
+        assert!((student_t_two_sided(0.0, 5.0) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_summary_percentile() {
+        let values: Vec<(u64, f64)> = (1..=100).map(|i| (i, i as f64)).collect();
+        let summary = summarize_values(&values).unwrap();
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.avg, 50.5);
+        assert_eq!(summary.p95, 95.0);
+        assert_eq!(summary.max, 100.0);
+    }
+
+    #[test]
+    fn test_detects_cooler_session() {
+        // Same load pattern, 6 °C cooler after the repaste
+        let wave = |i: u64| ((i % 30) as f32 / 30.0) * 10.0;
+        let before = session(600, |i| 70.0 + wave(i));
+        let after = session(600, |i| 64.0 + wave(i));
+
+        let comparison = compare_sessions("before", &before, "after", &after, &[]);
+        let cpu = comparison
+            .metrics
+            .iter()
+            .find(|m| m.metric == "cpu.temperature")
+            .unwrap();
+        assert_eq!(cpu.unit, "°C");
+        assert!((cpu.delta.unwrap().avg + 6.0).abs() < 1e-3);
+        let significance = cpu.significance.unwrap();
+        assert!(significance.significant);
+        assert!(significance.effect_size < 0.0);
+        assert_eq!(cpu.overlay.len(), 600);
+        assert_eq!(comparison.a.duration_ms, 599_000);
+        assert!(comparison.summary[1].starts_with("cpu.temperature: down 6.0 °C"));
+    }
+
+    #[test]
+    fn test_same_distribution_is_not_significant() {
+        let wave = |i: u64| ((i * 7919) % 13) as f32;
+        let a = session(300, |i| 60.0 + wave(i));
+        let b = session(300, |i| 60.0 + wave(i + 3));
+
+        let comparison = compare_sessions("a", &a, "b", &b, &["cpu.temperature".to_string()]);
+        assert!(!comparison.metrics[0].significance.unwrap().significant);
+        assert_eq!(
+            comparison.summary[0],
+            "0 of 1 compared metrics changed significantly"
+        );
+    }
+
+    #[test]
+    fn test_overlay_step_and_missing_metric() {
+        assert_eq!(overlay_step(60_000), 1000);
+        assert_eq!(overlay_step(3_600_000), 6000);
+
+        let a = session(5, |_| 50.0);
+        let comparison = compare_sessions("a", &a, "b", &[], &["cpu.temperature".to_string()]);
+        let metric = &comparison.metrics[0];
+        assert!(metric.b.is_none() && metric.delta.is_none());
+        assert!(metric.overlay.iter().all(|p| p.b.is_none()));
+    }
+
+    #[test]
+    fn test_reports() {
+        let a = session(120, |i| 60.0 + (i % 5) as f32);
+        let b = session(120, |i| 55.0 + (i % 5) as f32);
+        let comparison = compare_sessions("a<1>.pulse-session", &a, "b", &b, &[]);
+
+        let markdown = render_report(&comparison, ReportFormat::Markdown);
+        assert!(markdown.starts_with("# Session comparison"));
+        assert!(markdown.contains("| cpu.temperature | 62.0 °C | 57.0 °C |"));
+
+        let html = render_report(&comparison, ReportFormat::Html);
+        assert!(html.contains("<td>a&lt;1&gt;.pulse-session</td>"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Elevated child processes (Windows)
+//!
+//! The app itself runs as the invoking user. Only the sidecar needs admin
+//! rights for the hardware sensors, so it is launched through the UAC
+//! prompt ("runas") and serves its data on a named pipe instead of stdout,
+//! which can't be piped across the elevation boundary.
+
+use std::ffi::OsStr;
+use std::io;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+
+use windows_sys::Win32::Foundation::{CloseHandle, ERROR_CANCELLED, HANDLE, WAIT_OBJECT_0};
+use windows_sys::Win32::Security::{
+    GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+};
+use windows_sys::Win32::System::Threading::{
+    GetCurrentProcess, GetProcessId, OpenProcessToken, WaitForSingleObject,
+};
+use windows_sys::Win32::UI::Shell::{
+    ShellExecuteExW, SEE_MASK_NOASYNC, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW,
+};
+
+/// SW_HIDE - no console window for the elevated sidecar
+const SW_HIDE: i32 = 0;
+
+/// Whether this process runs with an elevated token
+pub fn is_elevated() -> bool {
+    // SAFETY: the pseudo handle of the current process needs no closing
+    process_is_elevated(unsafe { GetCurrentProcess() }).unwrap_or(false)
+}
+
+/// Whether `process` runs with an elevated token
+/// The handle needs PROCESS_QUERY_LIMITED_INFORMATION access
+pub fn process_is_elevated(process: HANDLE) -> io::Result<bool> {
+    let mut token: HANDLE = std::ptr::null_mut();
+
+    // SAFETY: the token handle is checked before use and closed afterwards,
+    // out pointers refer to live locals of the right size
+    unsafe {
+        if OpenProcessToken(process, TOKEN_QUERY, &mut token) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+        let mut len = 0u32;
+        let queried = GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut TOKEN_ELEVATION as *mut core::ffi::c_void,
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut len,
+        );
+        let error = io::Error::last_os_error();
+        CloseHandle(token);
+
+        if queried == 0 {
+            Err(error)
+        } else {
+            Ok(elevation.TokenIsElevated != 0)
+        }
+    }
+}
+
+/// Whether a launch failed because the user declined the UAC prompt
+pub fn is_cancelled(error: &io::Error) -> bool {
+    error.raw_os_error() == Some(ERROR_CANCELLED as i32)
+}
+
+/// A process started through the UAC prompt
+/// Only its exit can be observed - it has no pipes and can't be killed
+/// from here, so it has to end on its own (see the sidecar's --parent)
+pub struct ElevatedProcess {
+    handle: HANDLE,
+    pid: u32,
+}
+
+// SAFETY: the process handle isn't tied to the thread that opened it
+unsafe impl Send for ElevatedProcess {}
+
+impl ElevatedProcess {
+    /// Run `program` with `args` elevated, showing the UAC prompt if needed
+    /// Blocks until the prompt is answered
+    pub fn launch(program: &Path, args: &[String]) -> io::Result<Self> {
+        let file = wide(program.as_os_str());
+        let parameters = wide(OsStr::new(&join_args(args)));
+        let verb = wide(OsStr::new("runas"));
+
+        // SAFETY: all strings are NUL-terminated and outlive the call,
+        // the struct is zero-initialised before the used fields are set
+        unsafe {
+            let mut info: SHELLEXECUTEINFOW = std::mem::zeroed();
+            info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+            info.fMask = SEE_MASK_NOCLOSEPROCESS | SEE_MASK_NOASYNC;
+            info.lpVerb = verb.as_ptr();
+            info.lpFile = file.as_ptr();
+            info.lpParameters = parameters.as_ptr();
+            info.nShow = SW_HIDE;
+
+            if ShellExecuteExW(&mut info) == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if info.hProcess.is_null() {
+                return Err(io::Error::other("No process was started"));
+            }
+
+            Ok(Self {
+                handle: info.hProcess,
+                pid: GetProcessId(info.hProcess),
+            })
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// Check whether the process has exited
+    pub fn has_exited(&self) -> bool {
+        // SAFETY: the handle stays open until drop
+        unsafe { WaitForSingleObject(self.handle, 0) == WAIT_OBJECT_0 }
+    }
+}
+
+impl Drop for ElevatedProcess {
+    fn drop(&mut self) {
+        // SAFETY: the handle is owned and closed exactly once
+        unsafe {
+            CloseHandle(self.handle);
+        }
+    }
+}
+
+fn wide(s: &OsStr) -> Vec<u16> {
+    s.encode_wide().chain(std::iter::once(0)).collect()
+}
+
+/// Join arguments into one command line, quoting those with spaces
+fn join_args(args: &[String]) -> String {
+    args.iter()
+        .map(|a| {
+            if a.is_empty() || a.contains(' ') {
+                format!("\"{}\"", a)
+            } else {
+                a.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
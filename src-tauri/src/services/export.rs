@@ -0,0 +1,267 @@
+//! Export recorded metrics to files
+//!
+//! All formats share one wide layout: a row per timestamp and a column per
+//! metric, named `<metric> [<unit>]`. CSV follows HWiNFO's log layout (Date
+//! and Time first) so existing log viewers can open it; NDJSON and Parquet
+//! are meant for notebooks.
+
+use parquet::basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType};
+use parquet::data_type::{DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::format::MilliSeconds;
+use parquet::schema::types::Type;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::history::metric_unit;
+use crate::models::{ExportFormat, MetricSeries};
+
+/// Series pivoted into one row per timestamp
+struct Table {
+    headers: Vec<String>,
+    rows: Vec<(u64, Vec<Option<f64>>)>,
+}
+
+impl Table {
+    fn from_series(series: &[MetricSeries]) -> Self {
+        let headers = series
+            .iter()
+            .map(|s| match metric_unit(&s.metric) {
+                "" => s.metric.clone(),
+                unit => format!("{} [{}]", s.metric, unit),
+            })
+            .collect();
+
+        let mut rows: BTreeMap<u64, Vec<Option<f64>>> = BTreeMap::new();
+        for (column, s) in series.iter().enumerate() {
+            for point in &s.points {
+                rows.entry(point.timestamp)
+                    .or_insert_with(|| vec![None; series.len()])[column] = Some(point.value);
+            }
+        }
+
+        Self {
+            headers,
+            rows: rows.into_iter().collect(),
+        }
+    }
+}
+
+/// Write `series` to `path` in `format`, returning the number of rows
+pub fn export_metrics(
+    series: &[MetricSeries],
+    format: ExportFormat,
+    path: &Path,
+) -> Result<usize, String> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create export dir: {}", e))?;
+    }
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create export {:?}: {}", path, e))?;
+
+    let table = Table::from_series(series);
+    match format {
+        ExportFormat::Csv => write_csv(&table, BufWriter::new(file)),
+        ExportFormat::Ndjson => write_ndjson(&table, BufWriter::new(file)),
+        ExportFormat::Parquet => write_parquet(&table, file),
+    }
+    .map_err(|e| format!("Failed to write export {:?}: {}", path, e))?;
+
+    Ok(table.rows.len())
+}
+
+fn write_csv(table: &Table, mut out: impl Write) -> Result<(), String> {
+    let mut header = vec!["Date".to_string(), "Time".to_string()];
+    header.extend(table.headers.iter().map(|h| csv_field(h)));
+    writeln!(out, "{}", header.join(",")).map_err(|e| e.to_string())?;
+
+    for (timestamp, values) in &table.rows {
+        let time = chrono::DateTime::from_timestamp_millis(*timestamp as i64)
+            .ok_or_else(|| format!("Invalid timestamp {}", timestamp))?
+            .with_timezone(&chrono::Local);
+        let mut row = vec![
+            time.format("%-d.%-m.%Y").to_string(),
+            time.format("%H:%M:%S%.3f").to_string(),
+        ];
+        row.extend(
+            values
+                .iter()
+                .map(|v| v.map(|v| v.to_string()).unwrap_or_default()),
+        );
+        writeln!(out, "{}", row.join(",")).map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+/// Quote a CSV field if it needs it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_ndjson(table: &Table, mut out: impl Write) -> Result<(), String> {
+    for (timestamp, values) in &table.rows {
+        let mut object = serde_json::Map::new();
+        object.insert("timestamp".to_string(), (*timestamp).into());
+        for (header, value) in table.headers.iter().zip(values) {
+            if let Some(value) = value {
+                object.insert(header.clone(), (*value).into());
+            }
+        }
+        serde_json::to_writer(&mut out, &object).map_err(|e| e.to_string())?;
+        writeln!(out).map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+fn write_parquet(table: &Table, out: File) -> Result<(), String> {
+    let mut fields = vec![Arc::new(
+        Type::primitive_type_builder("timestamp", PhysicalType::INT64)
+            .with_repetition(Repetition::REQUIRED)
+            .with_logical_type(Some(LogicalType::Timestamp {
+                is_adjusted_to_u_t_c: true,
+                unit: TimeUnit::MILLIS(MilliSeconds::new()),
+            }))
+            .build()
+            .map_err(|e| e.to_string())?,
+    )];
+    for header in &table.headers {
+        fields.push(Arc::new(
+            Type::primitive_type_builder(header, PhysicalType::DOUBLE)
+                .with_repetition(Repetition::OPTIONAL)
+                .build()
+                .map_err(|e| e.to_string())?,
+        ));
+    }
+    let schema = Type::group_type_builder("pulse_metrics")
+        .with_fields(fields)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let props = Arc::new(WriterProperties::builder().build());
+    let mut writer =
+        SerializedFileWriter::new(out, Arc::new(schema), props).map_err(|e| e.to_string())?;
+    let mut row_group = writer.next_row_group().map_err(|e| e.to_string())?;
+
+    let mut index = 0;
+    while let Some(mut column) = row_group.next_column().map_err(|e| e.to_string())? {
+        if index == 0 {
+            let timestamps: Vec<i64> = table.rows.iter().map(|(t, _)| *t as i64).collect();
+            column
+                .typed::<Int64Type>()
+                .write_batch(&timestamps, None, None)
+                .map_err(|e| e.to_string())?;
+        } else {
+            // Missing values are nulls: definition level 0, no value
+            let cells = table.rows.iter().map(|(_, values)| values[index - 1]);
+            let values: Vec<f64> = cells.clone().flatten().collect();
+            let levels: Vec<i16> = cells.map(|v| v.is_some() as i16).collect();
+            column
+                .typed::<DoubleType>()
+                .write_batch(&values, Some(&levels), None)
+                .map_err(|e| e.to_string())?;
+        }
+        column.close().map_err(|e| e.to_string())?;
+        index += 1;
+    }
+
+    row_group.close().map_err(|e| e.to_string())?;
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HistoryPoint;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn series() -> Vec<MetricSeries> {
+        vec![
+            MetricSeries {
+                metric: "cpu.temperature".to_string(),
+                points: vec![
+                    HistoryPoint::sample(1000, 55.5),
+                    HistoryPoint::sample(2000, 56.0),
+                ],
+            },
+            MetricSeries {
+                metric: "gpu.power".to_string(),
+                points: vec![HistoryPoint::sample(2000, 120.0)],
+            },
+        ]
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("pulse-export-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_csv_layout() {
+        let path = temp_path("metrics.csv");
+        assert_eq!(
+            export_metrics(&series(), ExportFormat::Csv, &path).unwrap(),
+            2
+        );
+
+        let csv = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "Date,Time,cpu.temperature [°C],gpu.power [W]");
+        assert!(lines[1].ends_with(",55.5,"));
+        assert!(lines[2].ends_with(",56,120"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_ndjson_rows() {
+        let path = temp_path("metrics.ndjson");
+        export_metrics(&series(), ExportFormat::Ndjson, &path).unwrap();
+
+        let rows: Vec<serde_json::Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["timestamp"], 1000);
+        assert!(rows[0].get("gpu.power [W]").is_none());
+        assert_eq!(rows[1]["gpu.power [W]"], 120.0);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_parquet_schema_and_rows() {
+        let path = temp_path("metrics.parquet");
+        export_metrics(&series(), ExportFormat::Parquet, &path).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 2);
+        let names: Vec<&str> = metadata
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|c| c.name())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["timestamp", "cpu.temperature [°C]", "gpu.power [W]"]
+        );
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_empty_export() {
+        let path = temp_path("empty.csv");
+        assert_eq!(export_metrics(&[], ExportFormat::Csv, &path).unwrap(), 0);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Date,Time\n");
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,366 @@
+//! Bounded in-memory metric history
+//!
+//! The stats emitter records every sample here, so a window that opens late
+//! (e.g. the main window reopened from the tray) can backfill its charts
+//! instead of starting empty.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+
+use super::metric_store::MetricStore;
+use crate::models::{HistoryPoint, MetricSeries, Resolution, SystemStats};
+
+/// Points kept per metric - 1 hour at the emitter's 1 s resolution
+pub const HISTORY_CAPACITY: usize = 3600;
+
+/// Unit of a metric or stats field path, as shown in export headers
+pub fn metric_unit(metric: &str) -> &'static str {
+    let name = metric.split('.').nth(1).unwrap_or("");
+    if name.contains("temperature") || name.contains("thermal_headroom") || name == "tj_max" {
+        "°C"
+    } else if name.contains("usage") || name == "load" || name == "fan_speed" {
+        "%"
+    } else if name == "frequency" || name.ends_with("clock") {
+        "MHz"
+    } else if name == "power" || name == "core_powers" {
+        "W"
+    } else if name.ends_with("used") || name.ends_with("total") || name == "available" {
+        "B"
+    } else {
+        ""
+    }
+}
+
+/// Flatten stats into `(metric, value)` pairs
+/// Values the stats don't have (e.g. no sidecar temperature) are left out
+pub fn metric_values(stats: &SystemStats) -> Vec<(String, f64)> {
+    let mut values = Vec::new();
+    let mut push = |metric: &str, value: Option<f64>| {
+        if let Some(value) = value.filter(|v| v.is_finite()) {
+            values.push((metric.to_string(), value));
+        }
+    };
+
+    let cpu = &stats.cpu;
+    push("cpu.usage", Some(cpu.usage as f64));
+    push("cpu.frequency", Some(cpu.frequency as f64));
+    push("cpu.temperature", cpu.temperature.map(f64::from));
+    push(
+        "cpu.package_temperature",
+        cpu.package_temperature.map(f64::from),
+    );
+    push("cpu.max_temperature", cpu.max_temperature.map(f64::from));
+    push("cpu.thermal_headroom", cpu.thermal_headroom.map(f64::from));
+    push("cpu.power", cpu.power.map(f64::from));
+    for (i, usage) in cpu.per_core_usage.iter().enumerate() {
+        push(&format!("cpu.core_usage.{}", i), Some(*usage as f64));
+    }
+    for (i, temp) in cpu.core_temperatures.iter().flatten().enumerate() {
+        push(&format!("cpu.core_temperature.{}", i), Some(*temp as f64));
+    }
+
+    push("ram.used", Some(stats.ram.used as f64));
+    push("ram.usage_percent", Some(stats.ram.usage_percent as f64));
+
+    if let Some(gpu) = &stats.gpu {
+        push("gpu.usage", Some(gpu.usage as f64));
+        push("gpu.load", gpu.load.map(f64::from));
+        push("gpu.memory_used", Some(gpu.memory_used as f64));
+        push("gpu.temperature", gpu.temperature.map(f64::from));
+        push(
+            "gpu.hot_spot_temperature",
+            gpu.hot_spot_temperature.map(f64::from),
+        );
+        push("gpu.fan_speed", gpu.fan_speed.map(f64::from));
+        push("gpu.power", gpu.power.map(f64::from));
+        push("gpu.core_clock", gpu.core_clock.map(f64::from));
+        push("gpu.memory_clock", gpu.memory_clock.map(f64::from));
+    }
+
+    values
+}
+
+/// Per-metric ring buffers of recent samples
+pub struct MetricHistory {
+    series: Mutex<BTreeMap<String, VecDeque<HistoryPoint>>>,
+    capacity: usize,
+}
+
+impl MetricHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            series: Mutex::new(BTreeMap::new()),
+            capacity,
+        }
+    }
+
+    /// Record every metric of a stats sample at its timestamp
+    pub fn record(&self, stats: &SystemStats) {
+        let Ok(mut series) = self.series.lock() else {
+            return;
+        };
+
+        for (metric, value) in metric_values(stats) {
+            let points = series
+                .entry(metric)
+                .or_insert_with(|| VecDeque::with_capacity(self.capacity.min(64)));
+            if points.len() >= self.capacity {
+                points.pop_front();
+            }
+            points.push_back(HistoryPoint::sample(stats.timestamp, value));
+        }
+    }
+
+    /// Timestamp of the oldest point still held, if any
+    pub fn oldest(&self) -> Option<u64> {
+        let series = self.series.lock().ok()?;
+        series
+            .values()
+            .filter_map(|points| points.front())
+            .map(|p| p.timestamp)
+            .min()
+    }
+
+    /// Get the points of `metrics` between `from` and `to` (Unix ms, inclusive)
+    ///
+    /// An empty `metrics` list means all metrics. With `step` (ms) the points
+    /// are averaged into buckets of that size, timestamped at the bucket start.
+    /// Unknown metrics get an empty series.
+    pub fn query(
+        &self,
+        metrics: &[String],
+        from: Option<u64>,
+        to: Option<u64>,
+        step: Option<u64>,
+    ) -> Result<Vec<MetricSeries>, String> {
+        let from = from.unwrap_or(0);
+        let to = to.unwrap_or(u64::MAX);
+        if from > to {
+            return Err(format!("Invalid range: from {} is after to {}", from, to));
+        }
+        if step == Some(0) {
+            return Err("Step must be at least 1 ms".to_string());
+        }
+
+        let series = self
+            .series
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+        let names: Vec<String> = if metrics.is_empty() {
+            series.keys().cloned().collect()
+        } else {
+            metrics.to_vec()
+        };
+
+        Ok(names
+            .into_iter()
+            .map(|metric| {
+                let in_range = series
+                    .get(&metric)
+                    .into_iter()
+                    .flatten()
+                    .filter(|p| p.timestamp >= from && p.timestamp <= to);
+                let points = match step {
+                    Some(step) => downsample(in_range, step),
+                    None => in_range.copied().collect(),
+                };
+                MetricSeries { metric, points }
+            })
+            .collect())
+    }
+}
+
+/// Answer a history query from memory, or from the store when it reaches
+/// further back than memory does
+///
+/// Shared by the history commands and exports. Store queries use the
+/// coarsest rollup that still has a point per `step`.
+pub fn query_history(
+    memory: &MetricHistory,
+    store: Option<&MetricStore>,
+    metrics: &[String],
+    from: Option<u64>,
+    to: Option<u64>,
+    step: Option<u64>,
+) -> Result<Vec<MetricSeries>, String> {
+    let in_memory = match (from, memory.oldest()) {
+        (Some(from), Some(oldest)) => from >= oldest,
+        _ => store.is_none(),
+    };
+
+    match store {
+        Some(store) if !in_memory => {
+            let mut series = store.query(metrics, from, to, Resolution::for_step(step))?;
+            if let Some(step) = step {
+                for s in &mut series {
+                    s.points = downsample(s.points.iter(), step);
+                }
+            }
+            Ok(series)
+        }
+        _ => memory.query(metrics, from, to, step),
+    }
+}
+
+/// Average time-ordered points into buckets of `step` ms, keeping the
+/// lowest and highest value of each bucket
+pub(crate) fn downsample<'a>(
+    points: impl Iterator<Item = &'a HistoryPoint>,
+    step: u64,
+) -> Vec<HistoryPoint> {
+    let mut buckets: Vec<HistoryPoint> = Vec::new();
+    let mut count = 0u32;
+
+    for point in points {
+        let bucket = point.timestamp - point.timestamp % step;
+        let min = point.min.unwrap_or(point.value);
+        let max = point.max.unwrap_or(point.value);
+        match buckets.last_mut() {
+            Some(last) if last.timestamp == bucket => {
+                count += 1;
+                last.value += (point.value - last.value) / count as f64;
+                last.min = last.min.map(|m| m.min(min));
+                last.max = last.max.map(|m| m.max(max));
+            }
+            _ => {
+                buckets.push(HistoryPoint {
+                    timestamp: bucket,
+                    value: point.value,
+                    min: Some(min),
+                    max: Some(max),
+                });
+                count = 1;
+            }
+        }
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CpuStats, GpuStats};
+
+    fn stats_at(timestamp: u64, cpu_usage: f32) -> SystemStats {
+        SystemStats {
+            timestamp,
+            cpu: CpuStats {
+                usage: cpu_usage,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_metric_values_skip_missing() {
+        let mut stats = stats_at(1, 50.0);
+        stats.cpu.per_core_usage = vec![10.0, 20.0];
+        let names: Vec<String> = metric_values(&stats).into_iter().map(|(m, _)| m).collect();
+        assert!(names.contains(&"cpu.core_usage.1".to_string()));
+        assert!(!names.contains(&"cpu.temperature".to_string()));
+        assert!(!names.iter().any(|m| m.starts_with("gpu.")));
+
+        stats.gpu = Some(GpuStats {
+            temperature: Some(65.0),
+            ..Default::default()
+        });
+        let values = metric_values(&stats);
+        assert!(values.contains(&("gpu.temperature".to_string(), 65.0)));
+    }
+
+    #[test]
+    fn test_metric_unit() {
+        assert_eq!(metric_unit("cpu.core_temperature.3"), "°C");
+        assert_eq!(metric_unit("cpu.thermal_headroom"), "°C");
+        assert_eq!(metric_unit("ram.usage_percent"), "%");
+        assert_eq!(metric_unit("gpu.memory_clock"), "MHz");
+        assert_eq!(metric_unit("gpu.power"), "W");
+        assert_eq!(metric_unit("ram.used"), "B");
+        assert_eq!(metric_unit("gpu.memory_total"), "B");
+        assert_eq!(metric_unit("cpu.core_powers.1"), "W");
+        assert_eq!(metric_unit("cpu.tj_max"), "°C");
+        assert_eq!(metric_unit("cpu.cores"), "");
+        assert_eq!(metric_unit("something.else"), "");
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let history = MetricHistory::new(3);
+        for i in 0..5 {
+            history.record(&stats_at(i * 1000, i as f32));
+        }
+
+        let series = history
+            .query(&["cpu.usage".to_string()], None, None, None)
+            .unwrap();
+        let values: Vec<f64> = series[0].points.iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_query_range_and_step() {
+        let history = MetricHistory::new(HISTORY_CAPACITY);
+        for i in 0..10u64 {
+            history.record(&stats_at(i * 1000, i as f32));
+        }
+
+        let metrics = ["cpu.usage".to_string(), "gpu.power".to_string()];
+        let series = history
+            .query(&metrics, Some(2000), Some(7000), Some(3000))
+            .unwrap();
+        assert_eq!(series.len(), 2);
+        // Buckets 0 (2), 3000 (3, 4, 5) and 6000 (6, 7)
+        let buckets: Vec<(u64, f64, Option<f64>, Option<f64>)> = series[0]
+            .points
+            .iter()
+            .map(|p| (p.timestamp, p.value, p.min, p.max))
+            .collect();
+        assert_eq!(
+            buckets,
+            vec![
+                (0, 2.0, Some(2.0), Some(2.0)),
+                (3000, 4.0, Some(3.0), Some(5.0)),
+                (6000, 6.5, Some(6.0), Some(7.0)),
+            ]
+        );
+        assert!(series[1].points.is_empty());
+
+        assert!(history.query(&[], Some(5), Some(1), None).is_err());
+        assert!(history.query(&[], None, None, Some(0)).is_err());
+        let all = history.query(&[], None, None, None).unwrap();
+        assert!(all.iter().any(|s| s.metric == "cpu.usage"));
+        assert!(all.iter().all(|s| !s.points.is_empty()));
+    }
+
+    #[test]
+    fn test_query_history_falls_back_to_store() {
+        let store = MetricStore::open_in_memory().unwrap();
+        let memory = MetricHistory::new(HISTORY_CAPACITY);
+        for i in 0..4u64 {
+            let stats = stats_at(i * 60_000, i as f32);
+            store.record(&stats).unwrap();
+            // Memory only holds the last two samples
+            if i >= 2 {
+                memory.record(&stats);
+            }
+        }
+        let metric = ["cpu.usage".to_string()];
+
+        let recent = query_history(&memory, Some(&store), &metric, Some(120_000), None, None);
+        assert_eq!(recent.unwrap()[0].points.len(), 2);
+
+        let all = query_history(&memory, Some(&store), &metric, Some(0), None, None).unwrap();
+        assert_eq!(all[0].points.len(), 4);
+
+        // A one-minute step reads the minute rollups
+        let minutes =
+            query_history(&memory, Some(&store), &metric, None, None, Some(60_000)).unwrap();
+        assert_eq!(minutes[0].points.len(), 4);
+        assert_eq!(minutes[0].points[3].max, Some(3.0));
+
+        let no_store = query_history(&memory, None, &metric, Some(0), None, None).unwrap();
+        assert_eq!(no_store[0].points.len(), 2);
+    }
+}
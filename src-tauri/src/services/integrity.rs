@@ -0,0 +1,329 @@
+//! Sidecar binary integrity check
+//!
+//! The sidecar runs with elevated rights, so a binary planted next to the
+//! app would be a privilege escalation. Before every spawn the executable
+//! is hashed and compared to the SHA-256 digest embedded at build time by
+//! build.rs.
+//!
+//! The checked bytes are also the ones that run: on Unix they are copied
+//! into a directory only the app can write and the copy is executed, on
+//! Windows the file stays open without write or delete sharing until the
+//! process is gone, so it can't be swapped after the check.
+
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Digest of the sidecar this app was built with (lowercase hex)
+const EXPECTED_SHA256: Option<&str> = option_env!("PULSE_SIDECAR_SHA256");
+
+/// Why a binary failed verification
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegrityError {
+    /// The file couldn't be read
+    Unreadable(String),
+    /// The file's digest differs from the expected one
+    Mismatch { expected: String, actual: String },
+    /// No digest was embedded, so nothing can be trusted
+    NoDigest,
+    /// The verified copy couldn't be set up, retrying may help
+    Staging(String),
+}
+
+impl std::fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityError::Unreadable(e) => write!(f, "Failed to read sidecar binary: {}", e),
+            IntegrityError::Mismatch { expected, actual } => write!(
+                f,
+                "Sidecar binary was modified (SHA-256 {}, expected {})",
+                actual, expected
+            ),
+            IntegrityError::NoDigest => {
+                write!(
+                    f,
+                    "No sidecar digest embedded in this build, refusing to run it"
+                )
+            }
+            IntegrityError::Staging(e) => write!(f, "Failed to stage sidecar binary: {}", e),
+        }
+    }
+}
+
+/// SHA-256 of a byte slice as lowercase hex
+pub fn sha256_bytes(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Compare a digest against the expected one (hex, case-insensitive)
+fn compare_digest(actual: String, expected: &str) -> Result<(), IntegrityError> {
+    let expected = expected.trim().to_lowercase();
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(IntegrityError::Mismatch { expected, actual })
+    }
+}
+
+/// File name of the Linux sidecar, built from this crate as a second binary
+/// and bundled like the Windows one, so it's checked the same way
+pub const LINUX_SIDECAR: &str = "pulse-sensord";
+
+/// A sidecar binary that passed the check and can't change before it runs
+/// Keep it alive as long as the process runs from it
+#[derive(Debug)]
+pub struct VerifiedBinary {
+    /// What to execute - the private copy on Unix, the original on Windows
+    path: PathBuf,
+    /// Open without write/delete sharing, pins the original (Windows)
+    #[cfg(windows)]
+    _pin: File,
+}
+
+impl VerifiedBinary {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(unix)]
+impl Drop for VerifiedBinary {
+    fn drop(&mut self) {
+        // The copy is only needed to start the process
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Check the sidecar binary against the digest embedded at build time and
+/// return the binary to execute
+pub fn verify_sidecar(path: &Path, staging_dir: &Path) -> Result<VerifiedBinary, IntegrityError> {
+    verify_sidecar_with(path, staging_dir, EXPECTED_SHA256)
+}
+
+/// Check a binary against `expected` (hex, case-insensitive) and return
+/// the binary to execute
+///
+/// The file is read once through a single handle and the checked bytes are
+/// pinned (see the module docs), on Unix in `staging_dir`, which must be
+/// private to the current user. Debug builds without a digest (no sidecar
+/// built yet) skip the comparison; release builds refuse to run an
+/// unverifiable binary.
+pub fn verify_sidecar_with(
+    path: &Path,
+    staging_dir: &Path,
+    expected: Option<&str>,
+) -> Result<VerifiedBinary, IntegrityError> {
+    let (file, bytes) = read_pinned(path).map_err(|e| IntegrityError::Unreadable(e.to_string()))?;
+
+    match expected {
+        Some(expected) => compare_digest(sha256_bytes(&bytes), expected)?,
+        None if cfg!(debug_assertions) => {
+            println!("[Sidecar] No embedded digest, skipping integrity check (debug build)");
+        }
+        None => return Err(IntegrityError::NoDigest),
+    }
+
+    #[cfg(unix)]
+    {
+        drop(file);
+        let path =
+            stage(path, &bytes, staging_dir).map_err(|e| IntegrityError::Staging(e.to_string()))?;
+        Ok(VerifiedBinary { path })
+    }
+
+    #[cfg(windows)]
+    {
+        let _ = staging_dir;
+        Ok(VerifiedBinary {
+            path: path.to_path_buf(),
+            _pin: file,
+        })
+    }
+}
+
+/// Open `path` and read it whole through the same handle
+/// On Windows the handle denies writers, renames and deletes while open
+fn read_pinned(path: &Path) -> io::Result<(File, Vec<u8>)> {
+    let mut options = std::fs::OpenOptions::new();
+    options.read(true);
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        // FILE_SHARE_READ only
+        options.share_mode(0x00000001);
+    }
+
+    let mut file = options.open(path)?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok((file, bytes))
+}
+
+/// Write `bytes` as a fresh read-and-execute-only file in `dir`
+/// Creates `dir` owner-only and refuses one someone else could write to
+#[cfg(unix)]
+fn stage(original: &Path, bytes: &[u8], dir: &Path) -> io::Result<PathBuf> {
+    use std::io::Write;
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, OpenOptionsExt, PermissionsExt};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static COPIES: AtomicU64 = AtomicU64::new(0);
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+
+    let meta = std::fs::symlink_metadata(dir)?;
+    // SAFETY: geteuid has no preconditions
+    let uid = unsafe { libc::geteuid() };
+    if !meta.file_type().is_dir() || meta.uid() != uid {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{:?} is not a directory owned by this user", dir),
+        ));
+    }
+    if meta.mode() & 0o077 != 0 {
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+
+    let name = original
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "sidecar".to_string());
+    let path = dir.join(format!(
+        "{}-{}-{}",
+        name,
+        std::process::id(),
+        COPIES.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o500)
+        .open(&path)?;
+    let written = file.write_all(bytes).and_then(|_| file.sync_all());
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, content: &[u8]) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("pulse-integrity-{}-{}", name, std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "pulse-integrity-{}-dir-{}",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_sha256_bytes() {
+        assert_eq!(
+            sha256_bytes(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_verify_against_digest() {
+        let path = temp_file("verify", b"abc");
+        let dir = temp_dir("verify");
+        assert!(verify_sidecar_with(
+            &path,
+            &dir,
+            Some("BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD")
+        )
+        .is_ok());
+
+        std::fs::write(&path, b"abd").unwrap();
+        assert!(matches!(
+            verify_sidecar_with(
+                &path,
+                &dir,
+                Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+            ),
+            Err(IntegrityError::Mismatch { .. })
+        ));
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_dir(dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_verified_sidecar_runs_private_copy() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let script = b"#!/bin/sh\nexit 0\n";
+        let original = temp_file("staged", script);
+        let dir = temp_dir("staged");
+        let digest = sha256_bytes(script);
+
+        let verified = verify_sidecar_with(&original, &dir, Some(&digest)).unwrap();
+        assert_ne!(verified.path(), original.as_path());
+        assert!(verified.path().starts_with(&dir));
+        assert_eq!(
+            std::fs::read(verified.path()).unwrap(),
+            b"#!/bin/sh\nexit 0\n"
+        );
+        let mode = |p: &Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(verified.path()), 0o500);
+        assert_eq!(mode(&dir), 0o700);
+
+        // Changing the original afterwards doesn't touch what runs
+        std::fs::write(&original, b"tampered").unwrap();
+        assert_eq!(
+            std::fs::read(verified.path()).unwrap(),
+            b"#!/bin/sh\nexit 0\n"
+        );
+
+        let staged = verified.path().to_path_buf();
+        drop(verified);
+        assert!(!staged.exists());
+
+        // A symlink could point anywhere - refuse it as staging directory
+        std::fs::write(&original, script).unwrap();
+        let link =
+            std::env::temp_dir().join(format!("pulse-integrity-link-{}", std::process::id()));
+        let _ = std::fs::remove_file(&link);
+        std::os::unix::fs::symlink(&dir, &link).unwrap();
+        assert!(matches!(
+            verify_sidecar_with(&original, &link, Some(&digest)),
+            Err(IntegrityError::Staging(_))
+        ));
+
+        let _ = std::fs::remove_file(link);
+        let _ = std::fs::remove_file(original);
+        let _ = std::fs::remove_dir(dir);
+    }
+
+    #[test]
+    fn test_missing_file_is_unreadable() {
+        let err = verify_sidecar_with(
+            Path::new("/nonexistent/sidecar.exe"),
+            &temp_dir("missing"),
+            Some("00"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, IntegrityError::Unreadable(_)));
+    }
+}
@@ -0,0 +1,248 @@
+//! Per-process memory leak detection
+//!
+//! Samples the RSS of every process every few seconds and fits a line
+//! through each one's recent history. A process becomes a suspect once its
+//! memory has grown - almost never shrinking - faster than
+//! `min_growth_mb_per_hour` for at least `min_duration_minutes`: the steady
+//! climb of a leak rather than the sawtooth of a garbage collector or a
+//! one-off load. Each suspect's trend is projected onto the available system
+//! memory to tell how long until the machine starts swapping.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+use super::anomaly::linear_fit;
+use crate::models::{LeakSettings, LeakSuspect, ProcessInfo};
+
+/// Minimum time between two samples of the process list
+const SAMPLE_INTERVAL_MS: u64 = 10_000;
+
+/// Samples a trend needs before it's trusted
+const MIN_SAMPLES: usize = 10;
+
+/// How well the samples have to fit a line (r²)
+const MIN_FIT: f64 = 0.8;
+
+/// Share of sample-to-sample steps that may go down and still count as growing
+const MAX_SHRINKING_STEPS: f64 = 0.1;
+
+const HOUR_MS: f64 = 3_600_000.0;
+const MB: f64 = 1024.0 * 1024.0;
+
+/// RSS history of one process
+#[derive(Debug, Default)]
+struct Track {
+    first_seen: u64,
+    samples: VecDeque<(u64, u64)>, // (Unix ms, bytes) within the window, oldest first
+    reported: bool,                // Since it last became a suspect
+}
+
+#[derive(Default)]
+struct Inner {
+    tracks: HashMap<(u32, String), Track>, // By PID and name, as PIDs get reused
+    last_sample: Option<u64>,
+    suspects: Vec<LeakSuspect>,
+}
+
+/// Tracks process memory and the processes that look like they leak
+#[derive(Default)]
+pub struct LeakDetector {
+    inner: Mutex<Inner>,
+}
+
+impl LeakDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sample the processes of a refresh, returning the ones that just became suspects
+    /// `available` is the system memory still free (bytes). Refreshes within
+    /// `SAMPLE_INTERVAL_MS` of the last sample are skipped.
+    pub fn record(
+        &self,
+        processes: &[ProcessInfo],
+        available: u64,
+        now: u64,
+        settings: &LeakSettings,
+    ) -> Vec<LeakSuspect> {
+        let Ok(mut inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+        if inner
+            .last_sample
+            .is_some_and(|t| now.saturating_sub(t) < SAMPLE_INTERVAL_MS)
+        {
+            return Vec::new();
+        }
+        let Inner {
+            tracks,
+            last_sample,
+            suspects,
+        } = &mut *inner;
+        *last_sample = Some(now);
+
+        let window_ms = settings.min_duration_minutes as u64 * 60_000;
+        let mut seen = HashSet::new();
+        let mut current = Vec::new();
+        let mut new = Vec::new();
+        for process in processes {
+            let key = (process.pid, process.name.clone());
+            let track = tracks.entry(key.clone()).or_insert_with(|| Track {
+                first_seen: now,
+                ..Default::default()
+            });
+            track.samples.push_back((now, process.memory));
+            while track
+                .samples
+                .front()
+                .is_some_and(|&(t, _)| now.saturating_sub(t) > window_ms)
+            {
+                track.samples.pop_front();
+            }
+            seen.insert(key);
+
+            match evaluate(process, track, available, now, window_ms, settings) {
+                Some(suspect) => {
+                    if !track.reported {
+                        track.reported = true;
+                        new.push(suspect.clone());
+                    }
+                    current.push(suspect);
+                }
+                None => track.reported = false,
+            }
+        }
+
+        // Exited processes take their history with them
+        tracks.retain(|key, _| seen.contains(key));
+        current.sort_by(|a, b| b.growth_per_hour.total_cmp(&a.growth_per_hour));
+        *suspects = current;
+        new
+    }
+
+    /// Processes that currently look like they leak, fastest growing first
+    pub fn suspects(&self) -> Vec<LeakSuspect> {
+        match self.inner.lock() {
+            Ok(inner) => inner.suspects.clone(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// The process as a suspect, if its history looks like a leak
+fn evaluate(
+    process: &ProcessInfo,
+    track: &Track,
+    available: u64,
+    now: u64,
+    window_ms: u64,
+    settings: &LeakSettings,
+) -> Option<LeakSuspect> {
+    let long_running = now.saturating_sub(track.first_seen) >= window_ms;
+    if !long_running || track.samples.len() < MIN_SAMPLES {
+        return None;
+    }
+
+    let (start, _) = *track.samples.front()?;
+    let points: Vec<(f64, f64)> = track
+        .samples
+        .iter()
+        .map(|&(t, bytes)| ((t - start) as f64 / HOUR_MS, bytes as f64))
+        .collect();
+    let (_, growth_per_hour, fit) = linear_fit(&points);
+
+    let steps = track.samples.len() - 1;
+    let shrinking = track
+        .samples
+        .iter()
+        .zip(track.samples.iter().skip(1))
+        .filter(|(a, b)| b.1 < a.1)
+        .count();
+    if growth_per_hour < settings.min_growth_mb_per_hour * MB
+        || fit < MIN_FIT
+        || shrinking as f64 > MAX_SHRINKING_STEPS * steps as f64
+    {
+        return None;
+    }
+
+    Some(LeakSuspect {
+        pid: process.pid,
+        name: process.name.clone(),
+        memory: process.memory,
+        growth_per_hour,
+        growing_secs: (now - start) / 1000,
+        fit,
+        exhaustion_secs: Some((available as f64 / growth_per_hour * 3600.0) as u64),
+        detected_at: now,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::monitor::fixtures;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn process(pid: u32, name: &str, mb: f64) -> ProcessInfo {
+        fixtures::info(&fixtures::process(pid, name, 0.0, (mb * MB) as u64))
+    }
+
+    /// Feed a refresh every 10 s for `seconds`, collecting new suspects
+    fn feed(
+        detector: &LeakDetector,
+        seconds: std::ops::Range<u64>,
+        processes: impl Fn(u64) -> Vec<ProcessInfo>,
+    ) -> Vec<LeakSuspect> {
+        let settings = LeakSettings::default();
+        seconds
+            .step_by(10)
+            .flat_map(|s| detector.record(&processes(s), 8 * GB, s * 1000, &settings))
+            .collect()
+    }
+
+    #[test]
+    fn test_flags_steady_growth_only() {
+        let detector = LeakDetector::new();
+        let processes = |s: u64| {
+            let hours = s as f64 / 3600.0;
+            vec![
+                // 200 MB an hour, allocated in 1-minute steps
+                process(1, "language-server", 500.0 + 200.0 * (s / 60) as f64 / 60.0),
+                // Big, but flat
+                process(2, "browser", 2000.0 + (s % 7) as f64),
+                // Grows fast but a collector gives it back every 5 minutes
+                process(3, "node", 300.0 + 600.0 * (hours % (5.0 / 60.0))),
+            ]
+        };
+
+        // Nothing before the 30 minute default
+        assert!(feed(&detector, 0..1790, processes).is_empty());
+
+        let suspects = feed(&detector, 1790..2700, processes);
+        assert_eq!(suspects.len(), 1, "{:?}", suspects);
+        let leak = &suspects[0];
+        assert_eq!((leak.pid, leak.name.as_str()), (1, "language-server"));
+        assert!((leak.growth_per_hour / MB - 200.0).abs() < 10.0);
+        assert!(leak.fit > 0.95);
+        assert!(leak.growing_secs >= 1790);
+        // 8 GB free at 200 MB an hour is about 41 hours
+        let hours = leak.exhaustion_secs.unwrap() as f64 / 3600.0;
+        assert!((hours - 41.0).abs() < 2.0, "{}", hours);
+
+        // Reported once, but listed for as long as it keeps growing
+        assert_eq!(detector.suspects().len(), 1);
+    }
+
+    #[test]
+    fn test_exit_and_pid_reuse_start_over() {
+        let detector = LeakDetector::new();
+        let leaking = |s: u64| vec![process(7, "dev-server", 100.0 + s as f64 / 10.0)];
+        assert_eq!(feed(&detector, 0..1900, leaking).len(), 1);
+
+        // The PID comes back as another program: no history, no suspect
+        let reused = |s: u64| vec![process(7, "cargo", 100.0 + s as f64 / 10.0)];
+        assert!(feed(&detector, 1900..2000, reused).is_empty());
+        assert!(detector.suspects().is_empty());
+    }
+}
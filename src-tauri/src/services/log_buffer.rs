@@ -0,0 +1,217 @@
+//! Bounded in-memory log for sidecar diagnostics
+//!
+//! Keeps the most recent lines the sidecar wrote to stderr (plus our own
+//! lifecycle messages) so sensor failures can be diagnosed after the fact.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Log severity, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Guess the severity of a raw stderr line
+    /// Prefers an explicit `[level]` tag, falls back to keywords
+    pub fn detect(line: &str) -> Self {
+        let lower = line.trim_start().to_lowercase();
+
+        if let Some(tag) = lower
+            .strip_prefix('[')
+            .and_then(|rest| rest.split(']').next())
+        {
+            match tag {
+                "debug" | "trace" => return LogLevel::Debug,
+                "info" => return LogLevel::Info,
+                "warn" | "warning" => return LogLevel::Warn,
+                "error" | "err" | "fatal" => return LogLevel::Error,
+                _ => {}
+            }
+        }
+
+        if lower.contains("error") || lower.contains("exception") || lower.contains("fatal") {
+            LogLevel::Error
+        } else if lower.contains("warn") {
+            LogLevel::Warn
+        } else {
+            LogLevel::Info
+        }
+    }
+}
+
+/// Where a log entry came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSource {
+    /// Written by the sidecar to stderr
+    Stderr,
+    /// Written by Pulse while supervising the sidecar
+    Supervisor,
+}
+
+/// A single log line
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub timestamp: u64, // Unix timestamp in milliseconds
+    pub level: LogLevel,
+    pub source: LogSource,
+    pub message: String,
+}
+
+type LogListener = Box<dyn Fn(&LogEntry) + Send + Sync>;
+
+/// Ring buffer of log entries with an optional listener for live updates
+pub struct LogBuffer {
+    entries: Mutex<VecDeque<LogEntry>>,
+    capacity: usize,
+    listener: RwLock<Option<LogListener>>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            listener: RwLock::new(None),
+        }
+    }
+
+    /// Call `listener` for every new entry (e.g. to emit a Tauri event)
+    pub fn set_listener<F>(&self, listener: F)
+    where
+        F: Fn(&LogEntry) + Send + Sync + 'static,
+    {
+        if let Ok(mut guard) = self.listener.write() {
+            *guard = Some(Box::new(listener));
+        }
+    }
+
+    /// Append a raw stderr line, detecting its severity
+    pub fn push_stderr(&self, line: &str) {
+        self.push(LogLevel::detect(line), LogSource::Stderr, line);
+    }
+
+    /// Append a supervisor message
+    pub fn push_supervisor(&self, level: LogLevel, message: &str) {
+        self.push(level, LogSource::Supervisor, message);
+    }
+
+    fn push(&self, level: LogLevel, source: LogSource, message: &str) {
+        let entry = LogEntry {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            level,
+            source,
+            message: message.to_string(),
+        };
+
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() >= self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(entry.clone());
+        }
+
+        if let Ok(guard) = self.listener.read() {
+            if let Some(listener) = guard.as_ref() {
+                listener(&entry);
+            }
+        }
+    }
+
+    /// Get entries at or above `min_level`, oldest first
+    /// `limit` keeps only the most recent N matching entries
+    pub fn entries(&self, min_level: Option<LogLevel>, limit: Option<usize>) -> Vec<LogEntry> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+
+        let min_level = min_level.unwrap_or(LogLevel::Debug);
+        let mut matching: Vec<LogEntry> = entries
+            .iter()
+            .filter(|e| e.level >= min_level)
+            .cloned()
+            .collect();
+
+        if let Some(limit) = limit {
+            let skip = matching.len().saturating_sub(limit);
+            matching.drain(..skip);
+        }
+        matching
+    }
+
+    /// Remove all entries
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_detect_level() {
+        assert_eq!(LogLevel::detect("[warn] Sensor missing"), LogLevel::Warn);
+        assert_eq!(LogLevel::detect("[ERROR] boom"), LogLevel::Error);
+        assert_eq!(LogLevel::detect("[debug] tick"), LogLevel::Debug);
+        assert_eq!(LogLevel::detect("Unhandled exception: x"), LogLevel::Error);
+        assert_eq!(LogLevel::detect("Warning: driver old"), LogLevel::Warn);
+        assert_eq!(LogLevel::detect("Opened 3 hardware items"), LogLevel::Info);
+    }
+
+    #[test]
+    fn test_buffer_is_bounded() {
+        let buffer = LogBuffer::new(3);
+        for i in 0..5 {
+            buffer.push_stderr(&format!("line {}", i));
+        }
+
+        let entries = buffer.entries(None, None);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].message, "line 2");
+        assert_eq!(entries[2].message, "line 4");
+    }
+
+    #[test]
+    fn test_filter_and_limit() {
+        let buffer = LogBuffer::new(10);
+        buffer.push_stderr("[info] a");
+        buffer.push_stderr("[error] b");
+        buffer.push_supervisor(LogLevel::Warn, "c");
+        buffer.push_stderr("[error] d");
+
+        let errors = buffer.entries(Some(LogLevel::Warn), Some(2));
+        let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["c", "[error] d"]);
+        assert_eq!(errors[0].source, LogSource::Supervisor);
+    }
+
+    #[test]
+    fn test_listener_is_called() {
+        let buffer = LogBuffer::new(10);
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_clone = Arc::clone(&count);
+        buffer.set_listener(move |_| {
+            count_clone.fetch_add(1, Ordering::SeqCst);
+        });
+
+        buffer.push_stderr("one");
+        buffer.push_stderr("two");
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,274 @@
+//! Merge sidecar readings into sysinfo stats
+//!
+//! sysinfo provides usage/memory, the sidecar adds temperatures, power and
+//! clocks. Sidecar readings are only used while they're fresh - a dead
+//! sidecar must not keep showing its last temperature forever.
+
+use std::time::Duration;
+
+use super::sidecar::SidecarData;
+use crate::models::{Settings, SourceFreshness, SystemStats};
+
+/// CPU fields that only the sidecar provides
+const SIDECAR_CPU_FIELDS: &[&str] = &[
+    "cpu.temperature",
+    "cpu.core_temperatures",
+    "cpu.power",
+    "cpu.package_temperature",
+    "cpu.max_temperature",
+    "cpu.tj_max",
+    "cpu.thermal_headroom",
+    "cpu.core_thermal_headroom",
+    "cpu.core_powers",
+];
+
+/// GPU fields that only the sidecar provides
+/// (temperature and fan speed also come from NVML, so they stay live)
+const SIDECAR_GPU_FIELDS: &[&str] = &[
+    "gpu.hot_spot_temperature",
+    "gpu.power",
+    "gpu.core_clock",
+    "gpu.memory_clock",
+    "gpu.load",
+];
+
+/// Merge the latest sidecar data into `stats`
+///
+/// `age` is how long ago the data was received. Data older than the
+/// configured threshold is ignored and the fields it would have filled
+/// are listed in `stats.freshness.stale_fields`.
+pub fn merge_sidecar_data(
+    stats: &mut SystemStats,
+    sidecar_data: Option<&SidecarData>,
+    age: Option<Duration>,
+    settings: &Settings,
+) {
+    let (sidecar_data, age) = match (sidecar_data, age) {
+        (Some(data), Some(age)) => (data, age),
+        // Nothing received yet - not stale, just absent
+        _ => {
+            stats.freshness.sidecar = SourceFreshness::default();
+            return;
+        }
+    };
+
+    let age_ms = age.as_millis() as u64;
+    let stale = age > Duration::from_millis(settings.stale_threshold_ms);
+    stats.freshness.sidecar = SourceFreshness {
+        timestamp: Some(stats.timestamp.saturating_sub(age_ms)),
+        age_ms: Some(age_ms),
+        stale,
+    };
+
+    if stale {
+        let mut fields: Vec<String> = SIDECAR_CPU_FIELDS.iter().map(|f| f.to_string()).collect();
+        if stats.gpu.is_some() {
+            fields.extend(SIDECAR_GPU_FIELDS.iter().map(|f| f.to_string()));
+        }
+        stats.freshness.stale_fields = fields;
+        return;
+    }
+
+    // CPU temperature from sidecar
+    if let Some(cpu_data) = &sidecar_data.cpu {
+        stats.cpu.temperature = cpu_data.temperature;
+        stats.cpu.power = cpu_data.power;
+        stats.cpu.package_temperature = cpu_data.package_temperature;
+        stats.cpu.max_temperature = cpu_data.max_temperature;
+
+        // Core temperatures - filter out None values
+        stats.cpu.core_temperatures = flatten(&cpu_data.core_temperatures);
+        stats.cpu.core_powers = flatten(&cpu_data.core_powers);
+
+        // Thermal headroom - how far each reading is from throttling
+        stats.cpu.tj_max = settings.tj_max_override.or(cpu_data.tj_max);
+        if let Some(tj_max) = stats.cpu.tj_max {
+            stats.cpu.thermal_headroom = stats
+                .cpu
+                .package_temperature
+                .or(stats.cpu.temperature)
+                .map(|t| tj_max - t);
+            stats.cpu.core_thermal_headroom = stats
+                .cpu
+                .core_temperatures
+                .as_ref()
+                .map(|temps| temps.iter().map(|t| tj_max - t).collect());
+        }
+    }
+
+    // GPU data from sidecar (first GPU if available)
+    if let Some(gpu_data) = sidecar_data.gpu.first() {
+        if let Some(ref mut gpu) = stats.gpu {
+            // Use sidecar GPU temp if available
+            if let Some(temp) = gpu_data.temperature {
+                gpu.temperature = Some(temp);
+            }
+            // Hot spot temperature
+            gpu.hot_spot_temperature = gpu_data.hot_spot_temperature;
+            // Power consumption
+            gpu.power = gpu_data.power;
+            // Core clock
+            gpu.core_clock = gpu_data.core_clock;
+            // Memory clock
+            gpu.memory_clock = gpu_data.memory_clock;
+            // Use sidecar fan speed if available and we don't have it
+            if gpu.fan_speed.is_none() {
+                gpu.fan_speed = gpu_data.fan_speed;
+            }
+            // Core load and vendor
+            gpu.load = gpu_data.load;
+            if gpu_data.vendor.is_some() {
+                gpu.vendor = gpu_data.vendor.clone();
+            }
+        }
+    }
+}
+
+/// Drop missing per-core readings, None if nothing is left
+fn flatten(values: &[Option<f32>]) -> Option<Vec<f32>> {
+    let values: Vec<f32> = values.iter().filter_map(|v| *v).collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::GpuStats;
+
+    fn sidecar_data() -> SidecarData {
+        let json = r#"{"cpu":{"name":"CPU","temperature":65.0,"package_temperature":66.0,"core_temperatures":[null,60.0,62.0],"max_temperature":62.0,"tj_max":100.0,"power":35.5,"core_powers":[null,4.5,5.0]},"gpu":[{"vendor":"AMD","temperature":70.0,"hot_spot_temperature":80.0,"power":150.0,"load":97.0}],"timestamp":1}"#;
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn stats() -> SystemStats {
+        SystemStats {
+            gpu: Some(GpuStats::default()),
+            timestamp: 100_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merges_fresh_data() {
+        let mut stats = stats();
+        let data = sidecar_data();
+        merge_sidecar_data(
+            &mut stats,
+            Some(&data),
+            Some(Duration::from_millis(500)),
+            &Settings::default(),
+        );
+
+        assert_eq!(stats.cpu.temperature, Some(65.0));
+        assert_eq!(stats.cpu.core_temperatures, Some(vec![60.0, 62.0]));
+        assert_eq!(stats.gpu.as_ref().unwrap().hot_spot_temperature, Some(80.0));
+        assert_eq!(stats.freshness.sidecar.timestamp, Some(99_500));
+        assert_eq!(stats.freshness.sidecar.age_ms, Some(500));
+        assert!(!stats.freshness.sidecar.stale);
+        assert!(stats.freshness.stale_fields.is_empty());
+    }
+
+    #[test]
+    fn test_thermal_headroom_and_dropped_fields() {
+        let mut stats = stats();
+        let data = sidecar_data();
+        merge_sidecar_data(
+            &mut stats,
+            Some(&data),
+            Some(Duration::from_millis(500)),
+            &Settings::default(),
+        );
+
+        assert_eq!(stats.cpu.package_temperature, Some(66.0));
+        assert_eq!(stats.cpu.max_temperature, Some(62.0));
+        assert_eq!(stats.cpu.tj_max, Some(100.0));
+        assert_eq!(stats.cpu.thermal_headroom, Some(34.0));
+        assert_eq!(stats.cpu.core_thermal_headroom, Some(vec![40.0, 38.0]));
+        assert_eq!(stats.cpu.core_powers, Some(vec![4.5, 5.0]));
+
+        let gpu = stats.gpu.as_ref().unwrap();
+        assert_eq!(gpu.load, Some(97.0));
+        assert_eq!(gpu.vendor.as_deref(), Some("AMD"));
+    }
+
+    #[test]
+    fn test_tj_max_override() {
+        let mut stats = stats();
+        let mut data = sidecar_data();
+        data.cpu.as_mut().unwrap().tj_max = None;
+
+        // No TjMax reported and no override - no headroom
+        merge_sidecar_data(
+            &mut stats,
+            Some(&data),
+            Some(Duration::from_millis(500)),
+            &Settings::default(),
+        );
+        assert!(stats.cpu.thermal_headroom.is_none());
+
+        let settings = Settings {
+            tj_max_override: Some(95.0),
+            ..Default::default()
+        };
+        merge_sidecar_data(
+            &mut stats,
+            Some(&data),
+            Some(Duration::from_millis(500)),
+            &settings,
+        );
+        assert_eq!(stats.cpu.thermal_headroom, Some(29.0));
+    }
+
+    #[test]
+    fn test_drops_stale_data() {
+        let mut stats = stats();
+        let data = sidecar_data();
+        merge_sidecar_data(
+            &mut stats,
+            Some(&data),
+            Some(Duration::from_secs(30)),
+            &Settings::default(),
+        );
+
+        assert!(stats.cpu.temperature.is_none());
+        assert!(stats.gpu.as_ref().unwrap().power.is_none());
+        assert!(stats.freshness.sidecar.stale);
+        assert!(stats
+            .freshness
+            .stale_fields
+            .contains(&"cpu.temperature".to_string()));
+        assert!(stats
+            .freshness
+            .stale_fields
+            .contains(&"gpu.power".to_string()));
+    }
+
+    #[test]
+    fn test_no_gpu_fields_without_gpu() {
+        let mut stats = SystemStats::default();
+        let data = sidecar_data();
+        merge_sidecar_data(
+            &mut stats,
+            Some(&data),
+            Some(Duration::from_secs(30)),
+            &Settings::default(),
+        );
+        assert!(stats
+            .freshness
+            .stale_fields
+            .iter()
+            .all(|f| f.starts_with("cpu.")));
+    }
+
+    #[test]
+    fn test_no_data_is_not_stale() {
+        let mut stats = stats();
+        merge_sidecar_data(&mut stats, None, None, &Settings::default());
+        assert_eq!(stats.freshness.sidecar, SourceFreshness::default());
+        assert!(stats.freshness.stale_fields.is_empty());
+    }
+}
@@ -0,0 +1,410 @@
+//! On-disk metric history
+//!
+//! Persists every stats sample in a SQLite database in the app data dir and
+//! rolls it up into 1-minute and 1-hour min/avg/max buckets as it's written,
+//! so long ranges stay cheap to query. Each resolution is pruned on its own
+//! retention period (see `RetentionSettings`).
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::history::metric_values;
+use crate::models::{HistoryPoint, MetricSeries, Resolution, RetentionSettings, SystemStats};
+
+/// Resolutions maintained next to the raw samples
+const ROLLUPS: [Resolution; 2] = [Resolution::Minute, Resolution::Hour];
+
+const HOUR_MS: u64 = 3_600_000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS metrics (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS samples (
+    metric_id INTEGER NOT NULL,
+    ts INTEGER NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (metric_id, ts)
+) WITHOUT ROWID;
+CREATE INDEX IF NOT EXISTS samples_ts ON samples (ts);
+CREATE TABLE IF NOT EXISTS rollups (
+    bucket_ms INTEGER NOT NULL,
+    metric_id INTEGER NOT NULL,
+    ts INTEGER NOT NULL,
+    min_value REAL NOT NULL,
+    max_value REAL NOT NULL,
+    sum_value REAL NOT NULL,
+    count INTEGER NOT NULL,
+    PRIMARY KEY (bucket_ms, metric_id, ts)
+) WITHOUT ROWID;
+CREATE INDEX IF NOT EXISTS rollups_ts ON rollups (bucket_ms, ts);
+";
+
+struct Inner {
+    conn: Connection,
+    metric_ids: HashMap<String, i64>,
+}
+
+/// SQLite-backed metric history with rollups
+pub struct MetricStore {
+    inner: Mutex<Inner>,
+}
+
+impl MetricStore {
+    /// Open (or create) the database at `path`
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open metric store {:?}: {}", path, e))?;
+        // WAL keeps the once-per-second writes from blocking readers
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .and_then(|_| conn.pragma_update(None, "synchronous", "NORMAL"))
+            .map_err(|e| format!("Failed to configure metric store: {}", e))?;
+        Self::init(conn)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn open_in_memory() -> Result<Self, String> {
+        Self::init(Connection::open_in_memory().map_err(|e| e.to_string())?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to create metric store schema: {}", e))?;
+
+        let metric_ids = {
+            let mut stmt = conn
+                .prepare("SELECT name, id FROM metrics")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<HashMap<String, i64>, _>>()
+                .map_err(|e| format!("Failed to load metrics: {}", e))?
+        };
+
+        Ok(Self {
+            inner: Mutex::new(Inner { conn, metric_ids }),
+        })
+    }
+
+    /// Persist every metric of a stats sample
+    pub fn record(&self, stats: &SystemStats) -> Result<(), String> {
+        self.record_values(stats.timestamp, &metric_values(stats))
+    }
+
+    /// Persist `(metric, value)` pairs at `timestamp` (Unix ms) and update
+    /// the rollups they fall into
+    pub fn record_values(&self, timestamp: u64, values: &[(String, f64)]) -> Result<(), String> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let Inner { conn, metric_ids } = &mut *inner;
+
+        let mut ids = Vec::with_capacity(values.len());
+        for (metric, value) in values {
+            ids.push((metric_id(conn, metric_ids, metric)?, *value));
+        }
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        {
+            let mut insert_sample = tx
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO samples (metric_id, ts, value) VALUES (?1, ?2, ?3)",
+                )
+                .map_err(|e| e.to_string())?;
+            let mut upsert_rollup = tx
+                .prepare_cached(
+                    "INSERT INTO rollups (bucket_ms, metric_id, ts, min_value, max_value, sum_value, count)
+                     VALUES (?1, ?2, ?3, ?4, ?4, ?4, 1)
+                     ON CONFLICT (bucket_ms, metric_id, ts) DO UPDATE SET
+                         min_value = min(min_value, excluded.min_value),
+                         max_value = max(max_value, excluded.max_value),
+                         sum_value = sum_value + excluded.sum_value,
+                         count = count + 1",
+                )
+                .map_err(|e| e.to_string())?;
+
+            for (id, value) in ids {
+                insert_sample
+                    .execute(params![id, timestamp as i64, value])
+                    .map_err(|e| format!("Failed to write sample: {}", e))?;
+                for bucket_ms in ROLLUPS.iter().filter_map(|r| r.bucket_ms()) {
+                    let bucket = timestamp - timestamp % bucket_ms;
+                    upsert_rollup
+                        .execute(params![bucket_ms as i64, id, bucket as i64, value])
+                        .map_err(|e| format!("Failed to write rollup: {}", e))?;
+                }
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit samples: {}", e))
+    }
+
+    /// Delete rows older than their resolution's retention period
+    /// Returns the number of deleted rows
+    pub fn prune(&self, retention: &RetentionSettings, now: u64) -> Result<usize, String> {
+        let inner = self
+            .inner
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let cutoff = |keep_ms: u64| now.saturating_sub(keep_ms) as i64;
+
+        let mut deleted = inner
+            .conn
+            .execute(
+                "DELETE FROM samples WHERE ts < ?1",
+                params![cutoff(retention.raw_hours as u64 * HOUR_MS)],
+            )
+            .map_err(|e| format!("Failed to prune samples: {}", e))?;
+
+        for (resolution, keep_ms) in [
+            (Resolution::Minute, retention.minute_days as u64 * DAY_MS),
+            (Resolution::Hour, retention.hour_days as u64 * DAY_MS),
+        ] {
+            deleted += inner
+                .conn
+                .execute(
+                    "DELETE FROM rollups WHERE bucket_ms = ?1 AND ts < ?2",
+                    params![resolution.bucket_ms().unwrap_or(0) as i64, cutoff(keep_ms)],
+                )
+                .map_err(|e| format!("Failed to prune rollups: {}", e))?;
+        }
+        Ok(deleted)
+    }
+
+    /// Names of all stored metrics, sorted
+    pub fn metrics(&self) -> Vec<String> {
+        let Ok(inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = inner.metric_ids.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Get the points of `metrics` between `from` and `to` (Unix ms, inclusive)
+    /// at `resolution`
+    ///
+    /// An empty `metrics` list means all metrics. Rollup points carry the
+    /// bucket average as value plus its min and max. Unknown metrics get an
+    /// empty series.
+    pub fn query(
+        &self,
+        metrics: &[String],
+        from: Option<u64>,
+        to: Option<u64>,
+        resolution: Resolution,
+    ) -> Result<Vec<MetricSeries>, String> {
+        let from = from.unwrap_or(0).min(i64::MAX as u64) as i64;
+        let to = to.unwrap_or(u64::MAX).min(i64::MAX as u64) as i64;
+        if from > to {
+            return Err(format!("Invalid range: from {} is after to {}", from, to));
+        }
+
+        let names = if metrics.is_empty() {
+            self.metrics()
+        } else {
+            metrics.to_vec()
+        };
+
+        let inner = self
+            .inner
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+        let sql = match resolution.bucket_ms() {
+            None => {
+                "SELECT ts, value, NULL, NULL FROM samples
+                 WHERE metric_id = ?1 AND ts BETWEEN ?2 AND ?3 ORDER BY ts"
+            }
+            Some(_) => {
+                "SELECT ts, sum_value / count, min_value, max_value FROM rollups
+                 WHERE bucket_ms = ?4 AND metric_id = ?1 AND ts BETWEEN ?2 AND ?3 ORDER BY ts"
+            }
+        };
+        let mut stmt = inner.conn.prepare(sql).map_err(|e| e.to_string())?;
+
+        let mut series = Vec::with_capacity(names.len());
+        for metric in names {
+            let points = match inner.metric_ids.get(&metric) {
+                Some(&id) => {
+                    let read_point = |row: &rusqlite::Row| {
+                        Ok(HistoryPoint {
+                            timestamp: row.get::<_, i64>(0)? as u64,
+                            value: row.get(1)?,
+                            min: row.get(2)?,
+                            max: row.get(3)?,
+                        })
+                    };
+                    let rows = match resolution.bucket_ms() {
+                        None => stmt.query_map(params![id, from, to], read_point),
+                        Some(bucket_ms) => {
+                            stmt.query_map(params![id, from, to, bucket_ms as i64], read_point)
+                        }
+                    };
+                    rows.and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                        .map_err(|e| format!("Failed to query {}: {}", metric, e))?
+                }
+                None => Vec::new(),
+            };
+            series.push(MetricSeries { metric, points });
+        }
+        Ok(series)
+    }
+}
+
+/// Id of `metric`, inserting it on first use
+fn metric_id(
+    conn: &Connection,
+    metric_ids: &mut HashMap<String, i64>,
+    metric: &str,
+) -> Result<i64, String> {
+    if let Some(&id) = metric_ids.get(metric) {
+        return Ok(id);
+    }
+
+    conn.execute(
+        "INSERT OR IGNORE INTO metrics (name) VALUES (?1)",
+        params![metric],
+    )
+    .and_then(|_| {
+        conn.query_row(
+            "SELECT id FROM metrics WHERE name = ?1",
+            params![metric],
+            |row| row.get(0),
+        )
+        .optional()
+    })
+    .map_err(|e| format!("Failed to register metric {}: {}", metric, e))?
+    .inspect(|&id| {
+        metric_ids.insert(metric.to_string(), id);
+    })
+    .ok_or_else(|| format!("Failed to register metric {}", metric))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(store: &MetricStore, timestamp: u64, value: f64) {
+        store
+            .record_values(timestamp, &[("cpu.temperature".to_string(), value)])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_raw_and_rollups() {
+        let store = MetricStore::open_in_memory().unwrap();
+        // Two minutes: 40, 50, 60 in the first, 70 in the second
+        record(&store, 0, 40.0);
+        record(&store, 20_000, 50.0);
+        record(&store, 40_000, 60.0);
+        record(&store, 60_000, 70.0);
+
+        let metric = ["cpu.temperature".to_string()];
+        let raw = store.query(&metric, None, None, Resolution::Raw).unwrap();
+        assert_eq!(raw[0].points.len(), 4);
+        assert_eq!(raw[0].points[3], HistoryPoint::sample(60_000, 70.0));
+
+        let minutes = store
+            .query(&metric, None, None, Resolution::Minute)
+            .unwrap();
+        assert_eq!(
+            minutes[0].points,
+            vec![
+                HistoryPoint {
+                    timestamp: 0,
+                    value: 50.0,
+                    min: Some(40.0),
+                    max: Some(60.0),
+                },
+                HistoryPoint {
+                    timestamp: 60_000,
+                    value: 70.0,
+                    min: Some(70.0),
+                    max: Some(70.0),
+                },
+            ]
+        );
+
+        let hours = store.query(&metric, None, None, Resolution::Hour).unwrap();
+        assert_eq!(hours[0].points.len(), 1);
+        assert_eq!(hours[0].points[0].value, 55.0);
+    }
+
+    #[test]
+    fn test_query_range_and_unknown_metric() {
+        let store = MetricStore::open_in_memory().unwrap();
+        for i in 0..5 {
+            record(&store, i * 1000, i as f64);
+        }
+
+        let metrics = ["cpu.temperature".to_string(), "gpu.power".to_string()];
+        let series = store
+            .query(&metrics, Some(1000), Some(3000), Resolution::Raw)
+            .unwrap();
+        let values: Vec<f64> = series[0].points.iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![1.0, 2.0, 3.0]);
+        assert!(series[1].points.is_empty());
+
+        assert_eq!(store.metrics(), vec!["cpu.temperature".to_string()]);
+        assert!(store.query(&[], Some(2), Some(1), Resolution::Raw).is_err());
+    }
+
+    #[test]
+    fn test_prune_per_resolution() {
+        let store = MetricStore::open_in_memory().unwrap();
+        let retention = RetentionSettings {
+            raw_hours: 1,
+            minute_days: 1,
+            hour_days: 2,
+            alert_days: 0,
+        };
+        record(&store, 0, 1.0);
+        let now = 30 * HOUR_MS;
+        record(&store, now, 2.0);
+
+        // Raw and minute data from 30 h ago are gone, the hour rollup stays
+        assert_eq!(store.prune(&retention, now).unwrap(), 2);
+        let metric = ["cpu.temperature".to_string()];
+        let raw = store.query(&metric, None, None, Resolution::Raw).unwrap();
+        assert_eq!(raw[0].points.len(), 1);
+        let hours = store.query(&metric, None, None, Resolution::Hour).unwrap();
+        assert_eq!(hours[0].points.len(), 2);
+    }
+
+    #[test]
+    fn test_reopen_keeps_data() {
+        let path = std::env::temp_dir()
+            .join(format!("pulse-metrics-{}", std::process::id()))
+            .join("metrics.db");
+        {
+            let store = MetricStore::open(&path).unwrap();
+            record(&store, 1000, 42.0);
+        }
+
+        let store = MetricStore::open(&path).unwrap();
+        record(&store, 2000, 43.0);
+        let series = store
+            .query(
+                &["cpu.temperature".to_string()],
+                None,
+                None,
+                Resolution::Raw,
+            )
+            .unwrap();
+        assert_eq!(series[0].points.len(), 2);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -1,5 +1,40 @@
+pub mod alert;
+pub mod alert_action;
+pub mod alert_log;
+pub mod anomaly;
+pub mod collector;
+pub mod compare;
+#[cfg(windows)]
+pub mod elevated;
+pub mod export;
+pub mod history;
+pub mod integrity;
+pub mod leak;
+pub mod log_buffer;
+pub mod merge;
+pub mod metric_store;
 pub mod monitor;
+pub mod notify;
+pub mod process;
+pub mod session;
+pub mod settings;
 pub mod sidecar;
+pub mod sidecar_recording;
+pub mod summary;
+pub mod watchdog;
 
+pub use alert::AlertEngine;
+pub use alert_log::AlertLog;
+pub use anomaly::AnomalyDetector;
+pub use leak::LeakDetector;
+pub use history::MetricHistory;
+pub use log_buffer::{LogEntry, LogLevel};
+pub use merge::merge_sidecar_data;
+pub use metric_store::MetricStore;
 pub use monitor::*;
-pub use sidecar::{start_sidecar, SidecarState, SidecarStatusInfo};
+pub use notify::Notifier;
+pub use session::SessionManager;
+pub use settings::SettingsStore;
+pub use sidecar::{init_sidecar, SidecarStatusPayload, SidecarSupervisor};
+pub use summary::SensorSummaryTracker;
+pub use watchdog::Watchdog;
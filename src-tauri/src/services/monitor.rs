@@ -1,9 +1,14 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 use sysinfo::{
     CpuRefreshKind, MemoryRefreshKind, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System,
+    UpdateKind,
 };
 
-use crate::models::{CpuStats, GpuStats, ProcessInfo, RamStats, SystemInfo, SystemStats};
+use crate::models::{
+    CpuStats, DataFreshness, GpuStats, ProcessInfo, RamStats, SourceFreshness, SystemInfo,
+    SystemStats, WatchdogRule, WatchedProcess,
+};
+use crate::services::watchdog::{Watchdog, WatchdogTransition};
 
 /// GPU monitoring service using NVML (NVIDIA Management Library)
 pub struct GpuMonitor {
@@ -60,6 +65,8 @@ impl GpuMonitor {
             power: None,        // Will be filled from sidecar
             core_clock: None,   // Will be filled from sidecar
             memory_clock: None, // Will be filled from sidecar
+            load: None,         // Will be filled from sidecar
+            vendor: Some("NVIDIA".to_string()),
         })
     }
 }
@@ -96,7 +103,41 @@ impl SystemMonitor {
     pub fn refresh(&mut self) {
         self.system.refresh_cpu_all();
         self.system.refresh_memory();
-        self.system.refresh_processes(ProcessesToUpdate::All, true);
+        // Command line and working directory are read once per process,
+        // also for ones started later - the watchdog matches and restarts by them
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_cpu()
+                .with_memory()
+                .with_exe(UpdateKind::OnlyIfNotSet)
+                .with_cmd(UpdateKind::OnlyIfNotSet)
+                .with_cwd(UpdateKind::OnlyIfNotSet),
+        );
+    }
+
+    /// Refresh all system information and check the fresh process list
+    /// against the watchdog rules, handing every transition and the time of
+    /// the check to `on_transition`
+    pub fn refresh_supervised(
+        &mut self,
+        watchdog: &Watchdog,
+        rules: &[WatchdogRule],
+        mut on_transition: impl FnMut(WatchdogTransition, u64),
+    ) {
+        self.refresh();
+        if rules.is_empty() {
+            return;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        for transition in watchdog.evaluate(rules, &self.get_watched_processes(), now) {
+            on_transition(transition, now);
+        }
     }
 
     /// Get current CPU statistics
@@ -138,6 +179,12 @@ impl SystemMonitor {
             temperature: None,       // Will be filled from sidecar
             core_temperatures: None, // Will be filled from sidecar
             power: None,             // Will be filled from sidecar
+            package_temperature: None,
+            max_temperature: None,
+            tj_max: None,
+            thermal_headroom: None,
+            core_thermal_headroom: None,
+            core_powers: None,
         }
     }
 
@@ -205,10 +252,9 @@ impl SystemMonitor {
         }
     }
 
-    /// Get top processes sorted by CPU usage
-    pub fn get_top_processes(&self, limit: usize) -> Vec<ProcessInfo> {
-        let mut processes: Vec<ProcessInfo> = self
-            .system
+    /// Get every process from the last refresh, in no particular order
+    pub fn get_processes(&self) -> Vec<ProcessInfo> {
+        self.system
             .processes()
             .iter()
             .map(|(pid, process)| ProcessInfo {
@@ -217,6 +263,34 @@ impl SystemMonitor {
                 cpu_usage: process.cpu_usage(),
                 memory: process.memory(),
             })
+            .collect()
+    }
+
+    /// Get every process with its command line, for the watchdog
+    pub fn get_watched_processes(&self) -> Vec<WatchedProcess> {
+        self.system
+            .processes()
+            .iter()
+            .map(|(pid, process)| WatchedProcess {
+                pid: pid.as_u32(),
+                name: process.name().to_string_lossy().to_string(),
+                cmd: process
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().to_string())
+                    .collect(),
+                cwd: process.cwd().map(|cwd| cwd.to_path_buf()),
+                cpu_usage: process.cpu_usage(),
+                memory: process.memory(),
+            })
+            .collect()
+    }
+
+    /// Get top processes sorted by CPU usage
+    pub fn get_top_processes(&self, limit: usize) -> Vec<ProcessInfo> {
+        let mut processes: Vec<ProcessInfo> = self
+            .get_processes()
+            .into_iter()
             .filter(|p| p.cpu_usage > 0.0 || p.memory > 0) // Filter out idle processes
             .collect();
 
@@ -246,6 +320,14 @@ impl SystemMonitor {
             system_info: self.get_system_info(),
             processes: self.get_top_processes(10), // Top 10 processes
             timestamp,
+            freshness: DataFreshness {
+                sysinfo: SourceFreshness {
+                    timestamp: Some(timestamp),
+                    age_ms: Some(0),
+                    stale: false,
+                },
+                ..Default::default()
+            },
         }
     }
 
@@ -261,6 +343,37 @@ impl Default for SystemMonitor {
     }
 }
 
+/// Processes shaped like a refresh reports them, for the tests of
+/// everything that reads process lists
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::models::{ProcessInfo, WatchedProcess};
+    use std::path::PathBuf;
+
+    /// A process running `cmd` (split on spaces) from /home/dev/api
+    pub fn process(pid: u32, cmd: &str, cpu_usage: f32, memory: u64) -> WatchedProcess {
+        let cmd: Vec<String> = cmd.split(' ').map(String::from).collect();
+        WatchedProcess {
+            pid,
+            name: cmd[0].rsplit('/').next().unwrap().to_string(),
+            cmd,
+            cwd: Some(PathBuf::from("/home/dev/api")),
+            cpu_usage,
+            memory,
+        }
+    }
+
+    /// The same process as `get_processes` lists it
+    pub fn info(process: &WatchedProcess) -> ProcessInfo {
+        ProcessInfo {
+            pid: process.pid,
+            name: process.name.clone(),
+            cpu_usage: process.cpu_usage,
+            memory: process.memory,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,6 +384,30 @@ mod tests {
         assert!(monitor.get_cpu_stats().logical_cores > 0);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_watched_process_started_after_monitor() {
+        let mut monitor = SystemMonitor::new();
+        let marker = format!("pulse-monitor-{}", std::process::id());
+        // The trailing `true` keeps the shell from exec'ing into sleep
+        let mut child = std::process::Command::new("/bin/sh")
+            .args(["-c", "sleep 30; true", &marker])
+            .spawn()
+            .unwrap();
+
+        monitor.refresh();
+        let watched = monitor
+            .get_watched_processes()
+            .into_iter()
+            .find(|p| p.pid == child.id());
+        let _ = child.kill();
+        let _ = child.wait();
+
+        let watched = watched.unwrap();
+        assert!(watched.cmd.contains(&marker));
+        assert_eq!(watched.cwd, std::env::current_dir().ok());
+    }
+
     #[test]
     fn test_ram_stats() {
         let monitor = SystemMonitor::new();
@@ -0,0 +1,285 @@
+//! Desktop notifications
+//!
+//! Tells the user about fired alerts and a sidecar that gave up restarting
+//! while the window is hidden in the tray. Every notification has a key
+//! (the alert rule id, or "sidecar") so one flapping rule can't drown out
+//! the rest: a key notifies at most once per `min_interval_secs`, and all
+//! keys together at most `max_per_hour` times.
+//!
+//! Clicking a notification opens the main window on the related card. That
+//! needs notification actions, which only freedesktop servers (Linux/BSD)
+//! report back; elsewhere the notification is shown without a click action.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use super::sidecar::SidecarError;
+use crate::models::{AlertEvent, NotificationSettings, Severity};
+
+const HOUR_MS: u64 = 3_600_000;
+
+/// A notification to show, and the dashboard card it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub key: String, // Rate limiting key
+    pub title: String,
+    pub body: String,
+    pub severity: Severity,
+    pub card: String, // "cpu", "gpu", "ram" or "sidecar"
+}
+
+impl Notification {
+    pub fn for_alert(alert: &AlertEvent) -> Self {
+        let title = match alert.severity {
+            Severity::Critical => format!("Critical: {}", alert.metric),
+            Severity::Warning => format!("Warning: {}", alert.metric),
+            Severity::Info => alert.metric.clone(),
+        };
+        Self {
+            key: alert.rule_id.clone(),
+            title,
+            body: format!(
+                "{} is {} ({})",
+                alert.metric,
+                format_value(alert.value),
+                alert.condition
+            ),
+            severity: alert.severity,
+            card: card_for_metric(&alert.metric).to_string(),
+        }
+    }
+
+    pub fn for_sidecar_failure(error: &SidecarError) -> Self {
+        Self {
+            key: "sidecar".to_string(),
+            title: "Sensor readings stopped".to_string(),
+            body: format!(
+                "{}. Temperatures and power are unavailable until the sidecar is restarted.",
+                error.message
+            ),
+            severity: Severity::Critical,
+            card: "sidecar".to_string(),
+        }
+    }
+}
+
+/// Dashboard card showing a metric, by its prefix
+fn card_for_metric(metric: &str) -> &'static str {
+    match metric.split('.').next() {
+        Some("gpu") => "gpu",
+        Some("ram") => "ram",
+        _ => "cpu",
+    }
+}
+
+fn format_value(value: f64) -> String {
+    let text = format!("{:.1}", value);
+    text.strip_suffix(".0").map(str::to_string).unwrap_or(text)
+}
+
+/// Per-key interval and global hourly cap
+#[derive(Debug, Default)]
+struct RateLimiter {
+    last_by_key: HashMap<String, u64>,
+    recent: VecDeque<u64>, // Times of notifications within the last hour
+}
+
+impl RateLimiter {
+    fn allow(&mut self, key: &str, now: u64, settings: &NotificationSettings) -> bool {
+        while self
+            .recent
+            .front()
+            .is_some_and(|&t| now.saturating_sub(t) >= HOUR_MS)
+        {
+            self.recent.pop_front();
+        }
+
+        let too_soon = self.last_by_key.get(key).is_some_and(|&t| {
+            now.saturating_sub(t) < settings.min_interval_secs.saturating_mul(1000)
+        });
+        if too_soon || self.recent.len() >= settings.max_per_hour as usize {
+            return false;
+        }
+
+        self.last_by_key.insert(key.to_string(), now);
+        self.recent.push_back(now);
+        true
+    }
+}
+
+type ShowFn = Box<dyn Fn(Notification) + Send + Sync>;
+
+/// Rate-limited notification sender
+pub struct Notifier {
+    limiter: Mutex<RateLimiter>,
+    show: ShowFn,
+}
+
+impl Notifier {
+    /// `show` displays a notification that passed the rate limits
+    pub fn new(show: impl Fn(Notification) + Send + Sync + 'static) -> Self {
+        Self {
+            limiter: Mutex::new(RateLimiter::default()),
+            show: Box::new(show),
+        }
+    }
+
+    /// Show `notification` unless notifications are off or rate-limited
+    /// Returns whether it was shown
+    pub fn notify(
+        &self,
+        notification: Notification,
+        settings: &NotificationSettings,
+        now: u64,
+    ) -> bool {
+        if !settings.enabled {
+            return false;
+        }
+        let allowed = match self.limiter.lock() {
+            Ok(mut limiter) => limiter.allow(&notification.key, now, settings),
+            Err(_) => false,
+        };
+        if allowed {
+            (self.show)(notification);
+        }
+        allowed
+    }
+}
+
+/// Show a native desktop notification
+/// `on_click` gets the notification's card when the user clicks it
+pub fn show_desktop(notification: Notification, on_click: impl FnOnce(&str) + Send + 'static) {
+    let mut desktop = notify_rust::Notification::new();
+    desktop
+        .appname("Pulse")
+        .summary(&notification.title)
+        .body(&notification.body);
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        use notify_rust::{Hint, Urgency};
+        desktop
+            .action("default", "Open Pulse")
+            .hint(Hint::Category("device".to_string()))
+            .urgency(match notification.severity {
+                Severity::Info => Urgency::Low,
+                Severity::Warning => Urgency::Normal,
+                Severity::Critical => Urgency::Critical,
+            });
+    }
+
+    // Showing talks to the notification server, and waiting for the click
+    // blocks until the notification closes - keep both off the caller's thread
+    std::thread::spawn(move || match desktop.show() {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        Ok(handle) => handle.wait_for_action(|action| {
+            if action == "default" {
+                on_click(&notification.card);
+            }
+        }),
+        #[cfg(not(all(unix, not(target_os = "macos"))))]
+        Ok(_) => drop(on_click),
+        Err(e) => eprintln!("[Notify] Failed to show notification: {}", e),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::sidecar::SidecarErrorCode;
+    use std::sync::mpsc;
+
+    fn alert(rule_id: &str, metric: &str, severity: Severity) -> AlertEvent {
+        AlertEvent {
+            rule_id: rule_id.to_string(),
+            condition: format!("{} > 90 for 30s", metric),
+            severity,
+            metric: metric.to_string(),
+            threshold: 90.0,
+            value: 93.3,
+            peak: 93.3,
+            fired_at: 0,
+            resolved_at: None,
+            acknowledged_at: None,
+            actions: Vec::new(),
+        }
+    }
+
+    fn notifier() -> (Notifier, mpsc::Receiver<Notification>) {
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let notifier = Notifier::new(move |n| {
+            let _ = tx.lock().unwrap().send(n);
+        });
+        (notifier, rx)
+    }
+
+    #[test]
+    fn test_alert_content() {
+        let n = Notification::for_alert(&alert(
+            "gpu-hot",
+            "gpu.hot_spot_temperature",
+            Severity::Critical,
+        ));
+        assert_eq!(n.key, "gpu-hot");
+        assert_eq!(n.title, "Critical: gpu.hot_spot_temperature");
+        assert_eq!(
+            n.body,
+            "gpu.hot_spot_temperature is 93.3 (gpu.hot_spot_temperature > 90 for 30s)"
+        );
+        assert_eq!(n.card, "gpu");
+        assert_eq!(
+            Notification::for_alert(&alert("r", "ram.usage_percent", Severity::Info)).card,
+            "ram"
+        );
+
+        let error = SidecarError::new(
+            SidecarErrorCode::Crashed,
+            "Sidecar crashed 3 times, giving up",
+        );
+        let n = Notification::for_sidecar_failure(&error);
+        assert_eq!(n.card, "sidecar");
+        assert!(n.body.starts_with("Sidecar crashed 3 times, giving up."));
+    }
+
+    #[test]
+    fn test_per_key_interval() {
+        let (notifier, rx) = notifier();
+        let settings = NotificationSettings {
+            min_interval_secs: 60,
+            ..Default::default()
+        };
+        let cpu = Notification::for_alert(&alert("cpu-hot", "cpu.temperature", Severity::Warning));
+        let gpu = Notification::for_alert(&alert("gpu-hot", "gpu.temperature", Severity::Warning));
+
+        assert!(notifier.notify(cpu.clone(), &settings, 0));
+        assert!(!notifier.notify(cpu.clone(), &settings, 30_000));
+        assert!(notifier.notify(gpu, &settings, 30_000));
+        assert!(notifier.notify(cpu, &settings, 60_000));
+        assert_eq!(rx.try_iter().count(), 3);
+    }
+
+    #[test]
+    fn test_hourly_cap_and_disabled() {
+        let (notifier, rx) = notifier();
+        let settings = NotificationSettings {
+            min_interval_secs: 0,
+            max_per_hour: 2,
+            ..Default::default()
+        };
+        let n = Notification::for_alert(&alert("cpu-hot", "cpu.temperature", Severity::Warning));
+
+        assert!(notifier.notify(n.clone(), &settings, 0));
+        assert!(notifier.notify(n.clone(), &settings, 1000));
+        assert!(!notifier.notify(n.clone(), &settings, 2000));
+        // The first one drops out of the window after an hour
+        assert!(notifier.notify(n.clone(), &settings, HOUR_MS));
+
+        let off = NotificationSettings {
+            enabled: false,
+            ..settings
+        };
+        assert!(!notifier.notify(n, &off, 10 * HOUR_MS));
+        assert_eq!(rx.try_iter().count(), 3);
+    }
+}
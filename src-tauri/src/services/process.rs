@@ -0,0 +1,402 @@
+//! Supervised child process
+//!
+//! Spawns a program with piped stdin/stdout/stderr, hands every output line
+//! to a callback on its own reader thread and keeps the child handle so the
+//! process can be stopped or sent commands at any time. Tracks PID, exit
+//! code and run history.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+/// CREATE_NO_WINDOW - don't flash a console window on Windows
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Number of past runs kept in the history
+const MAX_HISTORY: usize = 20;
+
+/// One run of a supervised process (from spawn to exit)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProcessRun {
+    pub pid: u32,
+    pub started_at: u64,        // Unix timestamp in milliseconds
+    pub ended_at: Option<u64>,  // None while running
+    pub exit_code: Option<i32>, // None while running or if killed by a signal
+    pub killed: bool,           // Stopped by us rather than exiting on its own
+}
+
+/// A child process that can be started, stopped and restarted
+pub struct SupervisedProcess {
+    program: PathBuf,
+    args: Vec<String>,
+    child: Option<Child>,
+    stdin: Option<ChildStdin>,
+    history: VecDeque<ProcessRun>,
+}
+
+impl SupervisedProcess {
+    pub fn new(program: impl Into<PathBuf>, args: Vec<String>) -> Self {
+        Self {
+            program: program.into(),
+            args,
+            child: None,
+            stdin: None,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Program path this process runs
+    pub fn program(&self) -> &Path {
+        &self.program
+    }
+
+    /// Arguments used for the next start
+    #[cfg(windows)]
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Replace the arguments used for the next start
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    /// Spawn `executable` - the program path or e.g. a verified copy of
+    /// it - and start reading its stdout and stderr
+    ///
+    /// `on_line` is called for every non-empty stdout line, `on_stderr` for
+    /// every non-empty stderr line and `on_eof` once stdout is closed
+    /// (usually because the process exited). Stderr is always drained so
+    /// the child can't block on a full pipe.
+    pub fn start_as<L, S, E>(
+        &mut self,
+        executable: &Path,
+        on_line: L,
+        on_stderr: S,
+        on_eof: E,
+    ) -> io::Result<u32>
+    where
+        L: FnMut(&str) + Send + 'static,
+        S: FnMut(&str) + Send + 'static,
+        E: FnOnce() + Send + 'static,
+    {
+        if self.is_running() {
+            return Err(io::Error::other("Process is already running"));
+        }
+
+        let mut command = Command::new(executable);
+        command
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        #[cfg(windows)]
+        command.creation_flags(CREATE_NO_WINDOW);
+
+        let mut child = command.spawn()?;
+
+        let (stdout, stderr) = match (child.stdout.take(), child.stderr.take()) {
+            (Some(stdout), Some(stderr)) => (stdout, stderr),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(io::Error::other("Failed to capture stdout/stderr"));
+            }
+        };
+
+        let pid = child.id();
+        self.stdin = child.stdin.take();
+        self.child = Some(child);
+        self.push_run(ProcessRun {
+            pid,
+            started_at: now_millis(),
+            ended_at: None,
+            exit_code: None,
+            killed: false,
+        });
+
+        thread::spawn(move || read_lines(stderr, on_stderr));
+        thread::spawn(move || {
+            read_lines(stdout, on_line);
+            on_eof();
+        });
+
+        Ok(pid)
+    }
+
+    /// Send one line to the process's stdin
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotConnected, "Process is not running"))?;
+        writeln!(stdin, "{}", line)?;
+        stdin.flush()
+    }
+
+    /// Kill the process and wait for it, returning its exit code
+    pub fn stop(&mut self) -> Option<i32> {
+        self.stdin = None;
+        let mut child = self.child.take()?;
+
+        // It may have exited on its own just before the kill
+        let killed = match child.try_wait() {
+            Ok(Some(_)) => false,
+            _ => child.kill().is_ok(),
+        };
+        let status = child.wait().ok();
+        self.finish_run(status, killed);
+        status.and_then(|s| s.code())
+    }
+
+    /// Check whether the process has exited and record its exit status
+    /// Returns the finished run if the process exited since the last poll
+    pub fn poll(&mut self) -> Option<ProcessRun> {
+        let child = self.child.as_mut()?;
+
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                self.child = None;
+                self.stdin = None;
+                self.finish_run(Some(status), false);
+                self.history.back().cloned()
+            }
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("[Process] Failed to query exit status: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Check if the process is still alive
+    pub fn is_running(&mut self) -> bool {
+        self.poll();
+        self.child.is_some()
+    }
+
+    /// PID of the running process
+    pub fn pid(&self) -> Option<u32> {
+        self.child.as_ref().map(|c| c.id())
+    }
+
+    /// Past and current runs, oldest first
+    pub fn history(&self) -> Vec<ProcessRun> {
+        self.history.iter().cloned().collect()
+    }
+
+    fn push_run(&mut self, run: ProcessRun) {
+        if self.history.len() >= MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(run);
+    }
+
+    fn finish_run(&mut self, status: Option<ExitStatus>, killed: bool) {
+        if let Some(run) = self.history.back_mut() {
+            if run.ended_at.is_none() {
+                run.ended_at = Some(now_millis());
+                run.exit_code = status.and_then(|s| s.code());
+                run.killed = killed;
+            }
+        }
+    }
+}
+
+impl Drop for SupervisedProcess {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Feed every non-empty line of `source` to `on_line` until EOF
+/// Lines that aren't valid UTF-8 (e.g. text in the local code page) are
+/// decoded lossily rather than ending the read
+pub(crate) fn read_lines<R: Read>(source: R, mut on_line: impl FnMut(&str)) {
+    let mut reader = BufReader::new(source);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                let line = line.trim();
+                if !line.is_empty() {
+                    on_line(line);
+                }
+            }
+            Err(e) => {
+                eprintln!("[Process] Read error: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn shell(script: &str) -> SupervisedProcess {
+        SupervisedProcess::new("/bin/sh", vec!["-c".to_string(), script.to_string()])
+    }
+
+    fn start<L, S, E>(
+        process: &mut SupervisedProcess,
+        on_line: L,
+        on_stderr: S,
+        on_eof: E,
+    ) -> io::Result<u32>
+    where
+        L: FnMut(&str) + Send + 'static,
+        S: FnMut(&str) + Send + 'static,
+        E: FnOnce() + Send + 'static,
+    {
+        let program = process.program().to_path_buf();
+        process.start_as(&program, on_line, on_stderr, on_eof)
+    }
+
+    fn wait_for_exit(process: &mut SupervisedProcess) -> ProcessRun {
+        for _ in 0..100 {
+            if let Some(run) = process.poll() {
+                return run;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        panic!("process did not exit");
+    }
+
+    #[test]
+    fn test_reads_lines_and_exit_code() {
+        let mut process = shell("echo one; echo; echo oops >&2; echo two; exit 3");
+        let (line_tx, line_rx) = mpsc::channel();
+        let (err_tx, err_rx) = mpsc::channel();
+        let (eof_tx, eof_rx) = mpsc::channel();
+
+        let pid = start(
+            &mut process,
+            move |line| line_tx.send(line.to_string()).unwrap(),
+            move |line| err_tx.send(line.to_string()).unwrap(),
+            move || eof_tx.send(()).unwrap(),
+        )
+        .unwrap();
+        eof_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let lines: Vec<String> = line_rx.try_iter().collect();
+        assert_eq!(lines, vec!["one", "two"]);
+        let errors = err_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(errors, "oops");
+
+        let run = wait_for_exit(&mut process);
+        assert_eq!(run.pid, pid);
+        assert_eq!(run.exit_code, Some(3));
+        assert!(!run.killed);
+        assert!(process.pid().is_none());
+    }
+
+    #[test]
+    fn test_invalid_utf8_line_keeps_reading() {
+        let mut lines = Vec::new();
+        read_lines(&b"one\n\xff\xfeLatin \xe9\ntwo\n"[..], |line| {
+            lines.push(line.to_string())
+        });
+        assert_eq!(lines, vec!["one", "\u{fffd}\u{fffd}Latin \u{fffd}", "two"]);
+    }
+
+    #[test]
+    fn test_stop_kills_process() {
+        let mut process = shell("sleep 30");
+        let pid = start(&mut process, |_| {}, |_| {}, || {}).unwrap();
+        assert_eq!(process.pid(), Some(pid));
+        assert!(process.is_running());
+
+        assert_eq!(process.stop(), None);
+        assert!(!process.is_running());
+
+        let run = process.history().pop().unwrap();
+        assert!(run.killed);
+        assert!(run.ended_at.is_some());
+    }
+
+    #[test]
+    fn test_stop_after_exit_is_not_a_kill() {
+        let mut process = shell("exit 0");
+        start(&mut process, |_| {}, |_| {}, || {}).unwrap();
+        thread::sleep(Duration::from_millis(300));
+
+        assert_eq!(process.stop(), Some(0));
+        let run = process.history().pop().unwrap();
+        assert!(!run.killed);
+        assert_eq!(run.exit_code, Some(0));
+    }
+
+    #[test]
+    fn test_restart_history() {
+        let mut process = shell("exit 1");
+        for _ in 0..3 {
+            start(&mut process, |_| {}, |_| {}, || {}).unwrap();
+            wait_for_exit(&mut process);
+        }
+
+        let history = process.history();
+        assert_eq!(history.len(), 3);
+        assert!(history.iter().all(|r| r.exit_code == Some(1)));
+    }
+
+    #[test]
+    fn test_refuses_second_start_while_running() {
+        let mut process = shell("sleep 30");
+        start(&mut process, |_| {}, |_| {}, || {}).unwrap();
+        assert!(start(&mut process, |_| {}, |_| {}, || {}).is_err());
+        process.stop();
+    }
+
+    #[test]
+    fn test_missing_program() {
+        let mut process = SupervisedProcess::new("/nonexistent/sidecar", Vec::new());
+        let err = start(&mut process, |_| {}, |_| {}, || {}).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(process.history().is_empty());
+    }
+
+    #[test]
+    fn test_write_line_to_stdin() {
+        let mut process = shell("read cmd; echo \"got $cmd\"");
+        let (line_tx, line_rx) = mpsc::channel();
+        start(
+            &mut process,
+            move |line| line_tx.send(line.to_string()).unwrap(),
+            |_| {},
+            || {},
+        )
+        .unwrap();
+
+        process.write_line("hardware cpu,gpu").unwrap();
+        assert_eq!(
+            line_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "got hardware cpu,gpu"
+        );
+
+        wait_for_exit(&mut process);
+        assert!(process.write_line("too late").is_err());
+    }
+}
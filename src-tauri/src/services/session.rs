@@ -0,0 +1,577 @@
+//! Record and play back full `SystemStats` sessions
+//!
+//! A session file is gzip-compressed JSON lines: a header line, then every
+//! emitted `SystemStats` (processes included). Playing a session re-emits
+//! the samples at their original pace times a speed factor, so a gaming or
+//! benchmark run can be reviewed in the normal dashboard afterwards.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::models::{PlaybackStatus, SystemStats};
+
+/// Value of the header's `format` field
+const FORMAT: &str = "pulse-session";
+const VERSION: u32 = 1;
+
+/// Longest single sleep while playing, so seeks and stops are noticed quickly
+const MAX_SLEEP: Duration = Duration::from_millis(50);
+
+/// First line of a session file
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionHeader {
+    format: String,
+    version: u32,
+    started: String, // RFC 3339, local time
+}
+
+/// Writes emitted stats to a session file
+pub struct SessionRecorder {
+    path: PathBuf,
+    writer: GzEncoder<BufWriter<File>>,
+    samples: usize,
+}
+
+impl SessionRecorder {
+    /// Create a new session file at `path`
+    pub fn create(path: PathBuf) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create session dir: {}", e))?;
+        }
+
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create session {:?}: {}", path, e))?;
+        let mut writer = GzEncoder::new(BufWriter::new(file), Compression::default());
+        let header = SessionHeader {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            started: chrono::Local::now().to_rfc3339(),
+        };
+        serde_json::to_writer(&mut writer, &header)
+            .map_err(|e| format!("Failed to write session: {}", e))?;
+        writeln!(writer).map_err(|e| format!("Failed to write session: {}", e))?;
+
+        Ok(Self {
+            path,
+            writer,
+            samples: 0,
+        })
+    }
+
+    /// Default file name for a session started now
+    pub fn default_file_name() -> String {
+        format!(
+            "session-{}.pulse-session",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        )
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one sample
+    pub fn record(&mut self, stats: &SystemStats) -> Result<(), String> {
+        serde_json::to_writer(&mut self.writer, stats)
+            .map_err(|e| e.to_string())
+            .and_then(|_| writeln!(self.writer).map_err(|e| e.to_string()))
+            .map_err(|e| format!("Failed to write session: {}", e))?;
+        self.samples += 1;
+        Ok(())
+    }
+
+    /// Flush and close the file, returning its path and sample count
+    pub fn finish(self) -> Result<(PathBuf, usize), String> {
+        self.writer
+            .finish()
+            .and_then(|mut inner| inner.flush())
+            .map_err(|e| format!("Failed to finish session: {}", e))?;
+        Ok((self.path, self.samples))
+    }
+}
+
+/// Load all samples of a session file, oldest first
+pub fn load_session(path: &Path) -> Result<Vec<SystemStats>, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open session {:?}: {}", path, e))?;
+    let mut lines = BufReader::new(GzDecoder::new(file)).lines();
+
+    let header: SessionHeader = lines
+        .next()
+        .ok_or_else(|| "Session file is empty".to_string())?
+        .map_err(|e| format!("Failed to read session: {}", e))
+        .and_then(|line| {
+            serde_json::from_str(&line).map_err(|_| "Not a Pulse session file".to_string())
+        })?;
+    if header.format != FORMAT || header.version > VERSION {
+        return Err(format!(
+            "Unsupported session format {} v{}",
+            header.format, header.version
+        ));
+    }
+
+    let mut samples = Vec::new();
+    for (index, line) in lines.enumerate() {
+        match line {
+            Ok(line) if line.trim().is_empty() => {}
+            Ok(line) => samples.push(
+                serde_json::from_str(&line)
+                    .map_err(|e| format!("Invalid session sample {}: {}", index + 1, e))?,
+            ),
+            // A session cut short (e.g. the app was killed) keeps what was flushed
+            Err(_) if !samples.is_empty() => break,
+            Err(e) => return Err(format!("Failed to read session: {}", e)),
+        }
+    }
+    Ok(samples)
+}
+
+struct PlayerControl {
+    speed: f64,
+    seek: Option<u64>,
+    position_ms: u64,
+    finished: bool,
+}
+
+/// Plays a loaded session on a background thread - dropping it stops playback
+pub struct SessionPlayer {
+    path: PathBuf,
+    started_at: u64,
+    duration_ms: u64,
+    samples: usize,
+    stop: Arc<AtomicBool>,
+    control: Arc<Mutex<PlayerControl>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SessionPlayer {
+    /// Start playing `samples` at `speed`, calling `on_sample` for each one
+    /// at its (scaled) original offset and `on_end` whenever the last one
+    /// was played
+    ///
+    /// The player stays at the end after the last sample, so it can still
+    /// seek back until it's stopped.
+    pub fn start<S, E>(
+        path: PathBuf,
+        samples: Vec<SystemStats>,
+        speed: f64,
+        mut on_sample: S,
+        mut on_end: E,
+    ) -> Result<Self, String>
+    where
+        S: FnMut(&SystemStats) + Send + 'static,
+        E: FnMut() + Send + 'static,
+    {
+        validate_speed(speed)?;
+        let first = samples
+            .first()
+            .ok_or_else(|| "Session has no samples".to_string())?
+            .timestamp;
+        let offsets: Vec<u64> = samples
+            .iter()
+            .map(|s| s.timestamp.saturating_sub(first))
+            .collect();
+        let duration_ms = offsets.last().copied().unwrap_or(0);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let control = Arc::new(Mutex::new(PlayerControl {
+            speed,
+            seek: None,
+            position_ms: 0,
+            finished: false,
+        }));
+        let sample_count = samples.len();
+
+        let thread_stop = Arc::clone(&stop);
+        let thread_control = Arc::clone(&control);
+        let thread = thread::spawn(move || {
+            let mut index = 0;
+            let mut anchor = Instant::now();
+            let mut anchor_position = 0u64;
+            let mut speed = speed;
+
+            while !thread_stop.load(Ordering::SeqCst) {
+                let (position, reached_end) = {
+                    let Ok(mut control) = thread_control.lock() else {
+                        return;
+                    };
+                    let elapsed = anchor.elapsed().as_secs_f64() * 1000.0 * speed;
+                    let mut position = anchor_position + elapsed as u64;
+
+                    // Re-anchor the clock on seeks and speed changes
+                    if let Some(target) = control.seek.take() {
+                        position = target;
+                        index = offsets.partition_point(|&o| o < position);
+                        control.finished = false;
+                        anchor = Instant::now();
+                        anchor_position = position;
+                    } else if control.speed != speed {
+                        anchor = Instant::now();
+                        anchor_position = position;
+                    }
+                    speed = control.speed;
+                    control.position_ms = position.min(duration_ms);
+
+                    // Stay at the end (e.g. to seek back) until stopped
+                    let reached_end = index >= offsets.len() && !control.finished;
+                    if reached_end {
+                        control.finished = true;
+                    }
+                    (position, reached_end)
+                };
+
+                if reached_end {
+                    on_end();
+                }
+                match offsets.get(index) {
+                    Some(&offset) if offset <= position => {
+                        on_sample(&samples[index]);
+                        index += 1;
+                    }
+                    Some(&offset) => {
+                        let remaining = (offset - position) as f64 / speed / 1000.0;
+                        thread::sleep(Duration::from_secs_f64(remaining).min(MAX_SLEEP));
+                    }
+                    None => thread::sleep(MAX_SLEEP),
+                }
+            }
+        });
+
+        Ok(Self {
+            path,
+            started_at: first,
+            duration_ms,
+            samples: sample_count,
+            stop,
+            control,
+            thread: Some(thread),
+        })
+    }
+
+    /// Jump to `position_ms` from the session start (clamped to its end)
+    /// Also resumes a playback that reached the end
+    pub fn seek(&mut self, position_ms: u64) -> Result<(), String> {
+        let mut control = self
+            .control
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        control.seek = Some(position_ms.min(self.duration_ms));
+        control.position_ms = position_ms.min(self.duration_ms);
+        Ok(())
+    }
+
+    /// Change the playback speed (1.0 = real time)
+    pub fn set_speed(&mut self, speed: f64) -> Result<(), String> {
+        validate_speed(speed)?;
+        let mut control = self
+            .control
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        control.speed = speed;
+        Ok(())
+    }
+
+    pub fn status(&self) -> PlaybackStatus {
+        let (position_ms, speed, finished) = self
+            .control
+            .lock()
+            .map(|c| (c.position_ms, c.speed, c.finished))
+            .unwrap_or((0, 1.0, true));
+
+        PlaybackStatus {
+            path: self.path.to_string_lossy().into_owned(),
+            position_ms,
+            duration_ms: self.duration_ms,
+            speed,
+            samples: self.samples,
+            started_at: self.started_at,
+            finished,
+        }
+    }
+
+    /// Stop playback and wait for its thread
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for SessionPlayer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn validate_speed(speed: f64) -> Result<(), String> {
+    if speed.is_finite() && speed > 0.0 {
+        Ok(())
+    } else {
+        Err("Playback speed must be a positive number".to_string())
+    }
+}
+
+/// Session recording and playback shared by the stats emitter and commands
+#[derive(Default)]
+pub struct SessionManager {
+    recorder: Mutex<Option<SessionRecorder>>,
+    player: Mutex<Option<SessionPlayer>>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start writing emitted stats to `path`, replacing a running recording
+    pub fn start_recording(&self, path: PathBuf) -> Result<PathBuf, String> {
+        let recorder = SessionRecorder::create(path)?;
+        let path = recorder.path().to_path_buf();
+        let mut guard = self
+            .recorder
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        if let Some(previous) = guard.replace(recorder) {
+            previous.finish()?;
+        }
+        println!("[Session] Recording to {:?}", path);
+        Ok(path)
+    }
+
+    /// Stop recording, returning the file path and number of samples
+    pub fn stop_recording(&self) -> Result<Option<(PathBuf, usize)>, String> {
+        let recorder = self
+            .recorder
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?
+            .take();
+        recorder.map(SessionRecorder::finish).transpose()
+    }
+
+    /// Write a live sample to the running recording, if any
+    /// A failing recording is stopped so the error isn't repeated every second
+    pub fn record(&self, stats: &SystemStats) {
+        let Ok(mut guard) = self.recorder.lock() else {
+            return;
+        };
+        if let Some(recorder) = guard.as_mut() {
+            if let Err(e) = recorder.record(stats) {
+                eprintln!("[Session] {}, stopping recording", e);
+                if let Some(recorder) = guard.take() {
+                    let _ = recorder.finish();
+                }
+            }
+        }
+    }
+
+    /// Load `path` and play it, replacing a running playback
+    pub fn start_playback<S, E>(
+        &self,
+        path: &Path,
+        speed: f64,
+        on_sample: S,
+        on_end: E,
+    ) -> Result<PlaybackStatus, String>
+    where
+        S: FnMut(&SystemStats) + Send + 'static,
+        E: FnMut() + Send + 'static,
+    {
+        let samples = load_session(path)?;
+        let player = SessionPlayer::start(path.to_path_buf(), samples, speed, on_sample, on_end)?;
+        let status = player.status();
+
+        let mut guard = self
+            .player
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        *guard = Some(player);
+        println!(
+            "[Session] Playing {:?} ({} samples, {}x)",
+            path, status.samples, speed
+        );
+        Ok(status)
+    }
+
+    /// Seek and/or change the speed of the running playback
+    pub fn control_playback(
+        &self,
+        position_ms: Option<u64>,
+        speed: Option<f64>,
+    ) -> Result<PlaybackStatus, String> {
+        let mut guard = self
+            .player
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let player = guard
+            .as_mut()
+            .ok_or_else(|| "No session is playing".to_string())?;
+        if let Some(speed) = speed {
+            player.set_speed(speed)?;
+        }
+        if let Some(position_ms) = position_ms {
+            player.seek(position_ms)?;
+        }
+        Ok(player.status())
+    }
+
+    /// Stop playback and return to live data
+    pub fn stop_playback(&self) {
+        let player = self.player.lock().ok().and_then(|mut guard| guard.take());
+        if let Some(mut player) = player {
+            player.stop();
+            println!("[Session] Playback stopped");
+        }
+    }
+
+    /// Status of the current playback (None when showing live data)
+    pub fn playback_status(&self) -> Option<PlaybackStatus> {
+        self.player.lock().ok()?.as_ref().map(|p| p.status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProcessInfo;
+    use std::sync::mpsc;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "pulse-session-{}-{}.pulse-session",
+            name,
+            std::process::id()
+        ))
+    }
+
+    fn sample(timestamp: u64) -> SystemStats {
+        SystemStats {
+            timestamp,
+            processes: vec![ProcessInfo {
+                pid: 42,
+                name: "game.exe".to_string(),
+                cpu_usage: 80.0,
+                memory: 1 << 30,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let path = temp_path("roundtrip");
+        let mut recorder = SessionRecorder::create(path.clone()).unwrap();
+        recorder.record(&sample(1000)).unwrap();
+        recorder.record(&sample(2000)).unwrap();
+        assert_eq!(recorder.finish().unwrap(), (path.clone(), 2));
+
+        let samples = load_session(&path).unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1].timestamp, 2000);
+        assert_eq!(samples[0].processes[0].name, "game.exe");
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_load_rejects_other_files() {
+        let path = temp_path("garbage");
+        fs::write(&path, "not gzip").unwrap();
+        assert!(load_session(&path).is_err());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_playback_pace_and_end() {
+        // 3 s of samples at 16x should take ~190 ms
+        let samples = vec![sample(0), sample(1500), sample(3000)];
+        let (tx, rx) = mpsc::channel();
+        let (end_tx, end_rx) = mpsc::channel();
+
+        let started = Instant::now();
+        let mut player = SessionPlayer::start(
+            PathBuf::from("test"),
+            samples,
+            16.0,
+            move |s| tx.send(s.timestamp).unwrap(),
+            move || {
+                let _ = end_tx.send(());
+            },
+        )
+        .unwrap();
+        end_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(180));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![0, 1500, 3000]);
+        let status = player.status();
+        assert!(status.finished);
+        assert_eq!(status.position_ms, 3000);
+
+        // Seeking back after the end plays again
+        player.seek(1500).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 1500);
+    }
+
+    #[test]
+    fn test_playback_seek() {
+        let samples: Vec<SystemStats> = (0..10).map(|i| sample(i * 60_000)).collect();
+        let (tx, rx) = mpsc::channel();
+        let mut player = SessionPlayer::start(
+            PathBuf::from("test"),
+            samples,
+            1.0,
+            move |s| tx.send(s.timestamp).unwrap(),
+            || {},
+        )
+        .unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 0);
+
+        // Jump to the 7th minute, the next sample is the one at 7 min
+        player.seek(7 * 60_000).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 7 * 60_000);
+        assert!(player.status().position_ms >= 7 * 60_000);
+
+        assert!(player.set_speed(0.0).is_err());
+        player.set_speed(16.0).unwrap();
+        assert_eq!(player.status().speed, 16.0);
+        player.stop();
+    }
+
+    #[test]
+    fn test_manager_records_and_plays() {
+        let manager = SessionManager::new();
+        let path = temp_path("manager");
+        manager.start_recording(path.clone()).unwrap();
+        manager.record(&sample(0));
+        manager.record(&sample(100));
+        assert_eq!(manager.stop_recording().unwrap(), Some((path.clone(), 2)));
+        assert_eq!(manager.stop_recording().unwrap(), None);
+
+        let (end_tx, end_rx) = mpsc::channel();
+        let status = manager
+            .start_playback(
+                &path,
+                4.0,
+                |_| {},
+                move || {
+                    let _ = end_tx.send(());
+                },
+            )
+            .unwrap();
+        assert_eq!(status.samples, 2);
+        end_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(manager.playback_status().unwrap().finished);
+
+        manager.stop_playback();
+        assert!(manager.playback_status().is_none());
+        assert!(manager.control_playback(Some(0), None).is_err());
+        let _ = fs::remove_file(path);
+    }
+}
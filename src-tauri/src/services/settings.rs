@@ -0,0 +1,260 @@
+//! Settings store
+//!
+//! Loads `Settings` from a JSON file at startup and writes changes back.
+//! A missing or unreadable file falls back to the defaults.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use super::alert::validate_rules;
+use super::watchdog::validate_watchdogs;
+use crate::models::Settings;
+
+/// Lowest accepted staleness threshold - the sidecar reports once per second
+const MIN_STALE_THRESHOLD_MS: u64 = 1000;
+
+/// Longest accepted gap between two notifications for the same rule (one day)
+const MAX_NOTIFY_INTERVAL_SECS: u64 = 24 * 3600;
+
+/// Thread-safe settings container backed by a JSON file
+pub struct SettingsStore {
+    path: Option<PathBuf>,
+    settings: RwLock<Settings>,
+}
+
+impl SettingsStore {
+    /// Load settings from `path`, using defaults if it doesn't exist or is invalid
+    pub fn load(path: PathBuf) -> Self {
+        let settings = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("[Settings] Invalid settings file {:?}: {}", path, e);
+                Settings::default()
+            }),
+            Err(_) => Settings::default(),
+        };
+
+        Self {
+            path: Some(path),
+            settings: RwLock::new(settings),
+        }
+    }
+
+    /// Settings that are never written to disk
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            settings: RwLock::new(Settings::default()),
+        }
+    }
+
+    /// Get a copy of the current settings
+    pub fn get(&self) -> Settings {
+        self.settings.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Validate, store and persist new settings
+    pub fn update(&self, settings: Settings) -> Result<Settings, String> {
+        validate(&settings)?;
+
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create settings dir: {}", e))?;
+            }
+            let json = serde_json::to_string_pretty(&settings)
+                .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+            fs::write(path, json).map_err(|e| format!("Failed to save settings: {}", e))?;
+        }
+
+        let mut guard = self
+            .settings
+            .write()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        *guard = settings.clone();
+        Ok(settings)
+    }
+}
+
+fn validate(settings: &Settings) -> Result<(), String> {
+    if settings.stale_threshold_ms < MIN_STALE_THRESHOLD_MS {
+        return Err(format!(
+            "stale_threshold_ms must be at least {}",
+            MIN_STALE_THRESHOLD_MS
+        ));
+    }
+    if let Some(tj_max) = settings.tj_max_override {
+        if !(50.0..=150.0).contains(&tj_max) {
+            return Err("tj_max_override must be between 50 and 150 °C".to_string());
+        }
+    }
+    if settings.hardware_classes.is_empty() {
+        return Err("hardware_classes must enable at least one class".to_string());
+    }
+    if let Some(endpoint) = &settings.collector_endpoint {
+        if endpoint.trim().is_empty() {
+            return Err("collector_endpoint must not be empty".to_string());
+        }
+    }
+    let retention = &settings.retention;
+    if retention.raw_hours == 0 || retention.minute_days == 0 || retention.hour_days == 0 {
+        return Err("retention periods must be at least 1".to_string());
+    }
+    if settings.notifications.max_per_hour == 0 {
+        return Err("notifications.max_per_hour must be at least 1".to_string());
+    }
+    if settings.notifications.min_interval_secs > MAX_NOTIFY_INTERVAL_SECS {
+        return Err(format!(
+            "notifications.min_interval_secs must be at most {}",
+            MAX_NOTIFY_INTERVAL_SECS
+        ));
+    }
+    let anomaly = &settings.anomaly;
+    if !(anomaly.sensitivity.is_finite() && anomaly.sensitivity > 0.0) {
+        return Err("anomaly.sensitivity must be greater than 0".to_string());
+    }
+    if anomaly.baseline_minutes == 0 {
+        return Err("anomaly.baseline_minutes must be at least 1".to_string());
+    }
+    let creep = anomaly.memory_creep_percent_per_hour;
+    if !(creep.is_finite() && creep > 0.0) {
+        return Err("anomaly.memory_creep_percent_per_hour must be greater than 0".to_string());
+    }
+    let leaks = &settings.leaks;
+    if !(leaks.min_growth_mb_per_hour.is_finite() && leaks.min_growth_mb_per_hour > 0.0) {
+        return Err("leaks.min_growth_mb_per_hour must be greater than 0".to_string());
+    }
+    if leaks.min_duration_minutes == 0 {
+        return Err("leaks.min_duration_minutes must be at least 1".to_string());
+    }
+    validate_rules(&settings.alerts)?;
+    validate_watchdogs(&settings.watchdogs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HardwareClass;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("pulse-settings-{}-{}", name, std::process::id()))
+            .join("settings.json")
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let store = SettingsStore::load(temp_path("missing"));
+        assert_eq!(store.get(), Settings::default());
+    }
+
+    #[test]
+    fn test_update_persists() {
+        let path = temp_path("persist");
+        let store = SettingsStore::load(path.clone());
+        let mut settings = store.get();
+        settings.stale_threshold_ms = 8000;
+        store.update(settings.clone()).unwrap();
+
+        let reloaded = SettingsStore::load(path.clone());
+        assert_eq!(reloaded.get(), settings);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_rejects_invalid_threshold() {
+        let store = SettingsStore::in_memory();
+        let settings = Settings {
+            stale_threshold_ms: 10,
+            ..Default::default()
+        };
+        assert!(store.update(settings).is_err());
+
+        let settings = Settings {
+            tj_max_override: Some(-5.0),
+            ..Default::default()
+        };
+        assert!(store.update(settings).is_err());
+        assert_eq!(store.get(), Settings::default());
+    }
+
+    #[test]
+    fn test_hardware_classes() {
+        let settings: Settings =
+            serde_json::from_str(r#"{"hardware_classes":["cpu","storage"]}"#).unwrap();
+        assert_eq!(
+            settings.hardware_classes,
+            vec![HardwareClass::Cpu, HardwareClass::Storage]
+        );
+        assert_eq!(
+            HardwareClass::join(&settings.hardware_classes),
+            "cpu,storage"
+        );
+
+        let store = SettingsStore::in_memory();
+        let settings = Settings {
+            hardware_classes: Vec::new(),
+            ..Default::default()
+        };
+        assert!(store.update(settings).is_err());
+    }
+
+    #[test]
+    fn test_rejects_zero_retention() {
+        let store = SettingsStore::in_memory();
+        let mut settings = Settings::default();
+        settings.retention.minute_days = 0;
+        assert!(store.update(settings).is_err());
+
+        let settings: Settings = serde_json::from_str(r#"{"retention":{"raw_hours":48}}"#).unwrap();
+        assert_eq!(settings.retention.raw_hours, 48);
+        assert_eq!(settings.retention.hour_days, 365);
+    }
+
+    #[test]
+    fn test_rejects_unbounded_notification_interval() {
+        let store = SettingsStore::in_memory();
+        let mut settings = Settings::default();
+        settings.notifications.min_interval_secs = u64::MAX;
+        assert!(store.update(settings).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_detector_settings() {
+        let store = SettingsStore::in_memory();
+        let mut settings = Settings::default();
+        settings.anomaly.sensitivity = 0.0;
+        assert!(store.update(settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.anomaly.memory_creep_percent_per_hour = f64::NAN;
+        assert!(store.update(settings).is_err());
+
+        let mut settings = Settings::default();
+        settings.leaks.min_duration_minutes = 0;
+        assert!(store.update(settings).is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_alert_rule() {
+        let store = SettingsStore::in_memory();
+        let mut settings = Settings::default();
+        settings.alerts[0].condition = "cpu.temperature above 90".to_string();
+        let err = store.update(settings).unwrap_err();
+        assert!(err.contains("cpu.temperature above 90"));
+
+        let settings: Settings = serde_json::from_str(
+            r#"{"alerts":[{"id":"gpu-power","condition":"gpu.power > 300 for 5s"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(settings.alerts.len(), 1);
+        assert!(settings.alerts[0].enabled);
+        assert!(store.update(settings).is_ok());
+    }
+
+    #[test]
+    fn test_partial_file_keeps_defaults() {
+        let settings: Settings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+}
@@ -4,15 +4,19 @@
 //! CPU/GPU temperature data via LibreHardwareMonitor.
 
 use serde::{Deserialize, Serialize};
-use std::io::{BufRead, BufReader};
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, RwLock};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::Instant;
-use tauri::Manager;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
 
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
+use super::collector::{CollectorClient, CollectorEvent};
+use super::integrity;
+use super::log_buffer::{LogBuffer, LogLevel};
+use super::process::{ProcessRun, SupervisedProcess};
+use super::sidecar_recording::{self, ReplayHandle, StreamRecorder};
+use crate::models::{HardwareClass, Sensor, Settings};
 
 /// Data from sidecar matching the JSON output format
 #[derive(Debug, Clone, Deserialize)]
@@ -20,8 +24,12 @@ pub struct SidecarData {
     pub cpu: Option<SidecarCpuData>,
     #[serde(default)]
     pub gpu: Vec<SidecarGpuData>,
+    /// Every sensor of every enabled hardware item
+    #[serde(default)]
+    pub sensors: Vec<Sensor>,
     pub timestamp: i64,
-    pub error: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_error")]
+    pub error: Option<SidecarError>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -31,7 +39,8 @@ pub struct SidecarCpuData {
     pub package_temperature: Option<f32>,
     #[serde(default)]
     pub core_temperatures: Vec<Option<f32>>,
-    pub max_temperature: Option<f32>,
+    pub max_temperature: Option<f32>, // Hottest core ("Core Max"), not TjMax
+    pub tj_max: Option<f32>,
     pub power: Option<f32>,
     #[serde(default)]
     pub core_powers: Vec<Option<f32>>,
@@ -50,6 +59,118 @@ pub struct SidecarGpuData {
     pub load: Option<f32>,
 }
 
+/// Machine-readable sidecar error codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarErrorCode {
+    /// Sidecar needs administrator privileges to read sensors
+    RequiresAdmin,
+    /// Sidecar executable couldn't be located
+    BinaryNotFound,
+    /// Sidecar executable exists but couldn't be started
+    SpawnFailed,
+    /// A single sensor read failed, the sidecar keeps running
+    CollectionFailed,
+    /// Sidecar kept crashing and auto-restart gave up
+    Crashed,
+    /// Sidecar hit an unrecoverable error and exited
+    Fatal,
+    /// Collector endpoint isn't reachable (yet), the client keeps retrying
+    CollectorUnavailable,
+    /// Process behind the collector endpoint failed authentication
+    CollectorRejected,
+    /// Sidecar binary doesn't match the digest embedded at build time
+    IntegrityCheckFailed,
+    /// Code not known to this version (or a legacy string error)
+    #[serde(other)]
+    Unknown,
+}
+
+impl SidecarErrorCode {
+    /// Whether retrying (e.g. restarting the sidecar) can fix this error
+    pub fn is_retryable(self) -> bool {
+        !matches!(
+            self,
+            SidecarErrorCode::RequiresAdmin
+                | SidecarErrorCode::BinaryNotFound
+                | SidecarErrorCode::Crashed
+                | SidecarErrorCode::CollectorRejected
+                | SidecarErrorCode::IntegrityCheckFailed
+        )
+    }
+}
+
+/// Structured sidecar error, as sent in the `error` field of the JSON output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SidecarError {
+    pub code: SidecarErrorCode,
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl SidecarError {
+    /// Create an error with the default retry policy for `code`
+    pub fn new(code: SidecarErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: code.is_retryable(),
+        }
+    }
+
+    /// Classify a failed spawn by its OS error kind
+    pub fn from_spawn_error(err: &std::io::Error) -> Self {
+        let code = match err.kind() {
+            std::io::ErrorKind::NotFound => SidecarErrorCode::BinaryNotFound,
+            std::io::ErrorKind::PermissionDenied => SidecarErrorCode::RequiresAdmin,
+            // ERROR_ELEVATION_REQUIRED - the sidecar manifest asks for admin
+            _ if cfg!(windows) && err.raw_os_error() == Some(740) => {
+                SidecarErrorCode::RequiresAdmin
+            }
+            _ => SidecarErrorCode::SpawnFailed,
+        };
+        Self::new(code, format!("Failed to spawn sidecar: {}", err))
+    }
+}
+
+impl std::fmt::Display for SidecarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Accept both the structured error object and the legacy plain string
+fn deserialize_error<'de, D>(deserializer: D) -> Result<Option<SidecarError>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum RawError {
+        Structured {
+            code: SidecarErrorCode,
+            message: String,
+            retryable: Option<bool>,
+        },
+        Legacy(String),
+    }
+
+    Ok(
+        Option::<RawError>::deserialize(deserializer)?.map(|raw| match raw {
+            RawError::Structured {
+                code,
+                message,
+                retryable,
+            } => SidecarError {
+                code,
+                message,
+                retryable: retryable.unwrap_or_else(|| code.is_retryable()),
+            },
+            RawError::Legacy(message) => SidecarError::new(SidecarErrorCode::Unknown, message),
+        }),
+    )
+}
+
 /// Sidecar status
 #[derive(Debug, Clone, PartialEq)]
 pub enum SidecarStatus {
@@ -60,7 +181,7 @@ pub enum SidecarStatus {
     /// Stopped (crashed or terminated)
     Stopped,
     /// Error occurred (e.g., missing admin rights)
-    Error(String),
+    Error(SidecarError),
 }
 
 /// Serializable sidecar status for frontend events
@@ -79,6 +200,8 @@ pub enum SidecarStatusInfo {
     RequiresAdmin,
     #[serde(rename = "binary_not_found")]
     BinaryNotFound,
+    #[serde(rename = "integrity_check_failed")]
+    IntegrityCheckFailed(String),
 }
 
 impl From<&SidecarStatus> for SidecarStatusInfo {
@@ -87,35 +210,56 @@ impl From<&SidecarStatus> for SidecarStatusInfo {
             SidecarStatus::NotStarted => SidecarStatusInfo::NotStarted,
             SidecarStatus::Running => SidecarStatusInfo::Running,
             SidecarStatus::Stopped => SidecarStatusInfo::Stopped,
-            SidecarStatus::Error(msg) => {
-                // Detect specific error types
-                if msg.to_lowercase().contains("admin")
-                    || msg.to_lowercase().contains("access denied")
-                    || msg.to_lowercase().contains("permission")
-                {
-                    SidecarStatusInfo::RequiresAdmin
-                } else if msg.contains("not found") || msg.contains("binary") {
-                    SidecarStatusInfo::BinaryNotFound
-                } else {
-                    SidecarStatusInfo::Error(msg.clone())
+            SidecarStatus::Error(err) => match err.code {
+                SidecarErrorCode::RequiresAdmin => SidecarStatusInfo::RequiresAdmin,
+                SidecarErrorCode::BinaryNotFound => SidecarStatusInfo::BinaryNotFound,
+                SidecarErrorCode::IntegrityCheckFailed => {
+                    SidecarStatusInfo::IntegrityCheckFailed(err.message.clone())
                 }
-            }
+                _ => SidecarStatusInfo::Error(err.message.clone()),
+            },
         }
     }
 }
 
+/// Payload for sidecar status event and lifecycle commands
+#[derive(Debug, Clone, Serialize)]
+pub struct SidecarStatusPayload {
+    #[serde(flatten)]
+    pub status: SidecarStatusInfo,
+    pub restart_count: u32,
+    pub can_restart: bool,
+    pub error: Option<SidecarError>, // Set while status is an error
+    pub pid: Option<u32>,
+    pub runs: Vec<ProcessRun>,      // Spawn/exit history, oldest first
+    pub recording: Option<PathBuf>, // File raw output is recorded to
+    pub replaying: bool,            // Data comes from a recording, not LHM
+    pub collector: Option<String>,  // Endpoint when data comes from a collector
+}
+
 /// Thread-safe state container for sidecar data
 pub struct SidecarState {
     data: RwLock<Option<SidecarData>>,
     status: RwLock<SidecarStatus>,
     restart_count: RwLock<u32>,
     last_data_time: RwLock<Option<Instant>>,
+    /// Sidecar stderr and supervisor messages
+    logs: LogBuffer,
+    /// Incremented on every spawn so reader threads of old processes
+    /// can't overwrite the state of the current one
+    generation: AtomicU64,
+    /// Active recording of the raw sidecar output
+    recorder: Mutex<Option<StreamRecorder>>,
 }
 
 /// Maximum number of restart attempts before giving up
 const MAX_RESTART_ATTEMPTS: u32 = 3;
+/// Pause before each automatic restart
+const RESTART_DELAY: Duration = Duration::from_secs(2);
 /// How long to wait before considering sidecar stalled (no data received)
 const STALL_TIMEOUT_SECS: u64 = 10;
+/// Number of sidecar log lines kept in memory
+const LOG_CAPACITY: usize = 500;
 
 impl SidecarState {
     pub fn new() -> Self {
@@ -124,9 +268,77 @@ impl SidecarState {
             status: RwLock::new(SidecarStatus::NotStarted),
             restart_count: RwLock::new(0),
             last_data_time: RwLock::new(None),
+            logs: LogBuffer::new(LOG_CAPACITY),
+            generation: AtomicU64::new(0),
+            recorder: Mutex::new(None),
+        }
+    }
+
+    /// Start a new process generation and return its id
+    fn begin_generation(&self) -> u64 {
+        self.generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Check if the given generation is still the current one
+    fn is_current_generation(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+
+    /// Start recording raw sidecar lines to `path`
+    /// Replaces a recording that is already running
+    pub fn start_recording(&self, path: PathBuf) -> Result<PathBuf, String> {
+        let recorder = StreamRecorder::create(path)?;
+        let path = recorder.path().to_path_buf();
+        let mut guard = self
+            .recorder
+            .lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        *guard = Some(recorder);
+
+        self.logs.push_supervisor(
+            LogLevel::Info,
+            &format!("Recording sidecar output to {:?}", path),
+        );
+        Ok(path)
+    }
+
+    /// Stop recording and return the path of the finished recording
+    pub fn stop_recording(&self) -> Option<PathBuf> {
+        let recorder = self.recorder.lock().ok()?.take()?;
+        let path = recorder.path().to_path_buf();
+        self.logs
+            .push_supervisor(LogLevel::Info, &format!("Recording saved to {:?}", path));
+        Some(path)
+    }
+
+    /// Path of the active recording
+    pub fn recording_path(&self) -> Option<PathBuf> {
+        self.recorder
+            .lock()
+            .ok()
+            .and_then(|guard| guard.as_ref().map(|r| r.path().to_path_buf()))
+    }
+
+    /// Append a raw line to the active recording, if any
+    fn record_line(&self, line: &str) {
+        let Ok(mut guard) = self.recorder.lock() else {
+            return;
+        };
+        if let Some(recorder) = guard.as_mut() {
+            if let Err(e) = recorder.record(line) {
+                // Don't keep failing on every line (e.g. disk full)
+                eprintln!("[Sidecar] {}", e);
+                *guard = None;
+                self.logs.push_supervisor(LogLevel::Error, &e);
+            }
         }
     }
 
+    /// Log buffer with sidecar diagnostics
+    pub fn logs(&self) -> &LogBuffer {
+        &self.logs
+    }
+
     /// Get the latest sidecar data
     pub fn get_data(&self) -> Option<SidecarData> {
         self.data.read().ok().and_then(|d| d.clone())
@@ -149,6 +361,23 @@ impl SidecarState {
         }
     }
 
+    /// Mark the process as ended
+    /// A non-retryable error stays visible so the watcher won't restart into it
+    fn mark_ended(&self) {
+        match self.get_status() {
+            SidecarStatus::Error(ref err) if !err.retryable => {}
+            _ => self.set_status(SidecarStatus::Stopped),
+        }
+    }
+
+    /// Get the current error, if the status is an error
+    pub fn get_error(&self) -> Option<SidecarError> {
+        match self.get_status() {
+            SidecarStatus::Error(err) => Some(err),
+            _ => None,
+        }
+    }
+
     /// Get current status
     pub fn get_status(&self) -> SidecarStatus {
         self.status
@@ -197,6 +426,14 @@ impl SidecarState {
         self.get_restart_count() < MAX_RESTART_ATTEMPTS
     }
 
+    /// How long ago the latest data was received
+    pub fn data_age(&self) -> Option<Duration> {
+        self.last_data_time
+            .read()
+            .ok()
+            .and_then(|guard| guard.map(|t| t.elapsed()))
+    }
+
     /// Check if sidecar is stalled (not receiving data)
     #[allow(dead_code)]
     pub fn is_stalled(&self) -> bool {
@@ -208,6 +445,11 @@ impl SidecarState {
         false
     }
 
+    /// Get the full sensor tree from the latest sidecar data
+    pub fn get_sensors(&self) -> Vec<Sensor> {
+        self.get_data().map(|d| d.sensors).unwrap_or_default()
+    }
+
     /// Get CPU temperature from sidecar data
     pub fn get_cpu_temperature(&self) -> Option<f32> {
         self.get_data()
@@ -236,101 +478,470 @@ impl Default for SidecarState {
 }
 
 /// Sidecar manager handles spawning and communication with lhm-sidecar
+/// Can also read from an already running collector or replay a recording
 pub struct SidecarManager {
     state: Arc<SidecarState>,
-    child: Option<Child>,
+    /// Err if the sidecar binary couldn't be located
+    process: Result<SupervisedProcess, SidecarError>,
+    replay: Option<ReplayHandle>,
+    /// Collector socket/pipe to connect to instead of spawning the process
+    endpoint: Option<String>,
+    collector: Option<CollectorClient>,
+    /// Private directory for the verified copy of the binary (Unix)
+    staging_dir: PathBuf,
+    /// Verified binary the current process was started from
+    binary: Option<integrity::VerifiedBinary>,
+    /// Digest a test fixture is checked against instead of the embedded one
+    #[cfg(test)]
+    digest: Option<String>,
+    /// Sidecar started through the UAC prompt, read over its pipe
+    #[cfg(windows)]
+    elevated: Option<super::elevated::ElevatedProcess>,
 }
 
 impl SidecarManager {
-    pub fn new() -> Self {
+    pub fn new(path: Result<PathBuf, SidecarError>, state: Arc<SidecarState>) -> Self {
+        let args = sidecar_args(&Settings::default().hardware_classes);
         Self {
-            state: Arc::new(SidecarState::new()),
-            child: None,
+            state,
+            process: path.map(|p| SupervisedProcess::new(p, args)),
+            replay: None,
+            endpoint: None,
+            collector: None,
+            staging_dir: std::env::temp_dir().join(format!("pulse-sidecar-{}", std::process::id())),
+            binary: None,
+            #[cfg(test)]
+            digest: None,
+            #[cfg(windows)]
+            elevated: None,
         }
     }
 
-    /// Get shared state handle
-    pub fn state(&self) -> Arc<SidecarState> {
-        Arc::clone(&self.state)
+    /// Stage verified binaries in `dir` - it's created owner-only and
+    /// must not be writable by anyone else
+    pub fn set_staging_dir(&mut self, dir: PathBuf) {
+        self.staging_dir = dir;
     }
 
-    /// Spawn sidecar process from path
-    pub fn spawn_process(&mut self, path: &std::path::Path) -> Result<(), String> {
-        println!("[Sidecar] Starting: {:?}", path);
+    /// Use a collector endpoint instead of spawning the process
+    /// Takes effect on the next start
+    pub fn set_endpoint(&mut self, endpoint: Option<String>) {
+        self.endpoint = endpoint;
+    }
 
-        let mut child = Command::new(path)
-            .args(["--interval", "1000"]) // 1 second updates
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .creation_flags(0x08000000) // CREATE_NO_WINDOW on Windows
-            .spawn()
-            .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+    /// Choose the hardware classes the sidecar reads
+    /// A running sidecar is told over stdin, so no restart is needed
+    pub fn set_hardware(&mut self, classes: &[HardwareClass]) {
+        let Ok(process) = self.process.as_mut() else {
+            return;
+        };
+        process.set_args(sidecar_args(classes));
+
+        // No stdin across the elevation boundary - restarting would prompt again
+        #[cfg(windows)]
+        if let Some(elevated) = &self.elevated {
+            self.state.logs().push_supervisor(
+                LogLevel::Info,
+                &format!(
+                    "Elevated sidecar (pid {}) reads the new hardware classes on its next start",
+                    elevated.pid()
+                ),
+            );
+            return;
+        }
 
-        // Get stdout handle
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| "Failed to capture stdout".to_string())?;
+        if process.pid().is_some() {
+            let command = format!("hardware {}", HardwareClass::join(classes));
+            match process.write_line(&command) {
+                Ok(()) => self
+                    .state
+                    .logs()
+                    .push_supervisor(LogLevel::Info, &format!("Sent \"{}\"", command)),
+                Err(e) => self.state.logs().push_supervisor(
+                    LogLevel::Warn,
+                    &format!(
+                        "Failed to send \"{}\", applies on next start: {}",
+                        command, e
+                    ),
+                ),
+            }
+        }
+    }
+
+    /// Collector endpoint, if one is configured
+    pub fn endpoint(&self) -> Option<&str> {
+        self.endpoint.as_deref()
+    }
 
-        self.child = Some(child);
+    /// Start the configured source - the collector if an endpoint is set,
+    /// otherwise the sidecar process
+    /// An app that isn't elevated goes through `prepare_elevated` instead
+    pub fn start(&mut self) -> Result<(), SidecarError> {
+        match self.endpoint.clone() {
+            Some(endpoint) => {
+                self.connect_collector(&endpoint);
+                Ok(())
+            }
+            None => self.spawn_process(),
+        }
+    }
+
+    /// Check `program` and pin the bytes that will run
+    fn verify(
+        &self,
+        program: &Path,
+    ) -> Result<integrity::VerifiedBinary, integrity::IntegrityError> {
+        #[cfg(test)]
+        if let Some(digest) = &self.digest {
+            return integrity::verify_sidecar_with(program, &self.staging_dir, Some(digest));
+        }
+        integrity::verify_sidecar(program, &self.staging_dir)
+    }
+
+    /// Whether starting needs the sidecar launched through the UAC prompt
+    #[cfg(windows)]
+    pub fn needs_elevation(&self) -> bool {
+        self.endpoint.is_none() && !super::elevated::is_elevated()
+    }
+
+    /// Spawn the sidecar process and start reading its output
+    /// Used for the initial start as well as for every restart
+    pub fn spawn_process(&mut self) -> Result<(), SidecarError> {
+        self.stop_replay();
+        self.stop_collector();
+        let program = self.process.as_ref().map_err(|e| e.clone())?.program();
+        println!("[Sidecar] Starting: {:?}", program);
+
+        // Never run a binary we didn't ship - it gets elevated rights
+        let binary = self
+            .verify(program)
+            .map_err(|e| integrity_error(&self.state, e))?;
+
+        // Record the exit of a previous run before starting a new one
+        let process = self.process.as_mut().map_err(|e| e.clone())?;
+        process.poll();
+
+        let generation = self.state.begin_generation();
+        let line_state = Arc::clone(&self.state);
+        let stderr_state = Arc::clone(&self.state);
+        let eof_state = Arc::clone(&self.state);
+
+        let pid = process
+            .start_as(
+                binary.path(),
+                move |line| {
+                    if line_state.is_current_generation(generation) {
+                        line_state.record_line(line);
+                        handle_line(&line_state, line);
+                    }
+                },
+                move |line| stderr_state.logs().push_stderr(line),
+                move || {
+                    // Process ended - only report it if it wasn't replaced meanwhile
+                    if eof_state.is_current_generation(generation) {
+                        println!("[Sidecar] Process ended");
+                        eof_state
+                            .logs()
+                            .push_supervisor(LogLevel::Warn, "Sidecar process ended");
+                        eof_state.mark_ended();
+                    }
+                },
+            )
+            .map_err(|e| {
+                let err = SidecarError::from_spawn_error(&e);
+                self.state
+                    .logs()
+                    .push_supervisor(LogLevel::Error, &err.message);
+                err
+            })?;
+
+        self.state
+            .logs()
+            .push_supervisor(LogLevel::Info, &format!("Sidecar started (pid {})", pid));
+        self.binary = Some(binary);
         self.state.set_status(SidecarStatus::Running);
+        Ok(())
+    }
+
+    /// Verify the binary and build the command line for an elevated start
+    /// The prompt itself is shown by the caller without holding the manager
+    #[cfg(windows)]
+    pub fn prepare_elevated(&mut self) -> Result<ElevatedLaunch, SidecarError> {
+        self.stop();
+        let process = self.process.as_ref().map_err(|e| e.clone())?;
+        println!("[Sidecar] Starting elevated: {:?}", process.program());
+
+        // A binary we didn't ship must never get admin rights
+        let binary = self
+            .verify(process.program())
+            .map_err(|e| integrity_error(&self.state, e))?;
+
+        // A fresh pipe per launch, so a sidecar from an earlier launch
+        // can't answer. It exits with the app or once its client disconnects
+        let generation = self.state.begin_generation();
+        let app_pid = std::process::id().to_string();
+        let pipe = format!("pulse-sidecar-{}-{}", app_pid, generation);
+        let mut args = process.args().to_vec();
+        args.extend(["--pipe".to_string(), pipe.clone()]);
+        args.extend(["--parent".to_string(), app_pid]);
+
+        // Keep the watcher away while the prompt is open
+        self.state.set_status(SidecarStatus::NotStarted);
+        Ok(ElevatedLaunch {
+            binary,
+            args,
+            pipe: format!(r"\\.\pipe\{}", pipe),
+            generation,
+        })
+    }
+
+    /// Take over the sidecar started for `launch` and read its pipe
+    /// A launch that was stopped or replaced while the prompt was open is
+    /// let go, connecting to it once makes it exit
+    #[cfg(windows)]
+    pub fn finish_elevated(
+        &mut self,
+        launch: ElevatedLaunch,
+        child: std::io::Result<super::elevated::ElevatedProcess>,
+    ) -> Result<(), SidecarError> {
+        let current = self.state.is_current_generation(launch.generation);
+        let child = child.map_err(|e| {
+            let err = if super::elevated::is_cancelled(&e) {
+                SidecarError::new(
+                    SidecarErrorCode::RequiresAdmin,
+                    "Administrator rights were declined, hardware sensors are unavailable"
+                        .to_string(),
+                )
+            } else {
+                SidecarError::from_spawn_error(&e)
+            };
+            eprintln!("[Sidecar] {}", err);
+            self.state
+                .logs()
+                .push_supervisor(LogLevel::Error, &err.message);
+            err
+        });
+
+        if !current {
+            if let Ok(child) = child {
+                self.state.logs().push_supervisor(
+                    LogLevel::Info,
+                    &format!(
+                        "Sidecar was stopped during the prompt, releasing pid {}",
+                        child.pid()
+                    ),
+                );
+                release_pipe(launch.pipe);
+            }
+            return Ok(());
+        }
+        let child = child?;
+
+        self.state.logs().push_supervisor(
+            LogLevel::Info,
+            &format!("Sidecar started elevated (pid {})", child.pid()),
+        );
+        self.elevated = Some(child);
+        self.binary = Some(launch.binary);
+        self.attach_collector(&launch.pipe);
+        Ok(())
+    }
+
+    /// Drop an elevated sidecar that exited, so the watcher restarts it
+    /// Returns its PID while it's still running
+    #[cfg(windows)]
+    fn poll_elevated(&mut self) -> Option<u32> {
+        let child = self.elevated.as_ref()?;
+        if !child.has_exited() {
+            return Some(child.pid());
+        }
+
+        println!("[Sidecar] Elevated process ended");
+        self.state
+            .logs()
+            .push_supervisor(LogLevel::Warn, "Elevated sidecar process ended");
+        self.elevated = None;
+        self.stop_collector();
+        self.state.mark_ended();
+        None
+    }
+
+    /// Connect to a running collector instead of spawning the process
+    /// The client reconnects on its own, so the watcher never sees `Stopped`
+    fn connect_collector(&mut self, endpoint: &str) {
+        self.stop();
+        self.attach_collector(endpoint);
+    }
+
+    /// Start reading `endpoint` without touching the current source
+    fn attach_collector(&mut self, endpoint: &str) {
+        println!("[Sidecar] Connecting to collector at {}", endpoint);
+
+        let generation = self.state.begin_generation();
+        let line_state = Arc::clone(&self.state);
+        let event_state = Arc::clone(&self.state);
+
+        // Waiting for the first line - the watcher leaves this status alone
+        // Set before the client starts, so it can't hide an early failure
+        self.state.set_status(SidecarStatus::NotStarted);
 
-        // Spawn thread to read output
-        let state = Arc::clone(&self.state);
-        thread::spawn(move || {
-            let reader = BufReader::new(stdout);
-
-            for line in reader.lines() {
-                match line {
-                    Ok(json_line) => {
-                        let json_str: &str = json_line.trim();
-                        if json_str.is_empty() {
-                            continue;
-                        }
-
-                        match serde_json::from_str::<SidecarData>(json_str) {
-                            Ok(data) => {
-                                // Log first successful read
-                                if state.get_status() != SidecarStatus::Running {
-                                    println!("[Sidecar] Receiving data successfully");
-                                    state.set_status(SidecarStatus::Running);
-                                }
-                                state.set_data(data);
-                            }
-                            Err(e) => {
-                                eprintln!(
-                                    "[Sidecar] JSON parse error: {} - Line: {}",
-                                    e, json_line
-                                );
-                            }
-                        }
+        self.collector = Some(CollectorClient::start(
+            endpoint,
+            move |line| {
+                if line_state.is_current_generation(generation) {
+                    line_state.record_line(line);
+                    handle_line(&line_state, line);
+                }
+            },
+            move |event| {
+                if !event_state.is_current_generation(generation) {
+                    return;
+                }
+                match event {
+                    CollectorEvent::Connected => {
+                        println!("[Sidecar] Connected to collector");
+                        event_state
+                            .logs()
+                            .push_supervisor(LogLevel::Info, "Connected to collector");
+                    }
+                    CollectorEvent::Disconnected(message) => {
+                        eprintln!("[Sidecar] {}", message);
+                        event_state.logs().push_supervisor(LogLevel::Warn, &message);
+                        event_state.set_status(SidecarStatus::Error(SidecarError::new(
+                            SidecarErrorCode::CollectorUnavailable,
+                            message,
+                        )));
                     }
-                    Err(e) => {
-                        eprintln!("[Sidecar] Read error: {}", e);
-                        break;
+                    CollectorEvent::Rejected(message) => {
+                        eprintln!("[Sidecar] {}", message);
+                        event_state
+                            .logs()
+                            .push_supervisor(LogLevel::Error, &message);
+                        event_state.set_status(SidecarStatus::Error(SidecarError::new(
+                            SidecarErrorCode::CollectorRejected,
+                            message,
+                        )));
                     }
                 }
-            }
+            },
+        ));
+    }
 
-            // Process ended
-            println!("[Sidecar] Process ended");
-            state.set_status(SidecarStatus::Stopped);
-        });
+    /// Feed a recording into the state instead of running the process
+    /// `speed` scales the original pacing (1.0 = real time)
+    pub fn start_replay(&mut self, path: &Path, speed: f64) -> Result<(), SidecarError> {
+        let lines = sidecar_recording::load_recording(path)
+            .map_err(|e| SidecarError::new(SidecarErrorCode::Fatal, e))?;
 
+        self.stop();
+        println!("[Sidecar] Replaying {:?} at {}x", path, speed);
+
+        let generation = self.state.begin_generation();
+        let line_state = Arc::clone(&self.state);
+        let eof_state = Arc::clone(&self.state);
+
+        // Running before the first line, so a short replay's end isn't overwritten
+        self.state.set_status(SidecarStatus::Running);
+        let handle = sidecar_recording::start_replay(
+            lines,
+            speed,
+            move |line| {
+                if line_state.is_current_generation(generation) {
+                    handle_line(&line_state, line);
+                }
+            },
+            move || {
+                if eof_state.is_current_generation(generation) {
+                    println!("[Sidecar] Replay finished");
+                    eof_state
+                        .logs()
+                        .push_supervisor(LogLevel::Info, "Sidecar replay finished");
+                    eof_state.mark_ended();
+                }
+            },
+        )
+        .map_err(|e| {
+            self.state.set_status(SidecarStatus::Stopped);
+            SidecarError::new(SidecarErrorCode::Fatal, e)
+        })?;
+
+        self.replay = Some(handle);
+        self.state.logs().push_supervisor(
+            LogLevel::Info,
+            &format!("Replaying {:?} at {}x", path, speed),
+        );
         Ok(())
     }
 
-    /// Stop the sidecar process
+    /// Stop the sidecar process (or replay)
     pub fn stop(&mut self) {
-        if let Some(ref mut child) = self.child {
-            println!("[Sidecar] Stopping process");
-            let _ = child.kill();
-            let _ = child.wait();
+        // Invalidate the reader so the kill isn't reported as a crash
+        self.state.begin_generation();
+        self.stop_replay();
+        self.stop_collector();
+        // Exits on its own once the pipe connection is gone
+        #[cfg(windows)]
+        if let Some(child) = self.elevated.take() {
+            self.state.logs().push_supervisor(
+                LogLevel::Info,
+                &format!("Disconnecting elevated sidecar (pid {})", child.pid()),
+            );
+        }
+        if let Ok(process) = self.process.as_mut() {
+            if let Some(pid) = process.pid() {
+                println!("[Sidecar] Stopping process");
+                self.state
+                    .logs()
+                    .push_supervisor(LogLevel::Info, &format!("Stopping sidecar (pid {})", pid));
+            }
+            process.stop();
         }
-        self.child = None;
+        self.binary = None;
         self.state.set_status(SidecarStatus::Stopped);
     }
 
+    /// Check if a recording is being replayed
+    pub fn is_replaying(&self) -> bool {
+        self.replay.as_ref().is_some_and(|r| !r.is_finished())
+    }
+
+    fn stop_replay(&mut self) {
+        if let Some(mut replay) = self.replay.take() {
+            replay.stop();
+        }
+    }
+
+    fn stop_collector(&mut self) {
+        if let Some(mut collector) = self.collector.take() {
+            println!("[Sidecar] Disconnecting from {}", collector.endpoint());
+            collector.stop();
+        }
+    }
+
+    /// Collector endpoint while connected (or reconnecting) to one
+    pub fn collector_endpoint(&self) -> Option<&str> {
+        self.collector.as_ref().map(|c| c.endpoint())
+    }
+
+    /// PID of the running sidecar
+    pub fn pid(&mut self) -> Option<u32> {
+        #[cfg(windows)]
+        if let Some(pid) = self.poll_elevated() {
+            return Some(pid);
+        }
+        let process = self.process.as_mut().ok()?;
+        process.poll();
+        process.pid()
+    }
+
+    /// Spawn/exit history of the sidecar process
+    pub fn runs(&self) -> Vec<ProcessRun> {
+        self.process
+            .as_ref()
+            .map(|p| p.history())
+            .unwrap_or_default()
+    }
+
     /// Check if sidecar is running
     #[allow(dead_code)]
     pub fn is_running(&self) -> bool {
@@ -338,60 +949,367 @@ impl SidecarManager {
     }
 }
 
-impl Default for SidecarManager {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 impl Drop for SidecarManager {
     fn drop(&mut self) {
         self.stop();
     }
 }
 
-/// Start sidecar and return shared state
-/// Includes auto-restart logic with retry limit
-pub fn start_sidecar(app: &tauri::AppHandle) -> Arc<SidecarState> {
-    let mut manager = SidecarManager::new();
-    let state = manager.state();
+/// Command line for the sidecar process
+fn sidecar_args(classes: &[HardwareClass]) -> Vec<String> {
+    vec![
+        "--interval".to_string(),
+        "1000".to_string(), // 1 second updates
+        "--hardware".to_string(),
+        HardwareClass::join(classes),
+    ]
+}
 
-    // Get sidecar path once
-    let sidecar_path = get_sidecar_path(app);
+/// Log a failed integrity check and turn it into the sidecar error
+/// Only a failed copy is worth retrying, a bad binary stays bad
+fn integrity_error(state: &SidecarState, e: integrity::IntegrityError) -> SidecarError {
+    let code = match e {
+        integrity::IntegrityError::Staging(_) => SidecarErrorCode::SpawnFailed,
+        _ => SidecarErrorCode::IntegrityCheckFailed,
+    };
+    let err = SidecarError::new(code, e.to_string());
+    eprintln!("[Sidecar] {}", err);
+    state.logs().push_supervisor(LogLevel::Error, &err.message);
+    err
+}
 
-    match &sidecar_path {
-        Ok(path) => match manager.spawn_process(path) {
-            Ok(()) => {
-                println!("[Sidecar] Started successfully");
-                state.reset_restart_count();
+/// An elevated start waiting for the UAC prompt
+#[cfg(windows)]
+pub struct ElevatedLaunch {
+    binary: integrity::VerifiedBinary,
+    args: Vec<String>,
+    /// Full pipe path the sidecar serves on
+    pipe: String,
+    generation: u64,
+}
+
+#[cfg(windows)]
+impl ElevatedLaunch {
+    /// Show the UAC prompt and start the sidecar, blocks until answered
+    pub fn launch(&self) -> std::io::Result<super::elevated::ElevatedProcess> {
+        super::elevated::ElevatedProcess::launch(self.binary.path(), &self.args)
+    }
+}
+
+/// Connect to an elevated sidecar nobody reads and leave again, so it
+/// sees its last client disconnect and exits
+#[cfg(windows)]
+fn release_pipe(pipe: String) {
+    thread::spawn(move || {
+        // The sidecar may still be setting up its pipe
+        for _ in 0..50 {
+            if std::fs::OpenOptions::new().read(true).open(&pipe).is_ok() {
+                return;
             }
-            Err(e) => {
-                eprintln!("[Sidecar] Failed to start: {}", e);
-                state.set_status(SidecarStatus::Error(e));
+            thread::sleep(Duration::from_millis(200));
+        }
+    });
+}
+
+/// Parse one JSON line from the sidecar and store it in the state
+fn handle_line(state: &SidecarState, line: &str) {
+    match serde_json::from_str::<SidecarData>(line) {
+        Ok(data) => {
+            // Log first successful read
+            if data.error.is_none() && state.get_status() != SidecarStatus::Running {
+                println!("[Sidecar] Receiving data successfully");
+                state.set_status(SidecarStatus::Running);
             }
-        },
+            state.set_data(data);
+        }
         Err(e) => {
-            eprintln!("[Sidecar] Binary not found: {}", e);
-            state.set_status(SidecarStatus::Error(e.clone()));
+            eprintln!("[Sidecar] JSON parse error: {} - Line: {}", e, line);
+            state.logs().push_supervisor(
+                LogLevel::Warn,
+                &format!("JSON parse error: {} - Line: {}", e, line),
+            );
         }
     }
+}
 
-    // Leak manager to keep it alive
-    std::mem::forget(manager);
+type GiveUpListener = Box<dyn Fn(&SidecarError) + Send + Sync>;
 
-    // Start watcher thread for auto-restart
-    if let Ok(path) = sidecar_path {
-        let state_clone = Arc::clone(&state);
-        thread::spawn(move || {
-            sidecar_watcher(state_clone, path);
-        });
+/// Supervisor that owns the sidecar process for the whole app session
+/// All lifecycle changes (auto-restart, commands, app exit) go through it
+pub struct SidecarSupervisor {
+    manager: Mutex<SidecarManager>,
+    state: Arc<SidecarState>,
+    /// Whether the sidecar should be running (false after an explicit stop)
+    enabled: AtomicBool,
+    /// Called when the watcher stops restarting a crashing sidecar
+    give_up_listener: RwLock<Option<GiveUpListener>>,
+}
+
+impl SidecarSupervisor {
+    pub fn new(path: Result<PathBuf, String>) -> Self {
+        let state = Arc::new(SidecarState::new());
+        let path = path.map_err(|e| SidecarError::new(SidecarErrorCode::BinaryNotFound, e));
+        Self {
+            manager: Mutex::new(SidecarManager::new(path, Arc::clone(&state))),
+            state,
+            enabled: AtomicBool::new(true),
+            give_up_listener: RwLock::new(None),
+        }
+    }
+
+    /// Call `listener` when auto-restart gives up after `MAX_RESTART_ATTEMPTS`
+    pub fn set_give_up_listener<F>(&self, listener: F)
+    where
+        F: Fn(&SidecarError) + Send + Sync + 'static,
+    {
+        if let Ok(mut guard) = self.give_up_listener.write() {
+            *guard = Some(Box::new(listener));
+        }
+    }
+
+    /// Get shared state handle
+    pub fn state(&self) -> Arc<SidecarState> {
+        Arc::clone(&self.state)
+    }
+
+    /// Whether the sidecar is supposed to be running
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Start the sidecar if it isn't running yet
+    pub fn start(&self) -> Result<(), SidecarError> {
+        self.enabled.store(true, Ordering::SeqCst);
+        if self.state.get_status() == SidecarStatus::Running {
+            return Ok(());
+        }
+        self.state.reset_restart_count();
+        self.spawn()
+    }
+
+    /// Stop the sidecar and keep it stopped until started again
+    pub fn stop(&self) {
+        self.enabled.store(false, Ordering::SeqCst);
+        self.stop_process();
+    }
+
+    /// Stop the running sidecar (if any) and spawn a fresh one
+    /// Also resets the restart budget, so it works after the watcher gave up
+    pub fn restart(&self) -> Result<(), SidecarError> {
+        self.enabled.store(true, Ordering::SeqCst);
+        self.stop_process();
+        self.state.reset_restart_count();
+        self.spawn()
+    }
+
+    /// Replay a recording instead of running the sidecar
+    /// Auto-restart stays off afterwards - start or restart goes back to live data
+    pub fn replay(&self, path: &Path, speed: f64) -> Result<(), SidecarError> {
+        self.enabled.store(false, Ordering::SeqCst);
+        self.lock_manager()?.start_replay(path, speed)
+    }
+
+    /// Switch between a collector endpoint and spawning the sidecar
+    /// Restarts the data source if it changed while enabled
+    pub fn set_collector_endpoint(&self, endpoint: Option<String>) -> Result<(), SidecarError> {
+        let mut manager = self.lock_manager()?;
+        if manager.endpoint() == endpoint.as_deref() {
+            return Ok(());
+        }
+        manager.set_endpoint(endpoint);
+        drop(manager);
+
+        if self.is_enabled() {
+            self.restart()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Directory for the verified copy the sidecar runs from
+    pub fn set_staging_dir(&self, dir: PathBuf) -> Result<(), SidecarError> {
+        self.lock_manager()?.set_staging_dir(dir);
+        Ok(())
+    }
+
+    /// Choose the hardware classes the sidecar reads
+    /// Collectors are configured on their own side and ignore this
+    pub fn set_hardware_classes(&self, classes: &[HardwareClass]) -> Result<(), SidecarError> {
+        self.lock_manager()?.set_hardware(classes);
+        Ok(())
+    }
+
+    /// Kill the sidecar on app exit
+    pub fn shutdown(&self) {
+        println!("[Sidecar] Shutting down");
+        self.stop();
+    }
+
+    /// Status payload for the frontend
+    pub fn status_payload(&self) -> SidecarStatusPayload {
+        let (pid, runs, replaying, collector) = match self.manager.lock() {
+            Ok(mut manager) => (
+                manager.pid(),
+                manager.runs(),
+                manager.is_replaying(),
+                manager.collector_endpoint().map(str::to_string),
+            ),
+            Err(_) => (None, Vec::new(), false, None),
+        };
+
+        SidecarStatusPayload {
+            status: self.state.get_status_info(),
+            restart_count: self.state.get_restart_count(),
+            can_restart: self.is_enabled() && self.state.can_restart(),
+            error: self.state.get_error(),
+            pid,
+            runs,
+            recording: self.state.recording_path(),
+            replaying,
+            collector,
+        }
+    }
+
+    fn lock_manager(&self) -> Result<std::sync::MutexGuard<'_, SidecarManager>, SidecarError> {
+        self.manager.lock().map_err(|e| {
+            SidecarError::new(
+                SidecarErrorCode::Unknown,
+                format!("Failed to acquire lock: {}", e),
+            )
+        })
+    }
+
+    /// Whether a process, collector connection or replay is active
+    fn is_source_alive(&self) -> bool {
+        match self.manager.lock() {
+            Ok(mut manager) => {
+                manager.pid().is_some()
+                    || manager.collector_endpoint().is_some()
+                    || manager.is_replaying()
+            }
+            Err(_) => true,
+        }
+    }
+
+    fn stop_process(&self) {
+        if let Ok(mut manager) = self.manager.lock() {
+            manager.stop();
+        }
+    }
+
+    /// Start the process (or collector connection), replacing any exited one
+    fn spawn(&self) -> Result<(), SidecarError> {
+        #[cfg(windows)]
+        let result = if self.lock_manager()?.needs_elevation() {
+            self.spawn_elevated()
+        } else {
+            self.lock_manager()?.start()
+        };
+        #[cfg(not(windows))]
+        let result = self.lock_manager()?.start();
+
+        result.inspect_err(|e| {
+            self.state.set_status(SidecarStatus::Error(e.clone()));
+        })
+    }
+
+    /// Launch the sidecar through the UAC prompt and read it over a pipe
+    /// Only the sidecar gets admin rights, the app keeps running as the user.
+    /// The prompt blocks until answered, so the manager isn't locked
+    /// meanwhile - status and stats keep flowing.
+    #[cfg(windows)]
+    fn spawn_elevated(&self) -> Result<(), SidecarError> {
+        let launch = self.lock_manager()?.prepare_elevated()?;
+        let child = launch.launch();
+        self.lock_manager()?.finish_elevated(launch, child)
+    }
+}
+
+/// Start sidecar and return its supervisor
+/// Includes auto-restart logic with retry limit
+pub fn init_sidecar(app: &tauri::AppHandle, settings: &Settings) -> Arc<SidecarSupervisor> {
+    // Get sidecar path once
+    let sidecar_path = get_sidecar_path(app);
+    if let Err(e) = &sidecar_path {
+        eprintln!("[Sidecar] Binary not found: {}", e);
+    }
+
+    let supervisor = Arc::new(SidecarSupervisor::new(sidecar_path));
+    if let Ok(dir) = app.path().app_local_data_dir() {
+        if let Err(e) = supervisor.set_staging_dir(dir.join("sidecar")) {
+            eprintln!("[Sidecar] Failed to set staging directory: {}", e);
+        }
+    }
+    if let Err(e) = supervisor.set_hardware_classes(&settings.hardware_classes) {
+        eprintln!("[Sidecar] Failed to set hardware classes: {}", e);
     }
+    if let Err(e) = supervisor.set_collector_endpoint(settings.collector_endpoint.clone()) {
+        eprintln!("[Sidecar] Failed to set collector endpoint: {}", e);
+    }
+
+    // Forward every log line to the frontend
+    let app_handle = app.clone();
+    supervisor.state().logs().set_listener(move |entry| {
+        let _ = app_handle.emit("sidecar-log", entry);
+    });
 
-    state
+    // PULSE_SIDECAR_REPLAY=<file> replays a recording instead (e.g. on a dev box without LHM)
+    if let Ok(replay_path) = std::env::var("PULSE_SIDECAR_REPLAY") {
+        let speed = std::env::var("PULSE_SIDECAR_REPLAY_SPEED")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1.0);
+        if let Err(e) = supervisor.replay(Path::new(&replay_path), speed) {
+            eprintln!("[Sidecar] Failed to replay {}: {}", replay_path, e);
+        }
+    } else {
+        match supervisor.start() {
+            Ok(()) => println!("[Sidecar] Started successfully"),
+            Err(e) => eprintln!("[Sidecar] Failed to start: {}", e),
+        }
+    }
+
+    // Start watcher thread for auto-restart
+    let supervisor_clone = Arc::clone(&supervisor);
+    thread::spawn(move || {
+        sidecar_watcher(supervisor_clone);
+    });
+
+    supervisor
 }
 
 /// Get sidecar binary path (production or dev mode)
 fn get_sidecar_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    // Linux: pulse-sensord is bundled as an external binary (staged in
+    // binaries/ by scripts/build-sensord.sh) and installed next to the app
+    if cfg!(target_os = "linux") {
+        // Debug builds: the staged copy the digest was computed from - cargo
+        // may have rebuilt the one next to the app binary since
+        if cfg!(debug_assertions) {
+            let dev_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("binaries")
+                .join(format!(
+                    "{}-{}",
+                    integrity::LINUX_SIDECAR,
+                    env!("PULSE_TARGET_TRIPLE")
+                ));
+            println!("[Sidecar] Checking dev path: {:?}", dev_path);
+            if dev_path.exists() {
+                return Ok(dev_path);
+            }
+        }
+
+        let exe =
+            std::env::current_exe().map_err(|e| format!("Failed to locate app binary: {}", e))?;
+        let path = exe.with_file_name(integrity::LINUX_SIDECAR);
+        println!("[Sidecar] Checking Linux sidecar path: {:?}", path);
+        return if path.exists() {
+            Ok(path)
+        } else {
+            Err(format!("Sidecar binary not found. Expected at: {:?}", path))
+        };
+    }
+
     let binary_name = "lhm-sidecar-x86_64-pc-windows-msvc.exe";
 
     // Try 1: Production path via Tauri resource_dir
@@ -413,18 +1331,21 @@ fn get_sidecar_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String
         return Ok(dev_path);
     }
 
-    // Try 3: Fallback - current_dir based paths
-    if let Ok(cwd) = std::env::current_dir() {
-        // If running from project root
-        let root_path = cwd.join("src-tauri").join("binaries").join(binary_name);
-        if root_path.exists() {
-            return Ok(root_path);
-        }
+    // Try 3: Fallback - current_dir based paths (debug builds only, a
+    // release build must not pick up whatever binary sits in the cwd)
+    if cfg!(debug_assertions) {
+        if let Ok(cwd) = std::env::current_dir() {
+            // If running from project root
+            let root_path = cwd.join("src-tauri").join("binaries").join(binary_name);
+            if root_path.exists() {
+                return Ok(root_path);
+            }
 
-        // If running from src-tauri
-        let src_path = cwd.join("binaries").join(binary_name);
-        if src_path.exists() {
-            return Ok(src_path);
+            // If running from src-tauri
+            let src_path = cwd.join("binaries").join(binary_name);
+            if src_path.exists() {
+                return Ok(src_path);
+            }
         }
     }
 
@@ -437,129 +1358,95 @@ fn get_sidecar_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String
 }
 
 /// Watcher thread that monitors sidecar and restarts if needed
-fn sidecar_watcher(state: Arc<SidecarState>, path: std::path::PathBuf) {
-    use std::time::Duration;
-
+/// Runs for the whole session so a manual restart re-arms auto-restart
+fn sidecar_watcher(supervisor: Arc<SidecarSupervisor>) {
     // Wait a bit before starting to monitor
     thread::sleep(Duration::from_secs(5));
 
     loop {
         thread::sleep(Duration::from_secs(3));
+        check_sidecar(&supervisor, RESTART_DELAY);
+    }
+}
 
-        let status = state.get_status();
+/// One pass of the watcher
+fn check_sidecar(supervisor: &SidecarSupervisor, restart_delay: Duration) {
+    let state = supervisor.state();
+    let status = state.get_status();
 
-        match status {
-            SidecarStatus::Stopped => {
-                // Sidecar stopped - try to restart
-                if state.can_restart() {
-                    let count = state.increment_restart_count();
-                    println!(
-                        "[Sidecar] Attempting restart {}/{}",
-                        count, MAX_RESTART_ATTEMPTS
-                    );
-
-                    // Wait before restart
-                    thread::sleep(Duration::from_secs(2));
-
-                    // Try to spawn new process
-                    match spawn_standalone(&path, &state) {
-                        Ok(()) => {
-                            println!("[Sidecar] Restart successful");
-                            // Reset count on successful restart after receiving data
-                        }
-                        Err(e) => {
-                            eprintln!("[Sidecar] Restart failed: {}", e);
-                            state.set_status(SidecarStatus::Error(e));
-                        }
-                    }
-                } else {
-                    println!("[Sidecar] Max restart attempts reached, giving up");
-                    state.set_status(SidecarStatus::Error(format!(
-                        "Sidecar crashed {} times, giving up",
-                        MAX_RESTART_ATTEMPTS
-                    )));
-                    break; // Stop monitoring
-                }
-            }
-            SidecarStatus::Running => {
-                // Reset restart count when running successfully
-                if state.get_restart_count() > 0 {
-                    state.reset_restart_count();
-                }
-            }
-            SidecarStatus::Error(_) => {
-                // Error state - stop monitoring
-                break;
-            }
-            SidecarStatus::NotStarted => {
-                // Should not happen, but wait
+    match status {
+        SidecarStatus::Stopped => {
+            restart_within_budget(supervisor, &status, restart_delay);
+        }
+        SidecarStatus::Running => {
+            // Reset restart count when running successfully
+            if state.get_restart_count() > 0 {
+                state.reset_restart_count();
             }
         }
+        SidecarStatus::Error(ref err) if err.retryable && !supervisor.is_source_alive() => {
+            // Nothing running, e.g. the spawn itself failed - retry like a crash
+            restart_within_budget(supervisor, &status, restart_delay);
+        }
+        SidecarStatus::Error(ref err) if err.retryable => {
+            // Retryable error while the process is still running (e.g. a
+            // failed sensor read) - it recovers on the next good sample
+        }
+        SidecarStatus::Error(_) => {
+            // Permanent error - retrying won't help, wait for a manual restart
+        }
+        SidecarStatus::NotStarted => {
+            // Should not happen, but wait
+        }
     }
-
-    println!("[Sidecar] Watcher stopped");
 }
 
-/// Spawn sidecar process standalone (for restart)
-fn spawn_standalone(path: &std::path::Path, state: &Arc<SidecarState>) -> Result<(), String> {
-    println!("[Sidecar] Starting: {:?}", path);
-
-    let mut child = Command::new(path)
-        .args(["--interval", "1000"])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .creation_flags(0x08000000) // CREATE_NO_WINDOW
-        .spawn()
-        .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
-
-    let stdout = child
-        .stdout
-        .take()
-        .ok_or_else(|| "Failed to capture stdout".to_string())?;
-
-    state.set_status(SidecarStatus::Running);
+/// Restart a sidecar that stopped or failed in `status`, giving up once
+/// `MAX_RESTART_ATTEMPTS` restarts didn't get it running
+fn restart_within_budget(supervisor: &SidecarSupervisor, status: &SidecarStatus, delay: Duration) {
+    let state = supervisor.state();
 
-    // Spawn reader thread
-    let state_clone = Arc::clone(state);
-    thread::spawn(move || {
-        let reader = BufReader::new(stdout);
-
-        for line in reader.lines() {
-            match line {
-                Ok(json_line) => {
-                    let json_str = json_line.trim();
-                    if json_str.is_empty() {
-                        continue;
-                    }
+    // Stopped on purpose - leave it alone
+    if !supervisor.is_enabled() {
+        return;
+    }
 
-                    match serde_json::from_str::<SidecarData>(json_str) {
-                        Ok(data) => {
-                            if state_clone.get_status() != SidecarStatus::Running {
-                                println!("[Sidecar] Receiving data successfully");
-                                state_clone.set_status(SidecarStatus::Running);
-                            }
-                            state_clone.set_data(data);
-                        }
-                        Err(e) => {
-                            eprintln!("[Sidecar] JSON parse error: {} - Line: {}", e, json_line);
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("[Sidecar] Read error: {}", e);
-                    break;
-                }
+    if !state.can_restart() {
+        println!("[Sidecar] Max restart attempts reached, giving up");
+        state
+            .logs()
+            .push_supervisor(LogLevel::Error, "Max restart attempts reached, giving up");
+        let error = SidecarError::new(
+            SidecarErrorCode::Crashed,
+            format!("Sidecar crashed {} times, giving up", MAX_RESTART_ATTEMPTS),
+        );
+        state.set_status(SidecarStatus::Error(error.clone()));
+        if let Ok(guard) = supervisor.give_up_listener.read() {
+            if let Some(listener) = guard.as_ref() {
+                listener(&error);
             }
         }
+        return;
+    }
 
-        println!("[Sidecar] Process ended");
-        state_clone.set_status(SidecarStatus::Stopped);
+    let count = state.increment_restart_count();
+    println!(
+        "[Sidecar] Attempting restart {}/{}",
+        count, MAX_RESTART_ATTEMPTS
+    );
 
-        // Wait for child to fully exit
-        let _ = child.wait();
-    });
+    // Wait before restart
+    thread::sleep(delay);
 
-    Ok(())
+    // A command may have changed the state while we waited
+    if !supervisor.is_enabled() || state.get_status() != *status {
+        return;
+    }
+
+    match supervisor.spawn() {
+        Ok(()) => println!("[Sidecar] Restart successful"),
+        Err(e) => eprintln!("[Sidecar] Restart failed: {}", e),
+    }
 }
 
 #[cfg(test)]
@@ -581,10 +1468,310 @@ mod tests {
         assert_eq!(data.cpu.as_ref().unwrap().temperature, Some(65.0));
     }
 
+    #[test]
+    fn test_parse_sensor_tree() {
+        let json = r#"{"gpu":[],"sensors":[{"id":"/lpc/nct6798d/voltage/0","hardware_id":"/lpc/nct6798d","hardware_name":"Nuvoton NCT6798D","hardware_type":"SuperIO","sensor_type":"Voltage","name":"Vcore","value":1.25,"min":0.9,"max":1.4,"unit":"V"},{"id":"/hdd/0/temperature/0","name":"Temperature","value":null}],"timestamp":1}"#;
+        let data: SidecarData = serde_json::from_str(json).unwrap();
+        assert_eq!(data.sensors.len(), 2);
+        assert_eq!(data.sensors[0].sensor_type, "Voltage");
+        assert_eq!(data.sensors[0].max, Some(1.4));
+        assert!(data.sensors[1].value.is_none());
+
+        // Older sidecars don't send the tree at all
+        let json = r#"{"gpu":[],"timestamp":1}"#;
+        let data: SidecarData = serde_json::from_str(json).unwrap();
+        assert!(data.sensors.is_empty());
+    }
+
     #[test]
     fn test_parse_error_json() {
-        let json = r#"{"gpu":[],"timestamp":1234567890,"error":"Admin rights required"}"#;
+        let json = r#"{"gpu":[],"timestamp":1234567890,"error":{"code":"requires_admin","message":"Admin rights required","retryable":false}}"#;
+        let data: SidecarData = serde_json::from_str(json).unwrap();
+        let err = data.error.unwrap();
+        assert_eq!(err.code, SidecarErrorCode::RequiresAdmin);
+        assert!(!err.retryable);
+    }
+
+    #[test]
+    fn test_parse_legacy_and_unknown_errors() {
+        let json = r#"{"gpu":[],"timestamp":1,"error":"Admin rights required"}"#;
         let data: SidecarData = serde_json::from_str(json).unwrap();
-        assert!(data.error.is_some());
+        assert_eq!(data.error.unwrap().code, SidecarErrorCode::Unknown);
+
+        let json = r#"{"gpu":[],"timestamp":1,"error":{"code":"gpu_on_fire","message":"x"}}"#;
+        let data: SidecarData = serde_json::from_str(json).unwrap();
+        let err = data.error.unwrap();
+        assert_eq!(err.code, SidecarErrorCode::Unknown);
+        assert!(err.retryable);
+    }
+
+    #[test]
+    fn test_status_info_uses_error_code() {
+        let admin = SidecarStatus::Error(SidecarError::new(
+            SidecarErrorCode::RequiresAdmin,
+            "no rights",
+        ));
+        assert!(matches!(
+            SidecarStatusInfo::from(&admin),
+            SidecarStatusInfo::RequiresAdmin
+        ));
+
+        // Wording alone no longer changes the classification
+        let other = SidecarStatus::Error(SidecarError::new(
+            SidecarErrorCode::Fatal,
+            "binary admin permission not found",
+        ));
+        assert!(matches!(
+            SidecarStatusInfo::from(&other),
+            SidecarStatusInfo::Error(_)
+        ));
+
+        let tampered = SidecarStatus::Error(SidecarError::new(
+            SidecarErrorCode::IntegrityCheckFailed,
+            "Sidecar binary was modified",
+        ));
+        assert!(matches!(
+            SidecarStatusInfo::from(&tampered),
+            SidecarStatusInfo::IntegrityCheckFailed(_)
+        ));
+        assert!(!SidecarErrorCode::IntegrityCheckFailed.is_retryable());
+    }
+
+    #[test]
+    fn test_non_retryable_error_survives_process_exit() {
+        let state = SidecarState::new();
+        state.set_status(SidecarStatus::Error(SidecarError::new(
+            SidecarErrorCode::RequiresAdmin,
+            "no rights",
+        )));
+        state.mark_ended();
+        assert!(matches!(state.get_status(), SidecarStatus::Error(_)));
+
+        state.set_status(SidecarStatus::Error(SidecarError::new(
+            SidecarErrorCode::CollectionFailed,
+            "read failed",
+        )));
+        state.mark_ended();
+        assert_eq!(state.get_status(), SidecarStatus::Stopped);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_watcher_retries_failed_spawn() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path =
+            std::env::temp_dir().join(format!("pulse-sidecar-flaky-{}.sh", std::process::id()));
+        let script = b"#!/bin/sh\necho '{\"gpu\":[],\"timestamp\":1}'\nsleep 30\n";
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        // A file where the staging directory should be - the copy fails
+        let staging =
+            std::env::temp_dir().join(format!("pulse-sidecar-staging-{}", std::process::id()));
+        std::fs::write(&staging, b"").unwrap();
+        let supervisor = SidecarSupervisor::new(Ok(path.clone()));
+        supervisor.set_staging_dir(staging.clone()).unwrap();
+        supervisor.manager.lock().unwrap().digest = Some(integrity::sha256_bytes(script));
+        let err = supervisor.start().unwrap_err();
+        assert_eq!(err.code, SidecarErrorCode::SpawnFailed);
+        assert!(err.retryable);
+
+        std::fs::remove_file(&staging).unwrap();
+        check_sidecar(&supervisor, Duration::ZERO);
+        let state = supervisor.state();
+        wait_for_status(&state, SidecarStatus::Running);
+        assert_eq!(state.get_restart_count(), 1);
+        assert!(supervisor.status_payload().pid.is_some());
+
+        supervisor.stop();
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_dir(staging);
+    }
+
+    fn write_recording(name: &str, lines: &[(u64, &str)]) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("pulse-sidecar-{}-{}.rec", name, std::process::id()));
+        let mut content = String::from("# pulse sidecar recording v1\n");
+        for (offset, line) in lines {
+            content.push_str(&format!("{}\t{}\n", offset, line));
+        }
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn wait_for_status(state: &SidecarState, status: SidecarStatus) {
+        for _ in 0..250 {
+            if state.get_status() == status {
+                return;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        panic!("status never became {:?}", status);
+    }
+
+    #[test]
+    fn test_replay_into_state_and_merge() {
+        let path = write_recording(
+            "merge",
+            &[
+                (
+                    0,
+                    r#"{"cpu":{"temperature":50.0,"core_temperatures":[48.0],"power":20.0},"gpu":[],"timestamp":1}"#,
+                ),
+                (1000, "not json"),
+                (
+                    2000,
+                    r#"{"cpu":{"temperature":71.5,"core_temperatures":[70.0],"power":42.0},"gpu":[],"timestamp":3}"#,
+                ),
+            ],
+        );
+
+        // No binary needed - replay works on machines without LHM
+        let supervisor = SidecarSupervisor::new(Err("no sidecar here".to_string()));
+        supervisor.replay(&path, 50.0).unwrap();
+        assert!(supervisor.status_payload().replaying);
+
+        let state = supervisor.state();
+        wait_for_status(&state, SidecarStatus::Stopped);
+        assert_eq!(state.get_cpu_temperature(), Some(71.5));
+        assert!(!supervisor.status_payload().replaying);
+
+        // Replay end must not trigger the auto-restart
+        assert!(!supervisor.is_enabled());
+        assert!(!supervisor.status_payload().can_restart);
+
+        let mut stats = crate::models::SystemStats::default();
+        super::super::merge_sidecar_data(
+            &mut stats,
+            state.get_data().as_ref(),
+            Some(Duration::from_millis(10)),
+            &crate::models::Settings::default(),
+        );
+        assert_eq!(stats.cpu.temperature, Some(71.5));
+        assert_eq!(stats.cpu.power, Some(42.0));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_start_after_replay_reports_missing_binary() {
+        let path = write_recording("restart", &[(0, r#"{"gpu":[],"timestamp":1}"#)]);
+        let supervisor = SidecarSupervisor::new(Err("no sidecar here".to_string()));
+        supervisor.replay(&path, 10.0).unwrap();
+        wait_for_status(&supervisor.state(), SidecarStatus::Stopped);
+
+        let err = supervisor.start().unwrap_err();
+        assert_eq!(err.code, SidecarErrorCode::BinaryNotFound);
+        assert!(supervisor.is_enabled());
+        assert!(matches!(
+            supervisor.status_payload().status,
+            SidecarStatusInfo::BinaryNotFound
+        ));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_stopped_replay_does_not_touch_state() {
+        let path = write_recording(
+            "stop",
+            &[
+                (0, r#"{"cpu":{"temperature":40.0},"gpu":[],"timestamp":1}"#),
+                (
+                    60_000,
+                    r#"{"cpu":{"temperature":99.0},"gpu":[],"timestamp":2}"#,
+                ),
+            ],
+        );
+        let supervisor = SidecarSupervisor::new(Err("no sidecar here".to_string()));
+        supervisor.replay(&path, 1.0).unwrap();
+
+        let state = supervisor.state();
+        for _ in 0..250 {
+            if state.get_data().is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        supervisor.stop();
+        assert_eq!(state.get_status(), SidecarStatus::Stopped);
+        assert_eq!(state.get_cpu_temperature(), Some(40.0));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_recording_captures_raw_lines() {
+        let state = SidecarState::new();
+        let path =
+            std::env::temp_dir().join(format!("pulse-sidecar-capture-{}.rec", std::process::id()));
+        state.start_recording(path.clone()).unwrap();
+        assert_eq!(state.recording_path(), Some(path.clone()));
+
+        state.record_line(r#"{"gpu":[],"timestamp":1}"#);
+        state.record_line("garbage is kept as-is");
+        assert_eq!(state.stop_recording(), Some(path.clone()));
+        assert!(state.recording_path().is_none());
+
+        let lines = sidecar_recording::load_recording(&path).unwrap();
+        let raw: Vec<&str> = lines.iter().map(|l| l.line.as_str()).collect();
+        assert_eq!(
+            raw,
+            vec![r#"{"gpu":[],"timestamp":1}"#, "garbage is kept as-is"]
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collector_endpoint_replaces_process() {
+        use std::io::Write;
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!(
+            "pulse-sidecar-collector-{}.sock",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let endpoint = path.to_string_lossy().into_owned();
+
+        // No binary, but a collector endpoint is all we need
+        let supervisor = SidecarSupervisor::new(Err("no sidecar here".to_string()));
+        supervisor
+            .set_collector_endpoint(Some(endpoint.clone()))
+            .unwrap();
+        let state = supervisor.state();
+
+        // Collector not up yet - retryable error, nothing for the watcher to restart
+        // (read it once: the next connect attempt may clear it again)
+        let mut error = None;
+        for _ in 0..250 {
+            error = state.get_error();
+            if error.is_some() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        let err = error.unwrap();
+        assert_eq!(err.code, SidecarErrorCode::CollectorUnavailable);
+        assert!(err.retryable);
+
+        let listener = UnixListener::bind(&path).unwrap();
+        let (mut conn, _) = listener.accept().unwrap();
+        writeln!(
+            conn,
+            r#"{{"cpu":{{"temperature":55.0}},"gpu":[],"timestamp":1}}"#
+        )
+        .unwrap();
+        wait_for_status(&state, SidecarStatus::Running);
+        assert_eq!(state.get_cpu_temperature(), Some(55.0));
+
+        let payload = supervisor.status_payload();
+        assert_eq!(payload.collector, Some(endpoint));
+        assert!(payload.pid.is_none());
+
+        // Back to spawning the (missing) process
+        let err = supervisor.set_collector_endpoint(None).unwrap_err();
+        assert_eq!(err.code, SidecarErrorCode::BinaryNotFound);
+        assert!(supervisor.status_payload().collector.is_none());
+        let _ = std::fs::remove_file(path);
     }
 }
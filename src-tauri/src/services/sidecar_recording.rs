@@ -0,0 +1,293 @@
+//! Record and replay raw sidecar output
+//!
+//! A recording is a text file with one `<offset ms>\t<raw JSON line>` entry
+//! per line, where the offset is relative to the start of the recording.
+//! Replaying feeds the lines back at the original pace (or faster), so a
+//! user's sensor problem can be reproduced on a machine without LHM.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// First line of every recording
+const HEADER: &str = "# pulse sidecar recording v1";
+
+/// Longest single sleep while replaying, so stop requests are noticed quickly
+const MAX_SLEEP: Duration = Duration::from_millis(100);
+
+/// Writes raw sidecar lines to a recording file
+pub struct StreamRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    started: Instant,
+}
+
+impl StreamRecorder {
+    /// Create a new recording at `path`
+    pub fn create(path: PathBuf) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create recording dir: {}", e))?;
+        }
+
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create recording {:?}: {}", path, e))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(
+            writer,
+            "{} started {}",
+            HEADER,
+            chrono::Local::now().to_rfc3339()
+        )
+        .map_err(|e| format!("Failed to write recording: {}", e))?;
+
+        Ok(Self {
+            path,
+            writer,
+            started: Instant::now(),
+        })
+    }
+
+    /// Default file name for a recording started now
+    pub fn default_file_name() -> String {
+        format!(
+            "sidecar-{}.rec",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        )
+    }
+
+    /// Path of the recording file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one raw line
+    pub fn record(&mut self, line: &str) -> Result<(), String> {
+        let offset = self.started.elapsed().as_millis();
+        writeln!(self.writer, "{}\t{}", offset, line)
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("Failed to write recording: {}", e))
+    }
+}
+
+/// One recorded line
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedLine {
+    pub offset_ms: u64,
+    pub line: String,
+}
+
+/// Load all lines of a recording
+pub fn load_recording(path: &Path) -> Result<Vec<RecordedLine>, String> {
+    let file =
+        File::open(path).map_err(|e| format!("Failed to open recording {:?}: {}", path, e))?;
+
+    let mut lines = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read recording: {}", e))?;
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (offset, raw) = line
+            .split_once('\t')
+            .ok_or_else(|| format!("Invalid recording line {}", index + 1))?;
+        let offset_ms = offset
+            .parse()
+            .map_err(|_| format!("Invalid offset on recording line {}", index + 1))?;
+
+        lines.push(RecordedLine {
+            offset_ms,
+            line: raw.to_string(),
+        });
+    }
+    Ok(lines)
+}
+
+/// Handle of a running replay - dropping it stops the replay
+pub struct ReplayHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ReplayHandle {
+    /// Stop the replay and wait for its thread
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Check if the replay already sent its last line
+    pub fn is_finished(&self) -> bool {
+        self.thread
+            .as_ref()
+            .map(|t| t.is_finished())
+            .unwrap_or(true)
+    }
+}
+
+impl Drop for ReplayHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Feed recorded lines to `on_line` on a background thread
+///
+/// `speed` scales the original pacing (1.0 = real time, 10.0 = ten times
+/// faster). `on_eof` is called after the last line, unless stopped early.
+pub fn start_replay<L, E>(
+    lines: Vec<RecordedLine>,
+    speed: f64,
+    mut on_line: L,
+    on_eof: E,
+) -> Result<ReplayHandle, String>
+where
+    L: FnMut(&str) + Send + 'static,
+    E: FnOnce() + Send + 'static,
+{
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err("Replay speed must be a positive number".to_string());
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_flag = Arc::clone(&stop);
+
+    let thread = thread::spawn(move || {
+        let started = Instant::now();
+
+        for recorded in &lines {
+            let due = Duration::from_secs_f64(recorded.offset_ms as f64 / 1000.0 / speed);
+            while let Some(remaining) = due.checked_sub(started.elapsed()) {
+                if stop_flag.load(Ordering::SeqCst) || remaining.is_zero() {
+                    break;
+                }
+                thread::sleep(remaining.min(MAX_SLEEP));
+            }
+
+            if stop_flag.load(Ordering::SeqCst) {
+                return;
+            }
+            on_line(&recorded.line);
+        }
+
+        on_eof();
+    });
+
+    Ok(ReplayHandle {
+        stop,
+        thread: Some(thread),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "pulse-recording-{}-{}.rec",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let path = temp_path("roundtrip");
+        let mut recorder = StreamRecorder::create(path.clone()).unwrap();
+        recorder.record(r#"{"gpu":[],"timestamp":1}"#).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        recorder.record(r#"{"gpu":[],"timestamp":2}"#).unwrap();
+        drop(recorder);
+
+        let lines = load_recording(&path).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].line, r#"{"gpu":[],"timestamp":1}"#);
+        assert!(lines[1].offset_ms >= lines[0].offset_ms + 20);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_load_rejects_garbage() {
+        let path = temp_path("garbage");
+        fs::write(&path, "# header\nnot a recording line\n").unwrap();
+        assert!(load_recording(&path).is_err());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_replay_keeps_order_and_pace() {
+        let lines = vec![
+            RecordedLine {
+                offset_ms: 0,
+                line: "a".to_string(),
+            },
+            RecordedLine {
+                offset_ms: 1000,
+                line: "b".to_string(),
+            },
+            RecordedLine {
+                offset_ms: 2000,
+                line: "c".to_string(),
+            },
+        ];
+        let (tx, rx) = mpsc::channel();
+        let (eof_tx, eof_rx) = mpsc::channel();
+
+        // 2 s of recording at 20x should take ~100 ms
+        let started = Instant::now();
+        let _handle = start_replay(
+            lines,
+            20.0,
+            move |line| tx.send(line.to_string()).unwrap(),
+            move || eof_tx.send(()).unwrap(),
+        )
+        .unwrap();
+        eof_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        let received: Vec<String> = rx.try_iter().collect();
+        assert_eq!(received, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_replay_can_be_stopped() {
+        let lines = vec![
+            RecordedLine {
+                offset_ms: 0,
+                line: "a".to_string(),
+            },
+            RecordedLine {
+                offset_ms: 60_000,
+                line: "b".to_string(),
+            },
+        ];
+        let (tx, rx) = mpsc::channel();
+        let mut handle = start_replay(
+            lines,
+            1.0,
+            move |line| tx.send(line.to_string()).unwrap(),
+            || panic!("stopped replay must not report eof"),
+        )
+        .unwrap();
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "a");
+        handle.stop();
+        assert!(handle.is_finished());
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_rejects_invalid_speed() {
+        assert!(start_replay(Vec::new(), 0.0, |_| {}, || {}).is_err());
+        assert!(start_replay(Vec::new(), f64::NAN, |_| {}, || {}).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! Running min / max / average per sensor
+//!
+//! HWiNFO-style "since reset" columns. Every numeric `SystemStats` field
+//! (found by walking its serialized form, so new fields are picked up
+//! automatically) and every sidecar sensor keeps an aggregate in memory,
+//! independent of the metric history and its storage.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+use super::history::metric_unit;
+use super::sidecar::SidecarData;
+use crate::models::{SensorSummary, SummaryEntry, SystemStats};
+
+/// Top-level stats fields that aren't sensor readings
+const SKIPPED_FIELDS: [&str; 4] = ["system_info", "processes", "timestamp", "freshness"];
+
+#[derive(Debug, Clone, Default)]
+struct Aggregate {
+    current: Option<f64>,
+    min: f64,
+    max: f64,
+    sum: f64,
+    samples: u64,
+}
+
+impl Aggregate {
+    fn add(&mut self, value: f64) {
+        if self.samples == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += value;
+        self.samples += 1;
+        self.current = Some(value);
+    }
+
+    fn entry(&self, id: &str, name: &str, unit: &str) -> SummaryEntry {
+        SummaryEntry {
+            id: id.to_string(),
+            name: name.to_string(),
+            unit: unit.to_string(),
+            current: self.current,
+            min: self.min,
+            max: self.max,
+            average: self.sum / self.samples as f64,
+            samples: self.samples,
+        }
+    }
+}
+
+/// Aggregate of a sidecar sensor plus what's needed to display it
+#[derive(Debug, Clone, Default)]
+struct SensorAggregate {
+    name: String,
+    unit: String,
+    order: usize, // Position in the sidecar's sensor list when first seen
+    aggregate: Aggregate,
+}
+
+#[derive(Default)]
+struct Inner {
+    since: u64,
+    updated: Option<u64>,
+    stats: BTreeMap<String, Aggregate>,
+    sensors: BTreeMap<String, SensorAggregate>,
+    sidecar_timestamp: Option<i64>, // Last sidecar sample folded in
+}
+
+/// Running aggregates since app start or the last reset
+pub struct SensorSummaryTracker {
+    inner: Mutex<Inner>,
+}
+
+impl Default for SensorSummaryTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SensorSummaryTracker {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                since: now_millis(),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Fold a stats sample, and the sidecar sample it was merged from, into the aggregates
+    /// A sidecar sample is only counted once, however many stats samples reuse it
+    pub fn record(&self, stats: &SystemStats, sidecar: Option<&SidecarData>) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        inner.updated = Some(stats.timestamp);
+
+        let mut values = Vec::new();
+        if let Ok(Value::Object(fields)) = serde_json::to_value(stats) {
+            for (field, value) in &fields {
+                if !SKIPPED_FIELDS.contains(&field.as_str()) {
+                    numeric_leaves(field, value, &mut values);
+                }
+            }
+        }
+        for aggregate in inner.stats.values_mut() {
+            aggregate.current = None;
+        }
+        for (id, value) in values {
+            inner.stats.entry(id).or_default().add(value);
+        }
+
+        let Some(sidecar) = sidecar.filter(|d| Some(d.timestamp) != inner.sidecar_timestamp) else {
+            return;
+        };
+        inner.sidecar_timestamp = Some(sidecar.timestamp);
+        for sensor in inner.sensors.values_mut() {
+            sensor.aggregate.current = None;
+        }
+        for sensor in &sidecar.sensors {
+            let Some(value) = sensor.value.map(f64::from).filter(|v| v.is_finite()) else {
+                continue;
+            };
+            let order = inner.sensors.len();
+            let entry = inner
+                .sensors
+                .entry(sensor.id.clone())
+                .or_insert_with(|| SensorAggregate {
+                    order,
+                    ..Default::default()
+                });
+            // Names can change when the sidecar restarts with other settings
+            entry.name = sensor_name(&sensor.hardware_name, &sensor.name);
+            entry.unit = sensor.unit.clone();
+            entry.aggregate.add(value);
+        }
+    }
+
+    /// Forget every aggregate and start over from now
+    pub fn reset(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            *inner = Inner {
+                since: now_millis(),
+                ..Default::default()
+            };
+        }
+    }
+
+    pub fn summary(&self) -> SensorSummary {
+        let Ok(inner) = self.inner.lock() else {
+            return SensorSummary::default();
+        };
+
+        let stats = inner
+            .stats
+            .iter()
+            .map(|(id, aggregate)| aggregate.entry(id, id, metric_unit(id)))
+            .collect();
+
+        let mut sensors: Vec<(&String, &SensorAggregate)> = inner.sensors.iter().collect();
+        sensors.sort_by_key(|(_, sensor)| sensor.order);
+        let sensors = sensors
+            .into_iter()
+            .map(|(id, sensor)| sensor.aggregate.entry(id, &sensor.name, &sensor.unit))
+            .collect();
+
+        SensorSummary {
+            since: inner.since,
+            updated: inner.updated,
+            stats,
+            sensors,
+        }
+    }
+}
+
+/// Collect every number below `value`, named by its dotted path
+/// Array items get their index as the last segment, e.g. "cpu.per_core_usage.3"
+fn numeric_leaves(path: &str, value: &Value, out: &mut Vec<(String, f64)>) {
+    match value {
+        Value::Number(n) => {
+            if let Some(n) = n.as_f64().filter(|n| n.is_finite()) {
+                out.push((path.to_string(), n));
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                numeric_leaves(&format!("{}.{}", path, i), item, out);
+            }
+        }
+        Value::Object(fields) => {
+            for (field, item) in fields {
+                numeric_leaves(&format!("{}.{}", path, field), item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn sensor_name(hardware: &str, name: &str) -> String {
+    if hardware.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} / {}", hardware, name)
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GpuStats, Sensor};
+
+    fn stats(timestamp: u64, usage: f32, temperature: Option<f32>) -> SystemStats {
+        let mut stats = SystemStats {
+            timestamp,
+            ..Default::default()
+        };
+        stats.cpu.usage = usage;
+        stats.cpu.temperature = temperature;
+        stats.cpu.per_core_usage = vec![usage, usage / 2.0];
+        stats
+    }
+
+    fn sidecar(timestamp: i64, vcore: f32) -> SidecarData {
+        let sensor: Sensor = serde_json::from_value(serde_json::json!({
+            "id": "/lpc/nct6798d/voltage/0",
+            "hardware_name": "Nuvoton NCT6798D",
+            "name": "Vcore",
+            "value": vcore,
+            "min": null,
+            "max": null,
+            "unit": "V"
+        }))
+        .unwrap();
+        SidecarData {
+            cpu: None,
+            gpu: Vec::new(),
+            sensors: vec![sensor],
+            timestamp,
+            error: None,
+        }
+    }
+
+    fn find<'a>(entries: &'a [SummaryEntry], id: &str) -> &'a SummaryEntry {
+        entries.iter().find(|e| e.id == id).unwrap()
+    }
+
+    #[test]
+    fn test_stats_aggregates() {
+        let tracker = SensorSummaryTracker::new();
+        tracker.record(&stats(1000, 10.0, Some(50.0)), None);
+        tracker.record(&stats(2000, 30.0, Some(70.0)), None);
+        tracker.record(&stats(3000, 20.0, None), None);
+
+        let summary = tracker.summary();
+        assert_eq!(summary.updated, Some(3000));
+
+        let usage = find(&summary.stats, "cpu.usage");
+        assert_eq!(usage.unit, "%");
+        assert_eq!((usage.min, usage.max, usage.average), (10.0, 30.0, 20.0));
+        assert_eq!(usage.current, Some(20.0));
+        assert_eq!(find(&summary.stats, "cpu.per_core_usage.1").max, 15.0);
+
+        // Missing from the latest sample: no current value, aggregates kept
+        let temperature = find(&summary.stats, "cpu.temperature");
+        assert_eq!(temperature.current, None);
+        assert_eq!(temperature.samples, 2);
+        assert_eq!(temperature.average, 60.0);
+
+        assert!(summary
+            .stats
+            .iter()
+            .all(|e| !e.id.starts_with("system_info")
+                && !e.id.starts_with("freshness")
+                && e.id != "timestamp"));
+    }
+
+    #[test]
+    fn test_nested_gpu_fields() {
+        let tracker = SensorSummaryTracker::new();
+        let mut sample = stats(1000, 5.0, None);
+        sample.gpu = Some(GpuStats {
+            power: Some(120.5),
+            ..Default::default()
+        });
+        tracker.record(&sample, None);
+
+        let summary = tracker.summary();
+        let power = find(&summary.stats, "gpu.power");
+        assert_eq!(power.unit, "W");
+        assert_eq!(power.current, Some(120.5));
+    }
+
+    #[test]
+    fn test_sidecar_sample_counted_once() {
+        let tracker = SensorSummaryTracker::new();
+        let first = sidecar(1, 1.2);
+        tracker.record(&stats(1000, 0.0, None), Some(&first));
+        tracker.record(&stats(2000, 0.0, None), Some(&first));
+        tracker.record(&stats(3000, 0.0, None), Some(&sidecar(2, 1.4)));
+
+        let summary = tracker.summary();
+        let vcore = &summary.sensors[0];
+        assert_eq!(vcore.name, "Nuvoton NCT6798D / Vcore");
+        assert_eq!(vcore.unit, "V");
+        assert_eq!(vcore.samples, 2);
+        assert!((vcore.average - 1.3).abs() < 1e-6);
+        assert!((vcore.max - 1.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_reset() {
+        let tracker = SensorSummaryTracker::new();
+        tracker.record(&stats(1000, 90.0, None), Some(&sidecar(1, 1.2)));
+        tracker.reset();
+
+        let summary = tracker.summary();
+        assert!(summary.stats.is_empty() && summary.sensors.is_empty());
+        assert_eq!(summary.updated, None);
+
+        tracker.record(&stats(2000, 10.0, None), None);
+        assert_eq!(find(&tracker.summary().stats, "cpu.usage").max, 10.0);
+    }
+}
@@ -0,0 +1,503 @@
+//! Process watchdog
+//!
+//! Checks user rules against the process list after every refresh: a
+//! process that must be running, one that burns too much CPU for too long or
+//! one that holds too much memory. Like alerts, a broken rule is reported
+//! once when it starts and once when it's over. Rules with `restart`
+//! relaunch their process with the command line and working directory it
+//! last ran with, at most `MAX_RESTARTS_PER_HOUR` times so a crash loop
+//! doesn't keep spinning.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use crate::models::{
+    WatchdogCondition, WatchdogEvent, WatchdogRestart, WatchdogRule, WatchedProcess,
+};
+
+/// CREATE_NO_WINDOW - don't flash a console window on Windows
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Relaunches of one rule allowed within an hour
+pub const MAX_RESTARTS_PER_HOUR: usize = 5;
+
+const HOUR_MS: u64 = 3_600_000;
+
+/// Check that watchdog rules are complete and their ids unique
+pub fn validate_watchdogs(rules: &[WatchdogRule]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for rule in rules {
+        if rule.id.trim().is_empty() {
+            return Err("watchdog rule ids must not be empty".to_string());
+        }
+        if !ids.insert(rule.id.as_str()) {
+            return Err(format!("Duplicate watchdog rule id {:?}", rule.id));
+        }
+        if rule.pattern.trim().is_empty() {
+            return Err(format!(
+                "Watchdog rule {:?}: pattern must not be empty",
+                rule.id
+            ));
+        }
+        if rule
+            .max_cpu
+            .is_some_and(|cpu| !(cpu.is_finite() && cpu > 0.0))
+        {
+            return Err(format!(
+                "Watchdog rule {:?}: max_cpu must be greater than 0",
+                rule.id
+            ));
+        }
+        if rule.max_memory == Some(0) {
+            return Err(format!(
+                "Watchdog rule {:?}: max_memory must be greater than 0",
+                rule.id
+            ));
+        }
+        if !rule.must_run && !rule.restart && rule.max_cpu.is_none() && rule.max_memory.is_none() {
+            return Err(format!(
+                "Watchdog rule {:?} doesn't check anything",
+                rule.id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// A command line to relaunch an exited process with
+#[derive(Debug, Clone, PartialEq)]
+pub struct LaunchRequest {
+    pub rule_id: String,
+    pub command: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub attempt: usize, // Restarts of the rule within the last hour, this one included
+}
+
+/// Something a refresh changed, to be emitted as an event
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchdogTransition {
+    Triggered(WatchdogEvent),
+    Cleared(WatchdogEvent),
+    Restart(LaunchRequest),
+}
+
+/// Evaluation state of one rule
+#[derive(Debug, Default)]
+struct RuleState {
+    pattern: String, // Pattern the state belongs to - reset when edited
+    was_running: bool,
+    last_launch: Option<(Vec<String>, Option<PathBuf>)>, // Of the last matching process
+    cpu_since: HashMap<u32, u64>,                        // First refresh of each PID's CPU breach
+    active: HashMap<(WatchdogCondition, Option<u32>), WatchdogEvent>,
+    restarts: VecDeque<u64>, // Unix ms, within the last hour
+}
+
+impl RuleState {
+    /// Trigger or clear one condition, depending on whether it's broken now
+    fn update(
+        &mut self,
+        key: (WatchdogCondition, Option<u32>),
+        broken: bool,
+        event: impl FnOnce() -> WatchdogEvent,
+        now: u64,
+        transitions: &mut Vec<WatchdogTransition>,
+    ) {
+        match (broken, self.active.contains_key(&key)) {
+            (true, false) => {
+                let event = event();
+                self.active.insert(key, event.clone());
+                transitions.push(WatchdogTransition::Triggered(event));
+            }
+            (false, true) => self.clear(key, now, transitions),
+            _ => {}
+        }
+    }
+
+    fn clear(
+        &mut self,
+        key: (WatchdogCondition, Option<u32>),
+        now: u64,
+        transitions: &mut Vec<WatchdogTransition>,
+    ) {
+        if let Some(mut cleared) = self.active.remove(&key) {
+            cleared.cleared_at = Some(now);
+            transitions.push(WatchdogTransition::Cleared(cleared));
+        }
+    }
+}
+
+/// Evaluates watchdog rules against the process list
+#[derive(Default)]
+pub struct Watchdog {
+    states: Mutex<HashMap<String, RuleState>>,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run every enabled rule against a process refresh, returning what
+    /// triggered, cleared or needs a restart
+    /// Rules that were removed, disabled or edited lose their state silently.
+    pub fn evaluate(
+        &self,
+        rules: &[WatchdogRule],
+        processes: &[WatchedProcess],
+        now: u64,
+    ) -> Vec<WatchdogTransition> {
+        let Ok(mut states) = self.states.lock() else {
+            return Vec::new();
+        };
+        states.retain(|id, state| {
+            rules
+                .iter()
+                .any(|r| r.enabled && &r.id == id && r.pattern == state.pattern)
+        });
+
+        let mut transitions = Vec::new();
+        for rule in rules.iter().filter(|r| r.enabled) {
+            let state = states.entry(rule.id.clone()).or_insert_with(|| RuleState {
+                pattern: rule.pattern.clone(),
+                ..Default::default()
+            });
+            let pattern = rule.pattern.to_lowercase();
+            let matching: Vec<&WatchedProcess> =
+                processes.iter().filter(|p| matches(p, &pattern)).collect();
+            let pids: HashSet<u32> = matching.iter().map(|p| p.pid).collect();
+
+            // Exited processes can't be too busy anymore
+            let gone: Vec<_> = state
+                .active
+                .keys()
+                .filter(|(_, pid)| pid.is_some_and(|pid| !pids.contains(&pid)))
+                .copied()
+                .collect();
+            for key in gone {
+                state.clear(key, now, &mut transitions);
+            }
+            state.cpu_since.retain(|pid, _| pids.contains(pid));
+
+            if rule.must_run {
+                let event = || WatchdogEvent {
+                    rule_id: rule.id.clone(),
+                    condition: WatchdogCondition::NotRunning,
+                    pid: None,
+                    name: None,
+                    value: 0.0,
+                    limit: 0.0,
+                    triggered_at: now,
+                    cleared_at: None,
+                };
+                let key = (WatchdogCondition::NotRunning, None);
+                state.update(key, matching.is_empty(), event, now, &mut transitions);
+            }
+
+            for process in &matching {
+                let event = |condition, value, limit| WatchdogEvent {
+                    rule_id: rule.id.clone(),
+                    condition,
+                    pid: Some(process.pid),
+                    name: Some(process.name.clone()),
+                    value,
+                    limit,
+                    triggered_at: now,
+                    cleared_at: None,
+                };
+                if let Some(limit) = rule.max_cpu {
+                    let over = process.cpu_usage > limit;
+                    let held = over && {
+                        let since = *state.cpu_since.entry(process.pid).or_insert(now);
+                        now.saturating_sub(since) >= rule.cpu_for_secs.saturating_mul(1000)
+                    };
+                    if !over {
+                        state.cpu_since.remove(&process.pid);
+                    }
+                    // Once triggered it stays so until the process calms down
+                    let key = (WatchdogCondition::HighCpu, Some(process.pid));
+                    let broken = held || (over && state.active.contains_key(&key));
+                    let value = process.cpu_usage as f64;
+                    let make = || event(WatchdogCondition::HighCpu, value, limit as f64);
+                    state.update(key, broken, make, now, &mut transitions);
+                }
+                if let Some(limit) = rule.max_memory {
+                    let key = (WatchdogCondition::HighMemory, Some(process.pid));
+                    let value = process.memory as f64;
+                    let make = || event(WatchdogCondition::HighMemory, value, limit as f64);
+                    state.update(key, process.memory > limit, make, now, &mut transitions);
+                }
+            }
+
+            // Relaunch once the last matching process is gone
+            if let Some(process) = matching.iter().find(|p| !p.cmd.is_empty()) {
+                state.last_launch = Some((process.cmd.clone(), process.cwd.clone()));
+            }
+            if rule.restart && state.was_running && matching.is_empty() {
+                state.restarts.retain(|&t| now.saturating_sub(t) < HOUR_MS);
+                if let Some((command, cwd)) = &state.last_launch {
+                    if state.restarts.len() < MAX_RESTARTS_PER_HOUR {
+                        state.restarts.push_back(now);
+                        transitions.push(WatchdogTransition::Restart(LaunchRequest {
+                            rule_id: rule.id.clone(),
+                            command: command.clone(),
+                            cwd: cwd.clone(),
+                            attempt: state.restarts.len(),
+                        }));
+                    }
+                }
+            }
+            state.was_running = !matching.is_empty();
+        }
+        transitions
+    }
+
+    /// Rules that are broken right now, oldest first
+    pub fn active(&self) -> Vec<WatchdogEvent> {
+        let Ok(states) = self.states.lock() else {
+            return Vec::new();
+        };
+        let mut active: Vec<WatchdogEvent> = states
+            .values()
+            .flat_map(|s| s.active.values().cloned())
+            .collect();
+        active.sort_by(|a, b| {
+            a.triggered_at
+                .cmp(&b.triggered_at)
+                .then(a.rule_id.cmp(&b.rule_id))
+        });
+        active
+    }
+}
+
+/// Whether the lowercase `pattern` is part of the process name or command line
+fn matches(process: &WatchedProcess, pattern: &str) -> bool {
+    process.name.to_lowercase().contains(pattern)
+        || process.cmd.join(" ").to_lowercase().contains(pattern)
+}
+
+/// Start the process of a launch request, detached from the app
+/// The child is reaped on a thread of its own once it exits.
+pub fn relaunch(request: &LaunchRequest, now: u64) -> WatchdogRestart {
+    let (pid, error) = match spawn(request) {
+        Ok(pid) => (Some(pid), None),
+        Err(e) => (None, Some(e)),
+    };
+    WatchdogRestart {
+        rule_id: request.rule_id.clone(),
+        command: request.command.clone(),
+        pid,
+        error,
+        attempt: request.attempt,
+        restarted_at: now,
+    }
+}
+
+fn spawn(request: &LaunchRequest) -> Result<u32, String> {
+    let (program, args) = request
+        .command
+        .split_first()
+        .ok_or("No command line to restart with")?;
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(cwd) = &request.cwd {
+        command.current_dir(cwd);
+    }
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", program, e))?;
+    let pid = child.id();
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::monitor::fixtures::process;
+
+    const GB: u64 = 1024 * 1024 * 1024;
+
+    fn conditions(transitions: &[WatchdogTransition]) -> Vec<(&str, WatchdogCondition)> {
+        transitions
+            .iter()
+            .filter_map(|t| match t {
+                WatchdogTransition::Triggered(e) => Some(("triggered", e.condition)),
+                WatchdogTransition::Cleared(e) => Some(("cleared", e.condition)),
+                WatchdogTransition::Restart(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cpu_and_memory_limits() {
+        let watchdog = Watchdog::new();
+        let rules = vec![WatchdogRule {
+            id: "api".to_string(),
+            pattern: "SERVER.JS".to_string(),
+            max_cpu: Some(80.0),
+            max_memory: Some(4 * GB),
+            ..Default::default()
+        }];
+        let busy = |cpu, memory| {
+            vec![
+                process(10, "/usr/bin/node server.js", cpu, memory),
+                process(11, "/usr/bin/node build.js", 100.0, 8 * GB),
+            ]
+        };
+
+        // Busy for just under the default 2 minutes, then a dip resets the clock
+        for s in 0..120 {
+            assert!(watchdog
+                .evaluate(&rules, &busy(95.0, GB), s * 1000)
+                .is_empty());
+        }
+        assert!(watchdog
+            .evaluate(&rules, &busy(20.0, GB), 120_000)
+            .is_empty());
+        for s in 121..241 {
+            assert!(watchdog
+                .evaluate(&rules, &busy(95.0, GB), s * 1000)
+                .is_empty());
+        }
+        let t = watchdog.evaluate(&rules, &busy(95.0, 5 * GB), 241_000);
+        assert_eq!(
+            conditions(&t),
+            vec![
+                ("triggered", WatchdogCondition::HighCpu),
+                ("triggered", WatchdogCondition::HighMemory)
+            ]
+        );
+        assert_eq!(watchdog.active().len(), 2);
+        assert!(watchdog.active().iter().all(|e| e.pid == Some(10)));
+
+        // Exiting clears whatever the process had broken
+        let t = watchdog.evaluate(&rules, &busy(95.0, 5 * GB)[1..], 242_000);
+        assert_eq!(conditions(&t).len(), 2);
+        assert!(watchdog.active().is_empty());
+    }
+
+    #[test]
+    fn test_must_run_and_restart() {
+        let watchdog = Watchdog::new();
+        let rules = vec![WatchdogRule {
+            id: "vite".to_string(),
+            pattern: "vite".to_string(),
+            must_run: true,
+            restart: true,
+            ..Default::default()
+        }];
+        let running = vec![process(
+            20,
+            "/usr/bin/node node_modules/.bin/vite --port 5173",
+            1.0,
+            GB,
+        )];
+
+        // Not running from the start: reported, but nothing to restart with
+        let t = watchdog.evaluate(&rules, &[], 0);
+        assert_eq!(
+            conditions(&t),
+            vec![("triggered", WatchdogCondition::NotRunning)]
+        );
+        let t = watchdog.evaluate(&rules, &running, 1000);
+        assert_eq!(
+            conditions(&t),
+            vec![("cleared", WatchdogCondition::NotRunning)]
+        );
+
+        // Exiting relaunches it with its last command line and directory
+        let t = watchdog.evaluate(&rules, &[], 2000);
+        assert_eq!(
+            conditions(&t),
+            vec![("triggered", WatchdogCondition::NotRunning)]
+        );
+        let Some(WatchdogTransition::Restart(request)) = t.last() else {
+            panic!("no restart in {:?}", t);
+        };
+        assert_eq!(request.command, running[0].cmd);
+        assert_eq!(request.cwd, Some(PathBuf::from("/home/dev/api")));
+        assert_eq!(request.attempt, 1);
+
+        // A crash loop is only restarted so many times an hour
+        let mut restarts = 1;
+        for i in 0..20 {
+            watchdog.evaluate(&rules, &running, 3000 + i * 2000);
+            let t = watchdog.evaluate(&rules, &[], 4000 + i * 2000);
+            restarts += t
+                .iter()
+                .filter(|t| matches!(t, WatchdogTransition::Restart(_)))
+                .count();
+        }
+        assert_eq!(restarts, MAX_RESTARTS_PER_HOUR);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rule_matches_process_from_refresh() {
+        let mut monitor = crate::services::SystemMonitor::new();
+        let marker = format!("pulse-watchdog-{}", std::process::id());
+        // Started after the monitor, matched by an argument only
+        let mut child = Command::new("/bin/sh")
+            .args(["-c", "sleep 30; true", &marker])
+            .spawn()
+            .unwrap();
+        let rules = vec![WatchdogRule {
+            id: "marker".to_string(),
+            pattern: marker.to_uppercase(),
+            max_memory: Some(1),
+            ..Default::default()
+        }];
+
+        let watchdog = Watchdog::new();
+        let mut transitions = Vec::new();
+        monitor.refresh_supervised(&watchdog, &rules, |t, _| transitions.push(t));
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert!(matches!(
+            &transitions[..],
+            [WatchdogTransition::Triggered(event)]
+                if event.condition == WatchdogCondition::HighMemory
+                    && event.pid == Some(child.id())
+        ));
+    }
+
+    #[test]
+    fn test_validate_watchdogs() {
+        let rule = WatchdogRule {
+            id: "db".to_string(),
+            pattern: "postgres".to_string(),
+            must_run: true,
+            ..Default::default()
+        };
+        assert!(validate_watchdogs(std::slice::from_ref(&rule)).is_ok());
+        assert!(validate_watchdogs(&[rule.clone(), rule.clone()]).is_err());
+
+        let idle = WatchdogRule {
+            must_run: false,
+            ..rule.clone()
+        };
+        assert!(validate_watchdogs(&[idle]).is_err());
+        let no_cpu = WatchdogRule {
+            max_cpu: Some(0.0),
+            ..rule
+        };
+        assert!(validate_watchdogs(&[no_cpu]).is_err());
+    }
+}
@@ -71,7 +71,7 @@ export function SidecarWarning({ status, message, show, onDismiss }: SidecarWarn
           {/* Help text for admin issue */}
           {isAdminIssue && (
             <p className="mt-2 text-xs opacity-75">
-              Close the app and right-click → "Run as administrator" to enable CPU/GPU temperature monitoring.
+              Restart the sensor sidecar and accept the Windows administrator prompt to enable CPU/GPU temperature monitoring.
             </p>
           )}
         </motion.div>
@@ -1,6 +1,7 @@
-import { useState, useCallback, useMemo } from "react";
+import { useState, useCallback, useEffect, useMemo } from "react";
 import { useTauriEvent } from "@/hooks/useTauriEvent";
-import type { SystemStats } from "@/types/stats";
+import { getHistory } from "@/lib/tauri";
+import type { MetricSeries, SystemStats } from "@/types/stats";
 
 /** Data point for time-series charts */
 export interface StatsHistoryPoint {
@@ -13,6 +14,34 @@ export interface StatsHistoryPoint {
 /** Maximum number of data points to keep in history (60 seconds at 1 point/sec) */
 const MAX_HISTORY_LENGTH = 60;
 
+/** Backend metrics used to backfill the chart history */
+const HISTORY_METRICS = ["cpu.usage", "ram.usage_percent", "gpu.usage"];
+
+/**
+ * Convert backend history series into chart points
+ * Points are keyed by the CPU usage timestamps, which every sample has
+ */
+function seriesToHistory(series: MetricSeries[]): StatsHistoryPoint[] {
+  const valuesOf = (metric: string) =>
+    new Map(
+      (series.find((s) => s.metric === metric)?.points ?? []).map((p) => [
+        p.timestamp,
+        p.value,
+      ])
+    );
+  const ram = valuesOf("ram.usage_percent");
+  const gpu = valuesOf("gpu.usage");
+
+  return (series.find((s) => s.metric === "cpu.usage")?.points ?? []).map(
+    (p) => ({
+      timestamp: p.timestamp,
+      cpuUsage: p.value,
+      ramUsage: ram.get(p.timestamp) ?? 0,
+      gpuUsage: gpu.get(p.timestamp) ?? 0,
+    })
+  );
+}
+
 /** Return type for useSystemStats hook */
 export interface UseSystemStatsReturn {
   /** Current system stats (latest) */
@@ -28,7 +57,7 @@ export interface UseSystemStatsReturn {
 /**
  * Hook to manage system stats from Tauri backend
  * - Listens to "system-stats" events
- * - Maintains history for charts
+ * - Maintains history for charts, backfilled from the backend on mount
  * - Provides connection status
  */
 export function useSystemStats(): UseSystemStatsReturn {
@@ -52,6 +81,11 @@ export function useSystemStats(): UseSystemStatsReturn {
     };
 
     setHistory((prev) => {
+      // Time went backwards (session playback started or seeked): start over
+      const last = prev[prev.length - 1];
+      if (last && newPoint.timestamp < last.timestamp) {
+        return [newPoint];
+      }
       const updated = [...prev, newPoint];
       // Keep only the last MAX_HISTORY_LENGTH points
       if (updated.length > MAX_HISTORY_LENGTH) {
@@ -64,6 +98,33 @@ export function useSystemStats(): UseSystemStatsReturn {
   // Listen to Tauri events
   useTauriEvent<SystemStats>("system-stats", handleStats);
 
+  // Backfill charts with what the backend recorded before this window opened
+  useEffect(() => {
+    let mounted = true;
+
+    getHistory(HISTORY_METRICS, Date.now() - MAX_HISTORY_LENGTH * 1000)
+      .then((series) => {
+        if (!mounted) return;
+        const backfill = seriesToHistory(series);
+        setHistory((prev) => {
+          // Keep live points that arrived while the query was running
+          const last = backfill[backfill.length - 1]?.timestamp ?? 0;
+          const merged = [
+            ...backfill,
+            ...prev.filter((p) => p.timestamp > last),
+          ];
+          return merged.slice(-MAX_HISTORY_LENGTH);
+        });
+      })
+      .catch((err) => {
+        console.error("Failed to load history:", err);
+      });
+
+    return () => {
+      mounted = false;
+    };
+  }, []);
+
   // Memoize return value to prevent unnecessary re-renders
   const result = useMemo(
     () => ({
@@ -80,14 +141,15 @@ export function useSystemStats(): UseSystemStatsReturn {
 
 /**
  * Format history data for Recharts
- * Converts timestamp to relative time (seconds ago)
+ * Converts timestamp to relative time (seconds before the newest point,
+ * so played-back sessions with old timestamps render the same way)
  */
 export function formatHistoryForChart(
   history: StatsHistoryPoint[]
 ): Array<StatsHistoryPoint & { time: string }> {
   if (history.length === 0) return [];
 
-  const now = Date.now();
+  const now = history[history.length - 1].timestamp;
   return history.map((point) => ({
     ...point,
     time: `${Math.round((now - point.timestamp) / 1000)}s`,
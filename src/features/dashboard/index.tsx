@@ -3,6 +3,8 @@ import { motion } from "framer-motion";
 import { Activity, Wifi, WifiOff } from "lucide-react";
 import { useSystemStats } from "./hooks/useSystemStats";
 import { useSidecarStatus } from "./hooks/useSidecarStatus";
+import { useTauriEvent } from "@/hooks/useTauriEvent";
+import type { DashboardCard } from "@/types/stats";
 import {
   CpuCard,
   RamCard,
@@ -26,6 +28,17 @@ export function Dashboard() {
     setWarningDismissed(true);
   }, []);
 
+  // A clicked desktop notification brings its card into view
+  const handleFocusCard = useCallback((card: DashboardCard) => {
+    if (card === "sidecar") {
+      setWarningDismissed(false);
+    }
+    document
+      .getElementById(`card-${card}`)
+      ?.scrollIntoView({ behavior: "smooth", block: "center" });
+  }, []);
+  useTauriEvent<DashboardCard>("focus-card", handleFocusCard);
+
   return (
     <div className="space-y-4 sm:space-y-6 p-2 sm:p-4 scrollbar-thin">
       {/* Header with Connection Status */}
@@ -52,22 +65,30 @@ export function Dashboard() {
       </motion.div>
 
       {/* Sidecar Warning Banner */}
-      <SidecarWarning
-        status={sidecarStatus}
-        message={sidecarMessage}
-        show={showWarning && !warningDismissed}
-        onDismiss={handleDismissWarning}
-      />
+      <div id="card-sidecar">
+        <SidecarWarning
+          status={sidecarStatus}
+          message={sidecarMessage}
+          show={showWarning && !warningDismissed}
+          onDismiss={handleDismissWarning}
+        />
+      </div>
 
       {/* Top Row: Hardware Cards - Responsive grid */}
       <div className="grid gap-3 sm:gap-4 grid-cols-1 sm:grid-cols-2 xl:grid-cols-3">
-        <CpuCard stats={stats?.cpu ?? null} history={history} />
-        <RamCard stats={stats?.ram ?? null} history={history} />
-        <GpuCard
-          stats={stats?.gpu}
-          history={history}
-          isAvailable={stats?.gpu !== undefined}
-        />
+        <div id="card-cpu">
+          <CpuCard stats={stats?.cpu ?? null} history={history} />
+        </div>
+        <div id="card-ram">
+          <RamCard stats={stats?.ram ?? null} history={history} />
+        </div>
+        <div id="card-gpu">
+          <GpuCard
+            stats={stats?.gpu}
+            history={history}
+            isAvailable={stats?.gpu !== undefined}
+          />
+        </div>
       </div>
 
       {/* Middle Row: Performance Chart */}
@@ -1,5 +1,23 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { SystemStats } from "@/types/stats";
+import type {
+  AlertEvent,
+  AnomalyEvent,
+  ExportFormat,
+  LeakSuspect,
+  MetricSeries,
+  PlaybackStatus,
+  ReportFormat,
+  Sensor,
+  SensorSummary,
+  SessionComparison,
+  Settings,
+  SidecarLogEntry,
+  SidecarLogLevel,
+  SidecarStatusPayload,
+  SystemStats,
+  TimeRange,
+  WatchdogEvent,
+} from "@/types/stats";
 
 /**
  * Type-safe wrapper for Tauri invoke commands
@@ -8,6 +26,47 @@ export async function getSystemStats(): Promise<SystemStats> {
   return invoke<SystemStats>("get_system_stats");
 }
 
+/**
+ * Get recorded metric history (all metrics if `metrics` is empty)
+ * `from`/`to` are Unix ms, `step` averages points into buckets of that many ms
+ * Ranges older than the last hour come from the on-disk store's rollups
+ */
+export async function getHistory(
+  metrics: string[],
+  from?: number,
+  to?: number,
+  step?: number
+): Promise<MetricSeries[]> {
+  return invoke<MetricSeries[]>("get_history", { metrics, from, to, step });
+}
+
+/**
+ * Write metrics (all if `metrics` is empty) within `range` to a file
+ * Column headers carry units, e.g. "cpu.temperature [°C]"
+ * Returns the number of rows written
+ */
+export async function exportMetrics(
+  range: TimeRange,
+  metrics: string[],
+  format: ExportFormat,
+  path: string
+): Promise<number> {
+  return invoke<number>("export_metrics", { range, metrics, format, path });
+}
+
+export async function getAllSensors(): Promise<Sensor[]> {
+  return invoke<Sensor[]>("get_all_sensors");
+}
+
+/** Current / min / max / average per stats field and sensor since the last reset */
+export async function getSensorSummary(): Promise<SensorSummary> {
+  return invoke<SensorSummary>("get_sensor_summary");
+}
+
+export async function resetSensorSummary(): Promise<SensorSummary> {
+  return invoke<SensorSummary>("reset_sensor_summary");
+}
+
 export async function toggleMiniMode(): Promise<void> {
   return invoke("toggle_mini_mode");
 }
@@ -19,3 +78,147 @@ export async function showMainWindow(): Promise<void> {
 export async function hideMiniWindow(): Promise<void> {
   return invoke("hide_mini_window");
 }
+
+export async function startSidecar(): Promise<SidecarStatusPayload> {
+  return invoke<SidecarStatusPayload>("start_sidecar");
+}
+
+export async function stopSidecar(): Promise<SidecarStatusPayload> {
+  return invoke<SidecarStatusPayload>("stop_sidecar");
+}
+
+export async function restartSidecar(): Promise<SidecarStatusPayload> {
+  return invoke<SidecarStatusPayload>("restart_sidecar");
+}
+
+export async function getSidecarLogs(
+  level?: SidecarLogLevel,
+  limit?: number
+): Promise<SidecarLogEntry[]> {
+  return invoke<SidecarLogEntry[]>("get_sidecar_logs", { level, limit });
+}
+
+export async function startSidecarRecording(path?: string): Promise<string> {
+  return invoke<string>("start_sidecar_recording", { path });
+}
+
+export async function stopSidecarRecording(): Promise<string | null> {
+  return invoke<string | null>("stop_sidecar_recording");
+}
+
+export async function replaySidecarRecording(
+  path: string,
+  speed?: number
+): Promise<SidecarStatusPayload> {
+  return invoke<SidecarStatusPayload>("replay_sidecar_recording", { path, speed });
+}
+
+/**
+ * Record every emitted SystemStats to a session file
+ * Without `path` it goes to the app data dir; returns the file path
+ */
+export async function startSessionRecording(path?: string): Promise<string> {
+  return invoke<string>("start_session_recording", { path });
+}
+
+export async function stopSessionRecording(): Promise<string | null> {
+  return invoke<string | null>("stop_session_recording");
+}
+
+/**
+ * Play a session on the "system-stats" event instead of live data
+ * `speed` scales the original pacing, e.g. 1, 4 or 16
+ */
+export async function startSessionPlayback(
+  path: string,
+  speed?: number
+): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>("start_session_playback", { path, speed });
+}
+
+export async function seekSessionPlayback(
+  positionMs: number
+): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>("seek_session_playback", { positionMs });
+}
+
+export async function setSessionPlaybackSpeed(
+  speed: number
+): Promise<PlaybackStatus> {
+  return invoke<PlaybackStatus>("set_session_playback_speed", { speed });
+}
+
+export async function stopSessionPlayback(): Promise<void> {
+  return invoke<void>("stop_session_playback");
+}
+
+export async function getSessionPlayback(): Promise<PlaybackStatus | null> {
+  return invoke<PlaybackStatus | null>("get_session_playback");
+}
+
+/**
+ * Compare session `a` (before) with session `b` (after)
+ * Empty `metrics` compares every whole-device metric; with `reportPath` a
+ * Markdown or HTML report is written too (format from the extension by default)
+ */
+export async function compareSessions(
+  a: string,
+  b: string,
+  metrics: string[] = [],
+  reportPath?: string,
+  format?: ReportFormat
+): Promise<SessionComparison> {
+  return invoke<SessionComparison>("compare_sessions", {
+    a,
+    b,
+    metrics,
+    reportPath,
+    format,
+  });
+}
+
+export async function getSettings(): Promise<Settings> {
+  return invoke<Settings>("get_settings");
+}
+
+/** Alerts that fired and haven't resolved yet, unacknowledged and most severe first */
+export async function getActiveAlerts(): Promise<AlertEvent[]> {
+  return invoke<AlertEvent[]>("get_active_alerts");
+}
+
+/**
+ * Alerts that were active at some point within `from`..`to` (Unix ms),
+ * optionally of one rule, newest first (200 unless `limit` is given)
+ */
+export async function getAlertHistory(
+  from?: number,
+  to?: number,
+  ruleId?: string,
+  limit?: number
+): Promise<AlertEvent[]> {
+  return invoke<AlertEvent[]>("get_alert_history", { from, to, ruleId, limit });
+}
+
+/** Silence the active alert of a rule */
+export async function acknowledgeAlert(ruleId: string): Promise<AlertEvent> {
+  return invoke<AlertEvent>("acknowledge_alert", { ruleId });
+}
+
+/** Anomalies found since app start, newest first */
+export async function getRecentAnomalies(): Promise<AnomalyEvent[]> {
+  return invoke<AnomalyEvent[]>("get_recent_anomalies");
+}
+
+/** Processes whose memory currently keeps growing, fastest first */
+export async function getLeakSuspects(): Promise<LeakSuspect[]> {
+  return invoke<LeakSuspect[]>("get_leak_suspects");
+}
+
+/** Watchdog rules that are broken right now, oldest first */
+export async function getWatchdogEvents(): Promise<WatchdogEvent[]> {
+  return invoke<WatchdogEvent[]>("get_watchdog_events");
+}
+
+export async function updateSettings(settings: Settings): Promise<Settings> {
+  return invoke<Settings>("update_settings", { settings });
+}
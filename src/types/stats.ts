@@ -10,6 +10,12 @@ export interface CpuStats {
   temperature?: number; // Celsius (from LibreHardwareMonitor sidecar)
   core_temperatures?: number[]; // Per-core temps (from sidecar)
   power?: number; // Watts (from sidecar)
+  package_temperature?: number; // Celsius (from sidecar)
+  max_temperature?: number; // Hottest core, Celsius (from sidecar)
+  tj_max?: number; // Throttle temperature, Celsius
+  thermal_headroom?: number; // TjMax - package temperature
+  core_thermal_headroom?: number[]; // TjMax - core temperature, per core
+  core_powers?: number[]; // Per-core Watts (from sidecar)
 }
 
 export interface RamStats {
@@ -30,6 +36,8 @@ export interface GpuStats {
   power?: number; // Watts (from sidecar)
   core_clock?: number; // MHz (from sidecar)
   memory_clock?: number; // MHz (from sidecar)
+  load?: number; // 0-100% core load (from sidecar)
+  vendor?: string; // "NVIDIA", "AMD" or "Intel"
 }
 
 export interface SystemInfo {
@@ -52,6 +60,53 @@ export interface ProcessInfo {
   memory: number; // bytes
 }
 
+// Full sensor tree from the sidecar - mirrors Rust Sensor
+
+export interface Sensor {
+  id: string; // Stable identifier, e.g. "/lpc/nct6798d/voltage/0"
+  hardware_id: string;
+  hardware_name: string;
+  hardware_type: string; // e.g. "SuperIO", "Storage"
+  sensor_type: string; // e.g. "Voltage", "Fan"
+  name: string;
+  value?: number;
+  min?: number;
+  max?: number;
+  unit: string; // e.g. "V", "RPM", "°C"
+}
+
+// Running aggregates since the last reset - mirrors Rust SensorSummary
+
+export interface SummaryEntry {
+  id: string; // Stats field path (e.g. "cpu.usage") or sensor id
+  name: string;
+  unit: string; // "" when unitless
+  current: number | null; // null when the latest sample didn't have it
+  min: number;
+  max: number;
+  average: number;
+  samples: number;
+}
+
+export interface SensorSummary {
+  since: number; // Unix ms of the last reset (or app start)
+  updated: number | null; // Unix ms of the newest sample
+  stats: SummaryEntry[]; // SystemStats fields
+  sensors: SummaryEntry[]; // Sidecar sensors
+}
+
+export interface SourceFreshness {
+  timestamp?: number; // Unix ms of the last sample (unset = never received)
+  age_ms?: number; // Age of that sample when the stats were built
+  stale: boolean; // Older than the configured threshold
+}
+
+export interface DataFreshness {
+  sysinfo: SourceFreshness;
+  sidecar: SourceFreshness;
+  stale_fields: string[]; // e.g. "cpu.temperature" - dropped because stale
+}
+
 export interface SystemStats {
   cpu: CpuStats;
   ram: RamStats;
@@ -59,6 +114,280 @@ export interface SystemStats {
   system_info: SystemInfo;
   processes: ProcessInfo[];
   timestamp: number;
+  freshness: DataFreshness;
+}
+
+// Metric history - mirrors Rust HistoryPoint / MetricSeries
+
+export interface HistoryPoint {
+  timestamp: number; // Unix ms (bucket start when aggregated)
+  value: number; // Sample value, or the bucket average
+  min?: number; // Lowest value in the bucket (aggregated points only)
+  max?: number; // Highest value in the bucket (aggregated points only)
+}
+
+export interface MetricSeries {
+  metric: string; // e.g. "cpu.usage", "gpu.temperature", "cpu.core_usage.3"
+  points: HistoryPoint[];
+}
+
+export interface TimeRange {
+  from?: number; // Unix ms, inclusive (omit for the oldest available)
+  to?: number; // Unix ms, inclusive (omit for the newest available)
+  step?: number; // Average into buckets of this many ms
+}
+
+// csv: HWiNFO-style Date/Time + one column per metric, ndjson/parquet: same columns
+export type ExportFormat = "csv" | "ndjson" | "parquet";
+
+// Session playback - mirrors Rust PlaybackStatus, emitted as "session-playback"
+// (null once playback stops and live data is back)
+
+export interface PlaybackStatus {
+  path: string;
+  position_ms: number; // Offset of the playhead from the session start
+  duration_ms: number; // Offset of the last sample
+  speed: number; // 1 = real time
+  samples: number;
+  started_at: number; // Unix ms of the first sample
+  finished: boolean; // The last sample was emitted
+}
+
+// Session comparison - mirrors Rust SessionComparison (A = before, B = after)
+
+export interface SessionInfo {
+  path: string;
+  started_at: number; // Unix ms of the first sample
+  duration_ms: number;
+  samples: number;
+}
+
+export interface MetricSummary {
+  min: number;
+  avg: number;
+  p95: number;
+  max: number;
+  samples: number;
+}
+
+// B minus A
+export interface MetricDelta {
+  min: number;
+  avg: number;
+  p95: number;
+  max: number;
+}
+
+// Welch's t-test on 10 s block means
+export interface Significance {
+  t: number;
+  p_value: number; // Two-sided
+  effect_size: number; // Cohen's d, positive when B is higher
+  significant: boolean; // p < 0.05 and at least a small effect
+}
+
+export interface OverlayPoint {
+  offset_ms: number; // Offset from each session's first sample
+  a: number | null;
+  b: number | null;
+}
+
+export interface MetricComparison {
+  metric: string;
+  unit: string;
+  a: MetricSummary | null;
+  b: MetricSummary | null;
+  delta: MetricDelta | null;
+  significance: Significance | null; // null with too little data
+  overlay: OverlayPoint[];
+}
+
+export interface SessionComparison {
+  a: SessionInfo;
+  b: SessionInfo;
+  overlay_step_ms: number;
+  metrics: MetricComparison[];
+  summary: string[]; // Human-readable findings, most important first
+}
+
+export type ReportFormat = "markdown" | "html";
+
+// LibreHardwareMonitor hardware classes - mirrors Rust HardwareClass
+export type HardwareClass =
+  | "cpu"
+  | "gpu"
+  | "memory"
+  | "motherboard"
+  | "storage"
+  | "network"
+  | "battery"
+  | "controller"
+  | "psu";
+
+// App settings - mirrors Rust Settings
+
+export interface RetentionSettings {
+  raw_hours: number; // Every 1 s sample
+  minute_days: number; // 1-minute rollups
+  hour_days: number; // 1-hour rollups
+  alert_days: number; // Resolved alerts (0 keeps them forever)
+}
+
+export interface Settings {
+  stale_threshold_ms: number; // Sidecar readings older than this are dropped
+  tj_max_override?: number; // TjMax for headroom when the CPU reports none (Celsius)
+  collector_endpoint?: string; // Socket/pipe of a running collector instead of spawning the sidecar
+  hardware_classes: HardwareClass[]; // LHM hardware the sidecar reads
+  retention: RetentionSettings; // How long the on-disk metric history is kept
+  alerts: AlertRule[]; // Threshold alerts checked against every sample
+  notifications: NotificationSettings; // Desktop notifications for alerts and sidecar failures
+  anomaly: AnomalySettings; // Flag values that are unusual for this machine (off by default)
+  leaks: LeakSettings; // Processes whose memory keeps growing
+  watchdogs: WatchdogRule[]; // Rules for processes that must keep running or stay within limits
+}
+
+export interface AnomalySettings {
+  enabled: boolean;
+  sensitivity: number; // Standard deviations from the baseline that count as unusual
+  baseline_minutes: number; // How far back the short-term baseline looks
+  metrics: string[]; // Checked for spikes and drops
+  memory_creep_percent_per_hour: number; // Steady RAM growth that is flagged as a leak
+}
+
+export interface LeakSettings {
+  enabled: boolean;
+  min_growth_mb_per_hour: number; // Steady growth below this isn't reported
+  min_duration_minutes: number; // How long a process has to keep growing before it's a suspect
+}
+
+export interface NotificationSettings {
+  enabled: boolean;
+  min_interval_secs: number; // Per alert rule (or the sidecar)
+  max_per_hour: number; // Across everything
+}
+
+// Alerts - mirrors Rust AlertRule / AlertEvent
+
+export type Severity = "info" | "warning" | "critical";
+
+// Payload of "focus-card", sent when a desktop notification is clicked
+export type DashboardCard = "cpu" | "gpu" | "ram" | "sidecar";
+
+export interface AlertRule {
+  id: string; // Unique, e.g. "cpu-hot"
+  condition: string; // "<metric> <op> <threshold> [for <duration>]", e.g. "cpu.temperature > 90 for 30s"
+  hysteresis: number; // Resolve only once the value is this far back past the threshold
+  cooldown_secs: number; // Minimum time between two firings
+  severity: Severity;
+  enabled: boolean;
+  notify: boolean; // Desktop notification when it fires (opt-in)
+  actions: AlertAction[];
+}
+
+// What an action does - commands get the alert as JSON on stdin,
+// webhooks get it POSTed and files get it appended as one JSON line
+export type ActionKind =
+  | { type: "command"; program: string; args: string[] }
+  | { type: "webhook"; url: string }
+  | { type: "file"; path: string };
+
+export type AlertAction = ActionKind & {
+  timeout_secs: number; // Per attempt
+  retries: number; // Extra attempts after a failure
+  on_resolve: boolean; // Run again when the alert resolves
+};
+
+export type ActionTrigger = "fired" | "resolved";
+
+export interface ActionOutcome {
+  action: string; // e.g. "webhook hooks.example.com"
+  trigger: ActionTrigger;
+  success: boolean;
+  attempts: number;
+  error: string | null; // Of the last failed attempt
+  duration_ms: number; // All attempts, including the waits between them
+  finished_at: number; // Unix ms
+}
+
+// Payload of "alert-fired", "alert-resolved", "alert-action" and "alert-acknowledged"
+export interface AlertEvent {
+  rule_id: string;
+  condition: string;
+  severity: Severity;
+  metric: string;
+  threshold: number;
+  value: number; // When fired, or when resolved for "alert-resolved"
+  peak: number; // Furthest past the threshold while active
+  fired_at: number; // Unix ms
+  resolved_at: number | null; // Unix ms (null while active)
+  acknowledged_at: number | null; // Unix ms, once the user silenced it
+  actions: ActionOutcome[]; // Filled in as the rule's actions finish
+}
+
+// Anomalies - mirrors Rust AnomalyEvent
+
+export type AnomalyKind = "spike" | "drop" | "creep" | "hot_for_load";
+
+// Payload of "anomaly-detected"
+export interface AnomalyEvent {
+  metric: string;
+  kind: AnomalyKind;
+  value: number;
+  expected: number; // Baseline, or where a creep started
+  score: number; // Standard deviations off, or %/hour for a creep
+  confidence: number; // 0 - 1
+  detected_at: number; // Unix ms
+}
+
+// Memory leaks - mirrors Rust LeakSuspect
+
+// Payload of "leak-suspected"
+export interface LeakSuspect {
+  pid: number;
+  name: string;
+  memory: number; // Current RSS in bytes
+  growth_per_hour: number; // Bytes, from the fitted trend
+  growing_secs: number; // How long the trend covers
+  fit: number; // r² of the trend (0 - 1)
+  exhaustion_secs: number | null; // Until available memory runs out at this rate
+  detected_at: number; // Unix ms
+}
+
+// Process watchdog - mirrors Rust WatchdogRule / WatchdogEvent / WatchdogRestart
+
+export interface WatchdogRule {
+  id: string; // Unique, e.g. "api-server"
+  pattern: string; // Case-insensitive substring of the name or command line
+  enabled: boolean;
+  must_run: boolean; // Report when no process matches
+  max_cpu?: number; // Percent of one core
+  cpu_for_secs: number; // How long a process has to stay above max_cpu
+  max_memory?: number; // Bytes
+  restart: boolean; // Relaunch the last matching process when it exits
+}
+
+export type WatchdogCondition = "not_running" | "high_cpu" | "high_memory";
+
+// Payload of "watchdog-triggered" and "watchdog-cleared"
+export interface WatchdogEvent {
+  rule_id: string;
+  condition: WatchdogCondition;
+  pid: number | null; // Of the offending process (null for not running)
+  name: string | null;
+  value: number; // CPU percent or memory bytes (0 for not running)
+  limit: number; // max_cpu or max_memory (0 for not running)
+  triggered_at: number; // Unix ms
+  cleared_at: number | null; // Unix ms (null while active)
+}
+
+// Payload of "watchdog-restarted"
+export interface WatchdogRestart {
+  rule_id: string;
+  command: string[];
+  pid: number | null; // Of the new process (null if it failed to start)
+  error: string | null; // Why it failed to start
+  attempt: number; // Restarts of the rule within the last hour, this one included
+  restarted_at: number; // Unix ms
 }
 
 // Sidecar status types - mirrors Rust SidecarStatusInfo
@@ -69,13 +398,57 @@ export type SidecarStatusType =
   | "stopped"
   | "error"
   | "requires_admin"
-  | "binary_not_found";
+  | "binary_not_found"
+  | "integrity_check_failed";
+
+export interface SidecarProcessRun {
+  pid: number;
+  started_at: number; // Unix timestamp in milliseconds
+  ended_at?: number; // Unset while running
+  exit_code?: number; // Unset while running or if killed by a signal
+  killed: boolean; // Stopped by the app rather than exiting on its own
+}
+
+export type SidecarErrorCode =
+  | "requires_admin"
+  | "binary_not_found"
+  | "spawn_failed"
+  | "collection_failed"
+  | "crashed"
+  | "fatal"
+  | "collector_unavailable"
+  | "collector_rejected"
+  | "integrity_check_failed"
+  | "unknown";
+
+export interface SidecarError {
+  code: SidecarErrorCode;
+  message: string;
+  retryable: boolean; // Whether restarting the sidecar can fix it
+}
 
 export interface SidecarStatusPayload {
   status: SidecarStatusType;
   message?: string; // Error message when status is "error"
   restart_count: number;
   can_restart: boolean;
+  error?: SidecarError; // Structured error while status is an error
+  pid?: number; // PID of the running sidecar process
+  runs: SidecarProcessRun[]; // Spawn/exit history, oldest first
+  recording?: string; // File raw sidecar output is recorded to
+  replaying: boolean; // Data comes from a recording, not LHM
+  collector?: string; // Endpoint when data comes from a running collector
+}
+
+// Sidecar log types - mirrors Rust LogEntry
+
+export type SidecarLogLevel = "debug" | "info" | "warn" | "error";
+
+export interface SidecarLogEntry {
+  timestamp: number; // Unix timestamp in milliseconds
+  level: SidecarLogLevel;
+  source: "stderr" | "supervisor";
+  message: string;
 }
 
 /**
@@ -106,9 +479,11 @@ export function getSidecarStatusMessage(status: SidecarStatusPayload): string {
         ? `Temperature monitoring stopped. Restarting... (${status.restart_count}/3)`
         : "Temperature monitoring unavailable";
     case "requires_admin":
-      return "Run as Administrator to enable temperature monitoring";
+      return "Allow administrator access to enable temperature monitoring";
     case "binary_not_found":
       return "Temperature monitoring component not found";
+    case "integrity_check_failed":
+      return "Temperature monitoring component was modified and was not started";
     case "error":
       return status.message || "Temperature monitoring error";
     default: